@@ -0,0 +1,957 @@
+//! 瞬剪 Instant Cut 命令行工具
+//!
+//! 与图形界面共用同一套探测、剪辑和版本化命名逻辑，适合在无界面的服务器上批量处理
+
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use instant_cut_core::error::AppResult;
+use instant_cut_core::animation::{self, AnimationOptions};
+use instant_cut_core::join::{self, JoinOptions, TimeRange};
+use instant_cut_core::audio::{self, AudioAdjustments, AudioOptions, Downmix};
+use instant_cut_core::correction::{self, CorrectionFilter};
+use instant_cut_core::container::normalize_container;
+use instant_cut_core::cropdetect;
+use instant_cut_core::loudness::{self, LoudnessPreset};
+use instant_cut_core::media;
+use instant_cut_core::output;
+use instant_cut_core::overlay::{self, ImageWatermark, OverlayPreset, Position, TextOverlay};
+use instant_cut_core::speed::{self, SpeedOptions};
+use instant_cut_core::still::{self, StillOptions};
+use instant_cut_core::subtitle::{self, SubtitleFormat, SubtitleSource};
+use instant_cut_core::settings::{self, Settings};
+use instant_cut_core::t;
+use instant_cut_core::timestamp::TimePoint;
+use instant_cut_core::transform::{self, CropRect, Flip, ScaleMode, TransformOptions};
+
+/// 校验未通过时的退出码（输出文件已写入）
+const EXIT_VERIFICATION_FAILED: u8 = 2;
+
+#[derive(Parser)]
+#[command(name = "instant-cut", version, about = "Probe and losslessly cut videos with versioned output names")]
+struct Cli {
+    /// Path to the ffmpeg binary (default: auto-detect)
+    #[arg(long, global = true)]
+    ffmpeg: Option<String>,
+
+    /// Path to the ffprobe binary (default: auto-detect)
+    #[arg(long, global = true)]
+    ffprobe: Option<String>,
+
+    /// Settings file of the desktop app, used to read overlay presets
+    #[arg(long, global = true)]
+    settings: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print video information as JSON
+    Probe {
+        /// Input video file
+        input: String,
+    },
+
+    /// Cut a time range into the next versioned file (exit code 2 if verification fails)
+    Cut {
+        /// Input video file
+        input: String,
+
+        /// Start time: seconds (12.5), [HH:]MM:SS[.sss], a frame number (1234f) or timecode (00:01:02:03, ; for drop-frame)
+        #[arg(long, allow_hyphen_values = true)]
+        start: TimePoint,
+
+        /// End time, in the same formats as --start
+        #[arg(long, allow_hyphen_values = true)]
+        end: TimePoint,
+
+        /// Notes appended to the output file name
+        #[arg(long)]
+        notes: Option<String>,
+
+        /// Output directory (default: next to the input)
+        #[arg(long)]
+        output_dir: Option<String>,
+
+        /// Output container, e.g. mp4 or mkv (default: same as the input)
+        #[arg(long)]
+        container: Option<String>,
+
+        /// Two-pass loudness normalization: streaming (-14 LUFS), podcast (-16) or broadcast (-23); re-encodes the audio only
+        #[arg(long)]
+        loudness: Option<LoudnessPreset>,
+
+        /// Mute the audio over the whole range
+        #[arg(long)]
+        mute: bool,
+
+        /// Audio gain in dB (negative values lower the volume)
+        #[arg(long, allow_hyphen_values = true)]
+        gain: Option<f64>,
+
+        /// Audio fade-in length in seconds at the start of the range
+        #[arg(long)]
+        fade_in: Option<f64>,
+
+        /// Audio fade-out length in seconds at the end of the range
+        #[arg(long)]
+        fade_out: Option<f64>,
+
+        /// Downmix the audio to stereo or mono
+        #[arg(long)]
+        downmix: Option<Downmix>,
+
+        /// Stream index of an audio track to remove; repeat for several tracks
+        #[arg(long = "drop-track")]
+        drop_tracks: Vec<u32>,
+
+        /// Fully decode the output to detect corruption
+        #[arg(long)]
+        verify_decode: bool,
+
+        /// Print the full result as JSON instead of the output path
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Join several ranges into the next versioned file, with optional fades and transitions (exit code 2 if verification fails)
+    Join {
+        /// Input video file
+        input: String,
+
+        /// Range as START..END, times in the same formats as `cut --start`; repeat in output order
+        #[arg(long = "range", required = true, allow_hyphen_values = true)]
+        ranges: Vec<TimeRange>,
+
+        /// Fade-in length in seconds at the start of the output (video and audio)
+        #[arg(long)]
+        fade_in: Option<f64>,
+
+        /// Fade-out length in seconds at the end of the output (video and audio)
+        #[arg(long)]
+        fade_out: Option<f64>,
+
+        /// Transition between consecutive ranges, e.g. fade, dissolve, wipeleft, slideright, circleopen
+        #[arg(long)]
+        transition: Option<String>,
+
+        /// Transition length in seconds (default: 1)
+        #[arg(long)]
+        transition_duration: Option<f64>,
+
+        /// Notes appended to the output file name
+        #[arg(long)]
+        notes: Option<String>,
+
+        /// Output directory (default: next to the input)
+        #[arg(long)]
+        output_dir: Option<String>,
+
+        /// Output container, e.g. mp4 or mkv (default: same as the input)
+        #[arg(long)]
+        container: Option<String>,
+
+        /// Print the full result as JSON instead of the output path
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Crop, scale, pad, rotate or flip a range (or the whole file) into the next versioned file (exit code 2 if verification fails)
+    Transform {
+        /// Input video file
+        input: String,
+
+        /// Start time, in the same formats as `cut --start` (default: start of the file)
+        #[arg(long, allow_hyphen_values = true)]
+        start: Option<TimePoint>,
+
+        /// End time, in the same formats as `cut --start` (default: end of the file)
+        #[arg(long, allow_hyphen_values = true)]
+        end: Option<TimePoint>,
+
+        /// Crop rectangle as W:H (centred) or W:H:X:Y, in displayed pixels
+        #[arg(long)]
+        crop: Option<CropRect>,
+
+        /// Output width; with only one of --width/--height the other keeps the aspect ratio
+        #[arg(long)]
+        width: Option<u32>,
+
+        /// Output height
+        #[arg(long)]
+        height: Option<u32>,
+
+        /// How to fit both --width and --height: fit (default), fill (crop the overflow) or stretch
+        #[arg(long, default_value = "fit")]
+        scale_mode: ScaleMode,
+
+        /// Pad (letterbox) to --width x --height with this colour, e.g. black or #202020
+        #[arg(long)]
+        pad: Option<String>,
+
+        /// Rotate clockwise by 90, 180 or 270 degrees; alone it only rewrites the display matrix of MP4/MOV
+        #[arg(long)]
+        rotate: Option<u32>,
+
+        /// Flip horizontal, vertical or both
+        #[arg(long)]
+        flip: Option<Flip>,
+
+        /// Notes appended to the output file name
+        #[arg(long)]
+        notes: Option<String>,
+
+        /// Output directory (default: next to the input)
+        #[arg(long)]
+        output_dir: Option<String>,
+
+        /// Output container, e.g. mp4 or mkv (default: same as the input)
+        #[arg(long)]
+        container: Option<String>,
+
+        /// Print the full result as JSON instead of the output path
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Speed up, slow down or reverse a range into the next versioned file (exit code 2 if verification fails)
+    Speed {
+        /// Input video file
+        input: String,
+
+        /// Start time, in the same formats as `cut --start`
+        #[arg(long, allow_hyphen_values = true)]
+        start: TimePoint,
+
+        /// End time, in the same formats as `cut --start`
+        #[arg(long, allow_hyphen_values = true)]
+        end: TimePoint,
+
+        /// Playback speed factor from 0.1 to 100, e.g. 8 for a timelapse or 0.5 for slow motion
+        #[arg(long)]
+        speed: Option<f64>,
+
+        /// Play the range backwards (rendered in memory-bounded chunks)
+        #[arg(long)]
+        reverse: bool,
+
+        /// Remove the audio instead of time-stretching it with atempo
+        #[arg(long)]
+        no_audio: bool,
+
+        /// Notes appended to the output file name
+        #[arg(long)]
+        notes: Option<String>,
+
+        /// Output directory (default: next to the input)
+        #[arg(long)]
+        output_dir: Option<String>,
+
+        /// Output container, e.g. mp4 or mkv (default: same as the input)
+        #[arg(long)]
+        container: Option<String>,
+
+        /// Print the full result as JSON instead of the output path
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Export a range with an image watermark and/or text overlay (exit code 2 if verification fails)
+    Overlay {
+        /// Input video file
+        input: String,
+
+        /// Start time, in the same formats as `cut --start`
+        #[arg(long, allow_hyphen_values = true)]
+        start: TimePoint,
+
+        /// End time, in the same formats as `cut --start`
+        #[arg(long, allow_hyphen_values = true)]
+        end: TimePoint,
+
+        /// Overlay preset saved in the settings file given by --settings
+        #[arg(long)]
+        preset: Option<String>,
+
+        /// Watermark image, e.g. a PNG with transparency (replaces the preset's watermark)
+        #[arg(long)]
+        logo: Option<String>,
+
+        /// Watermark position: top-left, top-right, bottom-left, bottom-right or center
+        #[arg(long, default_value = "bottom-right")]
+        logo_position: Position,
+
+        /// Watermark distance from the frame edges in pixels
+        #[arg(long, default_value_t = 24)]
+        logo_margin: u32,
+
+        /// Watermark width as a fraction of the frame width, e.g. 0.15 (default: original size)
+        #[arg(long)]
+        logo_scale: Option<f64>,
+
+        /// Watermark opacity from 0 to 1
+        #[arg(long, default_value_t = 1.0)]
+        opacity: f64,
+
+        /// Text template with {filename}, {name}, {notes} and a trailing {timecode} (replaces the preset's text)
+        #[arg(long)]
+        text: Option<String>,
+
+        /// Text position, same values as --logo-position
+        #[arg(long, default_value = "bottom-right")]
+        text_position: Position,
+
+        /// Text distance from the frame edges in pixels
+        #[arg(long, default_value_t = 24)]
+        text_margin: u32,
+
+        /// Font size in pixels
+        #[arg(long, default_value_t = 32)]
+        font_size: u32,
+
+        /// Font color, e.g. white, #ffcc00 or white@0.8
+        #[arg(long, default_value = "white")]
+        font_color: String,
+
+        /// Font file (default: chosen by fontconfig)
+        #[arg(long)]
+        font_file: Option<String>,
+
+        /// Background box color behind the text, e.g. black@0.5
+        #[arg(long)]
+        text_background: Option<String>,
+
+        /// Notes appended to the output file name (also available as {notes} in the text)
+        #[arg(long)]
+        notes: Option<String>,
+
+        /// Output directory (default: next to the input)
+        #[arg(long)]
+        output_dir: Option<String>,
+
+        /// Output container, e.g. mp4 or mkv (default: same as the input)
+        #[arg(long)]
+        container: Option<String>,
+
+        /// Print the full result as JSON instead of the output path
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Apply LUT, eq, denoise and deinterlace filters in the given order into the next versioned file (exit code 2 if verification fails)
+    Correct {
+        /// Input video file
+        input: String,
+
+        /// Start time, in the same formats as `cut --start` (default: start of the file)
+        #[arg(long, allow_hyphen_values = true)]
+        start: Option<TimePoint>,
+
+        /// End time, in the same formats as `cut --start` (default: end of the file)
+        #[arg(long, allow_hyphen_values = true)]
+        end: Option<TimePoint>,
+
+        /// Filter to apply, repeat in order: deinterlace[=yadif|bwdif], denoise[=STRENGTH], lut=FILE.cube,
+        /// eq=brightness=B:contrast=C:saturation=S:gamma=G
+        #[arg(long = "filter", required = true)]
+        filters: Vec<CorrectionFilter>,
+
+        /// Notes appended to the output file name
+        #[arg(long)]
+        notes: Option<String>,
+
+        /// Output directory (default: next to the input)
+        #[arg(long)]
+        output_dir: Option<String>,
+
+        /// Output container, e.g. mp4 or mkv (default: same as the input)
+        #[arg(long)]
+        container: Option<String>,
+
+        /// Print the full result as JSON instead of the output path
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Detect black bars with cropdetect and print the crop rectangle and confidence as JSON
+    Cropdetect {
+        /// Input video file
+        input: String,
+
+        /// Start time, in the same formats as `cut --start` (default: start of the file)
+        #[arg(long, allow_hyphen_values = true)]
+        start: Option<TimePoint>,
+
+        /// End time, in the same formats as `cut --start` (default: end of the file)
+        #[arg(long, allow_hyphen_values = true)]
+        end: Option<TimePoint>,
+    },
+
+    /// Export stills at one or more times into the next versioned files (one decode pass)
+    Still {
+        /// Input video file
+        input: String,
+
+        /// Time of a still, in the same formats as `cut --start`; repeat for several stills
+        #[arg(long = "at", required = true, allow_hyphen_values = true)]
+        times: Vec<TimePoint>,
+
+        /// Image format: png, jpg or webp (default: png)
+        #[arg(long)]
+        format: Option<String>,
+
+        /// Output width (keeps the aspect ratio if --height is not given)
+        #[arg(long)]
+        width: Option<u32>,
+
+        /// Output height (keeps the aspect ratio if --width is not given)
+        #[arg(long)]
+        height: Option<u32>,
+
+        /// Notes appended to the output file names
+        #[arg(long)]
+        notes: Option<String>,
+
+        /// Output directory (default: next to the input)
+        #[arg(long)]
+        output_dir: Option<String>,
+
+        /// Print the full result as JSON instead of the output paths
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Export a time range as an animated GIF (two-pass palette) or WebP into the next versioned file
+    Animate {
+        /// Input video file
+        input: String,
+
+        /// Start time, in the same formats as `cut --start`
+        #[arg(long, allow_hyphen_values = true)]
+        start: TimePoint,
+
+        /// End time, in the same formats as `cut --start`
+        #[arg(long, allow_hyphen_values = true)]
+        end: TimePoint,
+
+        /// Animation format: gif or webp (default: gif)
+        #[arg(long)]
+        format: Option<String>,
+
+        /// Output frame rate, 1-50 (default: 12)
+        #[arg(long)]
+        fps: Option<u32>,
+
+        /// Output width, never larger than the source (default: 480)
+        #[arg(long)]
+        width: Option<u32>,
+
+        /// Number of times the animation plays, 0 for infinite (default: 0)
+        #[arg(long = "loop")]
+        loop_count: Option<u32>,
+
+        /// GIF dither mode: sierra2_4a, sierra2, floyd_steinberg, bayer, heckbert or none (default: sierra2_4a)
+        #[arg(long)]
+        dither: Option<String>,
+
+        /// Notes appended to the output file name
+        #[arg(long)]
+        notes: Option<String>,
+
+        /// Output directory (default: next to the input)
+        #[arg(long)]
+        output_dir: Option<String>,
+
+        /// Only print the estimated size as JSON, without exporting
+        #[arg(long)]
+        estimate: bool,
+
+        /// Print the full result as JSON instead of the output path
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Extract the audio of a range (or the whole file) into the next versioned file
+    Audio {
+        /// Input video file
+        input: String,
+
+        /// Start time, in the same formats as `cut --start` (default: start of the file)
+        #[arg(long, allow_hyphen_values = true)]
+        start: Option<TimePoint>,
+
+        /// End time, in the same formats as `cut --start` (default: end of the file)
+        #[arg(long, allow_hyphen_values = true)]
+        end: Option<TimePoint>,
+
+        /// Audio format: m4a, ogg, mp3, flac, wav or opus (default: stream copy if possible, otherwise mp3)
+        #[arg(long)]
+        format: Option<String>,
+
+        /// Stream index of the audio track (default: the first audio stream)
+        #[arg(long)]
+        track: Option<u32>,
+
+        /// Bitrate in kbps for lossy formats; always re-encodes
+        #[arg(long)]
+        bitrate: Option<u32>,
+
+        /// Two-pass loudness normalization: streaming, podcast or broadcast; always re-encodes
+        #[arg(long)]
+        loudness: Option<LoudnessPreset>,
+
+        /// Notes appended to the output file name
+        #[arg(long)]
+        notes: Option<String>,
+
+        /// Output directory (default: next to the input)
+        #[arg(long)]
+        output_dir: Option<String>,
+
+        /// Only list the audio streams as JSON
+        #[arg(long)]
+        list: bool,
+
+        /// Print the full result as JSON instead of the output path
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Extract an embedded text subtitle stream as SRT or ASS into the next versioned file
+    Subtitles {
+        /// Input video file
+        input: String,
+
+        /// Start time, in the same formats as `cut --start`; subtitles are shifted to start at 0
+        #[arg(long, allow_hyphen_values = true)]
+        start: Option<TimePoint>,
+
+        /// End time, in the same formats as `cut --start` (default: end of the file)
+        #[arg(long, allow_hyphen_values = true)]
+        end: Option<TimePoint>,
+
+        /// Stream index of the subtitle track (default: the first subtitle stream)
+        #[arg(long)]
+        track: Option<u32>,
+
+        /// Subtitle format: srt or ass
+        #[arg(long, default_value = "srt")]
+        format: SubtitleFormat,
+
+        /// Notes appended to the output file name
+        #[arg(long)]
+        notes: Option<String>,
+
+        /// Output directory (default: next to the input)
+        #[arg(long)]
+        output_dir: Option<String>,
+
+        /// Only list the subtitle streams as JSON
+        #[arg(long)]
+        list: bool,
+
+        /// Print the full result as JSON instead of the output path
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Burn a subtitle track or file into a range of the video (exit code 2 if verification fails)
+    Burn {
+        /// Input video file
+        input: String,
+
+        /// Start time, in the same formats as `cut --start`
+        #[arg(long, allow_hyphen_values = true)]
+        start: TimePoint,
+
+        /// End time, in the same formats as `cut --start`
+        #[arg(long, allow_hyphen_values = true)]
+        end: TimePoint,
+
+        /// Stream index of an embedded subtitle track
+        #[arg(long, conflicts_with = "file", required_unless_present = "file")]
+        track: Option<u32>,
+
+        /// External SRT or ASS file
+        #[arg(long)]
+        file: Option<String>,
+
+        /// Notes appended to the output file name
+        #[arg(long)]
+        notes: Option<String>,
+
+        /// Output directory (default: next to the input)
+        #[arg(long)]
+        output_dir: Option<String>,
+
+        /// Output container, e.g. mp4 or mkv (default: same as the input)
+        #[arg(long)]
+        container: Option<String>,
+
+        /// Print the full result as JSON instead of the output path
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Measure integrated loudness, true peak and loudness range as JSON
+    Loudness {
+        /// Input video file
+        input: String,
+
+        /// Start time, in the same formats as `cut --start` (default: start of the file)
+        #[arg(long, allow_hyphen_values = true)]
+        start: Option<TimePoint>,
+
+        /// End time, in the same formats as `cut --start` (default: end of the file)
+        #[arg(long, allow_hyphen_values = true)]
+        end: Option<TimePoint>,
+
+        /// Stream index of the audio track (default: the first audio stream)
+        #[arg(long)]
+        track: Option<u32>,
+
+        /// Target preset used to compute the offset: streaming, podcast or broadcast
+        #[arg(long, default_value = "podcast")]
+        target: LoudnessPreset,
+    },
+
+    /// List versions derived from a file
+    Versions {
+        /// Input video file
+        input: String,
+
+        /// Directory to search (default: next to the input)
+        #[arg(long)]
+        output_dir: Option<String>,
+
+        /// Container extension to match (default: same as the input)
+        #[arg(long)]
+        container: Option<String>,
+
+        /// Print the path the next cut would use instead of listing versions
+        #[arg(long)]
+        next: bool,
+
+        /// Notes used when computing the next path
+        #[arg(long)]
+        notes: Option<String>,
+
+        /// Print the versions as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    // 命令行参数指定的可执行文件路径只在本次运行中生效
+    let applied = cli.settings.as_deref()
+        .map(load_overlay_presets)
+        .transpose()
+        .and_then(|presets| settings::update(Settings {
+            locale: None,
+            ffmpeg_path: cli.ffmpeg.clone(),
+            ffprobe_path: cli.ffprobe.clone(),
+            overlay_presets: presets.unwrap_or_default(),
+        }));
+
+    // 记录本次运行的临时输出，并清理之前中断的运行（进程已退出）遗留的临时文件；
+    // 指定了桌面版的设置文件时与桌面版共用同一个日志
+    let journal_dir = match cli.settings.as_deref().and_then(|p| Path::new(p).parent()) {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => std::env::temp_dir().join("instant-cut"),
+    };
+    if let Err(e) = output::init_temp_journal(&journal_dir) {
+        eprintln!("{}", t!("app.temp_cleanup_failed", error = e));
+    }
+
+    match applied.and_then(|_| run(cli.command)) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command) -> AppResult<ExitCode> {
+    match command {
+        Command::Probe { input } => {
+            let info = media::get_video_info(&input)?;
+            print_json(&info)?;
+        }
+
+        Command::Cut {
+            input, start, end, notes, output_dir, container, loudness, mute, gain, fade_in, fade_out, downmix,
+            drop_tracks, verify_decode, json
+        } => {
+            let audio = AudioAdjustments { mute, gain_db: gain, fade_in, fade_out, downmix, drop_tracks, loudness };
+            let result = media::cut_video(
+                &input, &start, &end, notes.as_deref(), output_dir.as_deref(), container.as_deref(),
+                &audio, verify_decode
+            )?;
+
+            if json {
+                print_json(&result)?;
+            } else {
+                println!("{}", result.output_path);
+                for skipped in &result.skipped_subtitles {
+                    eprintln!("{}", skipped.reason);
+                }
+            }
+
+            if !result.verification.passed {
+                eprintln!("{}", result.message);
+                return Ok(ExitCode::from(EXIT_VERIFICATION_FAILED));
+            }
+        }
+
+        Command::Join {
+            input, ranges, fade_in, fade_out, transition, transition_duration, notes, output_dir, container, json
+        } => {
+            let options = JoinOptions { fade_in, fade_out, transition, transition_duration };
+            let result = join::export_ranges(
+                &input, &ranges, &options, notes.as_deref(), output_dir.as_deref(), container.as_deref()
+            )?;
+
+            if json {
+                print_json(&result)?;
+            } else {
+                println!("{}", result.output_path);
+            }
+
+            if !result.verification.passed {
+                eprintln!("{}", result.message);
+                return Ok(ExitCode::from(EXIT_VERIFICATION_FAILED));
+            }
+        }
+
+        Command::Transform {
+            input, start, end, crop, width, height, scale_mode, pad, rotate, flip, notes, output_dir, container, json
+        } => {
+            let options = TransformOptions { crop, width, height, scale_mode, pad_color: pad, rotate, flip };
+            let result = transform::transform_video(
+                &input, start.as_ref(), end.as_ref(), &options, notes.as_deref(), output_dir.as_deref(),
+                container.as_deref()
+            )?;
+
+            if json {
+                print_json(&result)?;
+            } else {
+                println!("{}", result.output_path);
+            }
+
+            if !result.verification.passed || !result.dimensions_ok {
+                eprintln!("{}", result.message);
+                return Ok(ExitCode::from(EXIT_VERIFICATION_FAILED));
+            }
+        }
+
+        Command::Speed { input, start, end, speed, reverse, no_audio, notes, output_dir, container, json } => {
+            let options = SpeedOptions { speed, reverse, drop_audio: no_audio };
+            let result = speed::export_speed(
+                &input, &start, &end, &options, notes.as_deref(), output_dir.as_deref(), container.as_deref()
+            )?;
+
+            if json {
+                print_json(&result)?;
+            } else {
+                println!("{}", result.output_path);
+            }
+
+            if !result.verification.passed {
+                eprintln!("{}", result.message);
+                return Ok(ExitCode::from(EXIT_VERIFICATION_FAILED));
+            }
+        }
+
+        Command::Overlay {
+            input, start, end, preset, logo, logo_position, logo_margin, logo_scale, opacity, text, text_position,
+            text_margin, font_size, font_color, font_file, text_background, notes, output_dir, container, json
+        } => {
+            let mut options = preset.as_deref().map(overlay::find_preset).transpose()?.unwrap_or_default();
+            if let Some(path) = logo {
+                options.watermark = Some(ImageWatermark {
+                    path, position: logo_position, margin: logo_margin, scale: logo_scale, opacity
+                });
+            }
+            if let Some(text) = text {
+                options.text = Some(TextOverlay {
+                    text, position: text_position, margin: text_margin, font_size, font_color, font_file,
+                    background: text_background,
+                });
+            }
+            let result = overlay::export_overlay(
+                &input, &start, &end, &options, notes.as_deref(), output_dir.as_deref(), container.as_deref()
+            )?;
+
+            if json {
+                print_json(&result)?;
+            } else {
+                println!("{}", result.output_path);
+            }
+
+            if !result.verification.passed {
+                eprintln!("{}", result.message);
+                return Ok(ExitCode::from(EXIT_VERIFICATION_FAILED));
+            }
+        }
+
+        Command::Correct { input, start, end, filters, notes, output_dir, container, json } => {
+            let result = correction::correct_video(
+                &input, start.as_ref(), end.as_ref(), &filters, notes.as_deref(), output_dir.as_deref(),
+                container.as_deref()
+            )?;
+
+            if json {
+                print_json(&result)?;
+            } else {
+                println!("{}", result.output_path);
+            }
+
+            if !result.verification.passed {
+                eprintln!("{}", result.message);
+                return Ok(ExitCode::from(EXIT_VERIFICATION_FAILED));
+            }
+        }
+
+        Command::Cropdetect { input, start, end } => {
+            let detection = cropdetect::detect_crop(&input, start.as_ref(), end.as_ref())?;
+            print_json(&detection)?;
+        }
+
+        Command::Still { input, times, format, width, height, notes, output_dir, json } => {
+            let options = StillOptions { format, width, height };
+            let result = still::export_stills(&input, &times, &options, notes.as_deref(), output_dir.as_deref())?;
+
+            if json {
+                print_json(&result)?;
+            } else {
+                for path in &result.output_paths {
+                    println!("{}", path);
+                }
+            }
+        }
+
+        Command::Animate {
+            input, start, end, format, fps, width, loop_count, dither, notes, output_dir, estimate, json
+        } => {
+            let options = AnimationOptions { format, fps, width, loop_count, dither };
+            if estimate {
+                print_json(&animation::estimate_animation(&input, &start, &end, &options)?)?;
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let result = animation::export_animation(
+                &input, &start, &end, &options, notes.as_deref(), output_dir.as_deref()
+            )?;
+            if json {
+                print_json(&result)?;
+            } else {
+                println!("{}", result.output_path);
+            }
+
+            if !result.matches_estimate {
+                eprintln!("{}", result.message);
+                return Ok(ExitCode::from(EXIT_VERIFICATION_FAILED));
+            }
+        }
+
+        Command::Audio { input, start, end, format, track, bitrate, loudness, notes, output_dir, list, json } => {
+            if list {
+                print_json(&media::get_audio_streams(&input)?)?;
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let options = AudioOptions { format, track, bitrate, loudness };
+            let result = audio::extract_audio(
+                &input, start.as_ref(), end.as_ref(), &options, notes.as_deref(), output_dir.as_deref()
+            )?;
+            if json {
+                print_json(&result)?;
+            } else {
+                println!("{}", result.output_path);
+            }
+        }
+
+        Command::Subtitles { input, start, end, track, format, notes, output_dir, list, json } => {
+            if list {
+                print_json(&media::get_subtitle_streams(&input)?)?;
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let result = subtitle::extract_subtitles(
+                &input, start.as_ref(), end.as_ref(), track, format, notes.as_deref(), output_dir.as_deref()
+            )?;
+            if json {
+                print_json(&result)?;
+            } else {
+                println!("{}", result.output_path);
+            }
+        }
+
+        Command::Burn { input, start, end, track, file, notes, output_dir, container, json } => {
+            let source = match (track, file) {
+                (Some(track), _) => SubtitleSource::Track(track),
+                (None, file) => SubtitleSource::File(file.unwrap_or_default()),
+            };
+            let result = subtitle::burn_subtitles(
+                &input, &start, &end, &source, notes.as_deref(), output_dir.as_deref(), container.as_deref()
+            )?;
+
+            if json {
+                print_json(&result)?;
+            } else {
+                println!("{}", result.output_path);
+            }
+
+            if !result.verification.passed {
+                eprintln!("{}", result.message);
+                return Ok(ExitCode::from(EXIT_VERIFICATION_FAILED));
+            }
+        }
+
+        Command::Loudness { input, start, end, track, target } => {
+            let report = loudness::measure_loudness(&input, start.as_ref(), end.as_ref(), track, target)?;
+            print_json(&report)?;
+        }
+
+        Command::Versions { input, output_dir, container, next, notes, json } => {
+            let output_dir = output_dir.as_deref().map(Path::new);
+            // 与剪辑一致：`.MP4` 等写法规范化为扩展名
+            let container = container.as_deref().map(normalize_container).transpose()?;
+            if next {
+                let path = media::generate_next_filename(&input, notes.as_deref(), output_dir, container.as_deref())?;
+                println!("{}", path.display());
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let versions = media::list_versions(&input, output_dir, container.as_deref())?;
+            if json {
+                print_json(&versions)?;
+            } else {
+                for entry in versions {
+                    println!("{}", entry.path);
+                }
+            }
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// 读取桌面版设置文件中的叠加预设
+fn load_overlay_presets(path: &str) -> AppResult<Vec<OverlayPreset>> {
+    let settings: Settings = serde_json::from_str(&fs::read_to_string(path)?)?;
+    Ok(settings.overlay_presets)
+}
+
+fn print_json<T: Serialize>(value: &T) -> AppResult<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
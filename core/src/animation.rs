@@ -0,0 +1,517 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use serde::{Deserialize, Serialize};
+use crate::backend::{MediaBackend, ProcessBackend};
+use serde_json::Value;
+use crate::error::{AppResult, ffprobe_error, path_error, validation_error};
+use crate::media::{check_disk_space_for_output, get_video_info_with, reserve_next_output, resolve_range_with, ResolvedRange};
+use crate::still::target_size;
+use crate::timestamp::{Rational, TimePoint};
+use crate::utils::{check_command_success, validate_input_path, validate_output_dir};
+use crate::video::{AnimationEstimate, AnimationResult};
+use crate::t;
+
+/// 支持的动图格式
+pub const ANIMATION_FORMATS: &[&str] = &["gif", "webp"];
+
+/// paletteuse 支持的抖动算法
+pub const DITHER_MODES: &[&str] = &["sierra2_4a", "sierra2", "floyd_steinberg", "bayer", "heckbert", "none"];
+
+/// 未指定格式时使用 GIF
+const DEFAULT_FORMAT: &str = "gif";
+
+/// 默认帧率
+const DEFAULT_FPS: u32 = 12;
+
+/// 允许的帧率范围
+const MIN_FPS: u32 = 1;
+const MAX_FPS: u32 = 50;
+
+/// 默认输出宽度，源视频更窄时不放大
+const DEFAULT_WIDTH: u32 = 480;
+
+/// 默认抖动算法
+const DEFAULT_DITHER: &str = "sierra2_4a";
+
+/// 估算大小时每千像素帧的字节数：GIF 约 0.15 字节/像素，动态 WebP 约 0.05 字节/像素
+const GIF_BYTES_PER_KILO_PIXEL: u64 = 150;
+const WEBP_BYTES_PER_KILO_PIXEL: u64 = 50;
+
+/// 输出帧数与估算值允许的误差（fps 滤镜在范围两端的取整）
+const FRAME_TOLERANCE: u64 = 1;
+
+/// 动态 WebP 的编码质量
+const WEBP_QUALITY: &str = "75";
+
+/// 调色板临时文件的序号，避免并发导出互相覆盖
+static PALETTE_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// 动图导出选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnimationOptions {
+    pub format: Option<String>,     // gif（默认）/ webp
+    pub fps: Option<u32>,           // 输出帧率，默认 12
+    pub width: Option<u32>,         // 输出宽度，默认 480，高度按比例缩放
+    pub loop_count: Option<u32>,    // 播放次数，0（默认）为无限循环
+    pub dither: Option<String>,     // GIF 的抖动算法，默认 sierra2_4a
+}
+
+/// 校验并补全默认值后的选项
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Settings {
+    format: String,
+    fps: u32,
+    width: u32,
+    loop_count: u32,
+    dither: String,
+}
+
+impl AnimationOptions {
+    /// 校验选项，未指定的项使用默认值
+    fn settings(&self, source_width: u32) -> AppResult<Settings> {
+        let format = normalize_animation_format(self.format.as_deref().unwrap_or(DEFAULT_FORMAT))?;
+
+        let fps = self.fps.unwrap_or(DEFAULT_FPS);
+        if !(MIN_FPS..=MAX_FPS).contains(&fps) {
+            return Err(validation_error(t!("animation.invalid_fps", min = MIN_FPS, max = MAX_FPS)));
+        }
+
+        if self.width == Some(0) {
+            return Err(validation_error(t!("animation.invalid_width")));
+        }
+        let width = self.width.unwrap_or(DEFAULT_WIDTH).min(source_width.max(1));
+
+        let dither = self.dither.as_deref().unwrap_or(DEFAULT_DITHER).trim().to_ascii_lowercase();
+        if !DITHER_MODES.contains(&dither.as_str()) {
+            return Err(validation_error(t!("animation.dither_unsupported", dither = dither)));
+        }
+
+        Ok(Settings { format, fps, width, loop_count: self.loop_count.unwrap_or(0), dither })
+    }
+}
+
+impl Settings {
+    /// 播放次数换算为各格式 `-loop` 的取值
+    ///
+    /// GIF 的 `-loop` 为额外重复次数（-1 表示只播放一次），WebP 的为总播放次数，两者 0 都是无限循环
+    fn loop_arg(&self) -> String {
+        match (self.format.as_str(), self.loop_count) {
+            ("gif", 0) => "0".to_string(),
+            ("gif", 1) => "-1".to_string(),
+            ("gif", plays) => (plays - 1).to_string(),
+            (_, plays) => plays.to_string(),
+        }
+    }
+}
+
+/// 规范化动图格式 (例如 ".GIF" -> "gif")，并确认其受支持
+pub fn normalize_animation_format(format: &str) -> AppResult<String> {
+    let normalized = format.trim().trim_start_matches('.').to_ascii_lowercase();
+    if !ANIMATION_FORMATS.contains(&normalized.as_str()) {
+        return Err(validation_error(t!("animation.format_unsupported", format = format)));
+    }
+    Ok(normalized)
+}
+
+/// 估算将时间范围导出为动图后的尺寸、帧数和文件大小
+pub fn estimate_animation(
+    input_path: &str,
+    start: &TimePoint,
+    end: &TimePoint,
+    options: &AnimationOptions
+) -> AppResult<AnimationEstimate> {
+    estimate_animation_with(&ProcessBackend, input_path, start, end, options)
+}
+
+/// 使用指定后端估算动图大小，参数含义同 [`estimate_animation`]
+pub fn estimate_animation_with(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    start: &TimePoint,
+    end: &TimePoint,
+    options: &AnimationOptions
+) -> AppResult<AnimationEstimate> {
+    validate_input_path(input_path)?;
+    let range = resolve_range_with(backend, input_path, start, end)?;
+    let (_, estimate) = prepare(backend, input_path, &range, options)?;
+    Ok(estimate)
+}
+
+/// 将时间范围导出为动图 (GIF 或动态 WebP)，输出到下一个版本文件
+///
+/// GIF 先用 palettegen 为该范围生成调色板，再用 paletteuse 编码，画质明显优于默认调色板
+pub fn export_animation(
+    input_path: &str,
+    start: &TimePoint,
+    end: &TimePoint,
+    options: &AnimationOptions,
+    notes: Option<&str>,
+    output_dir: Option<&str>
+) -> AppResult<AnimationResult> {
+    export_animation_with(&ProcessBackend, input_path, start, end, options, notes, output_dir)
+}
+
+/// 使用指定后端导出动图，参数含义同 [`export_animation`]
+pub fn export_animation_with(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    start: &TimePoint,
+    end: &TimePoint,
+    options: &AnimationOptions,
+    notes: Option<&str>,
+    output_dir: Option<&str>
+) -> AppResult<AnimationResult> {
+    // 验证输入路径和输出目录
+    validate_input_path(input_path)?;
+    let output_dir = output_dir
+        .map(validate_output_dir)
+        .transpose()?;
+
+    // 换算并验证时间范围，校验选项
+    let range = resolve_range_with(backend, input_path, start, end)?;
+    let (settings, estimate) = prepare(backend, input_path, &range, options)?;
+
+    // 预留输出文件并检查磁盘空间
+    let reservation = reserve_next_output(input_path, notes, output_dir.as_deref(), Some(&settings.format))?;
+    check_disk_space_for_output(reservation.final_path(), estimate.estimated_bytes)?;
+    let output = reservation.temp_path().to_str()
+        .ok_or_else(|| path_error(t!("path.not_utf8")))?;
+
+    if settings.format == "gif" {
+        // 第一遍：生成调色板
+        let palette = PaletteFile::new();
+        let palette_path = palette.0.to_str()
+            .ok_or_else(|| path_error(t!("path.not_utf8")))?;
+        run_ffmpeg(backend, palette_args(input_path, &range, &settings, palette_path))?;
+
+        // 第二遍：使用调色板编码
+        run_ffmpeg(backend, gif_args(input_path, &range, &settings, palette_path, output))?;
+    } else {
+        run_ffmpeg(backend, webp_args(input_path, &range, &settings, output))?;
+    }
+
+    // 检查临时文件并重命名为最终文件名
+    let output_path = reservation.commit()?.to_string_lossy().to_string();
+
+    // 探测输出，尺寸和帧数应与估算一致
+    let (width, height, frames) = probe_animation(backend, &output_path)?;
+    let matches_estimate = (width, height) == (estimate.width, estimate.height)
+        && frames.abs_diff(estimate.frames) <= FRAME_TOLERANCE;
+
+    let message = if matches_estimate {
+        t!("animation.success", path = output_path)
+    } else {
+        t!("animation.mismatch", path = output_path,
+            actual = format!("{}x{}, {} frames", width, height, frames),
+            expected = format!("{}x{}, {} frames", estimate.width, estimate.height, estimate.frames))
+    };
+
+    Ok(AnimationResult {
+        output_path,
+        message,
+        estimate,
+        width,
+        height,
+        frames,
+        matches_estimate,
+    })
+}
+
+/// 探测动图的尺寸和帧数（按数据包计数，无需解码）
+fn probe_animation(backend: &dyn MediaBackend, path: &str) -> AppResult<(u32, u32, u64)> {
+    let output = backend.ffprobe(&[
+        "-v", "quiet",
+        "-print_format", "json",
+        "-count_packets",
+        "-select_streams", "v:0",
+        "-show_entries", "stream=width,height,nb_read_packets",
+        path
+    ])?;
+    check_command_success(&output, "ffprobe")?;
+
+    let data: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| ffprobe_error(t!("probe.json_invalid", error = e)))?;
+    let stream = &data["streams"][0];
+    if !stream.is_object() {
+        return Err(ffprobe_error(t!("probe.no_video_stream")));
+    }
+
+    let dimension = |key: &str| stream[key].as_u64().unwrap_or(0) as u32;
+    let frames = stream["nb_read_packets"].as_str()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(0);
+    Ok((dimension("width"), dimension("height"), frames))
+}
+
+/// 校验选项并估算输出
+fn prepare(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    range: &ResolvedRange,
+    options: &AnimationOptions
+) -> AppResult<(Settings, AnimationEstimate)> {
+    let info = get_video_info_with(backend, input_path)?;
+    // ffmpeg 默认按显示矩阵自动旋转，估算和宽度上限都按显示尺寸计算
+    let (source_width, source_height) = info.display_size();
+    let settings = options.settings(source_width)?;
+    let (width, height) = target_size(source_width, source_height, Some(settings.width), None);
+
+    let frames = (range.duration() * Rational::from_integer(settings.fps as i64)).to_f64().ceil().max(1.0) as u64;
+    let bytes_per_kilo_pixel = if settings.format == "gif" { GIF_BYTES_PER_KILO_PIXEL } else { WEBP_BYTES_PER_KILO_PIXEL };
+    let estimated_bytes = width as u64 * height as u64 * frames * bytes_per_kilo_pixel / 1000;
+
+    Ok((settings, AnimationEstimate { width, height, frames, estimated_bytes }))
+}
+
+fn run_ffmpeg(backend: &dyn MediaBackend, args: Vec<String>) -> AppResult<()> {
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    let output = backend.ffmpeg(&args)?;
+    check_command_success(&output, "ffmpeg")
+}
+
+/// 调色板临时文件，离开作用域时删除
+struct PaletteFile(PathBuf);
+
+impl PaletteFile {
+    fn new() -> Self {
+        let id = PALETTE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        Self(std::env::temp_dir().join(format!("instant-cut-palette-{}-{}.png", std::process::id(), id)))
+    }
+}
+
+impl Drop for PaletteFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// 输入参数：先定位再限制时长，只解码所选范围
+fn input_args(input_path: &str, range: &ResolvedRange) -> Vec<String> {
+    vec![
+        "-y".into(),
+        "-ss".into(), range.start.to_ffmpeg_seconds(),
+        "-t".into(), range.duration().to_ffmpeg_seconds(),
+        "-i".into(), input_path.into(),
+    ]
+}
+
+/// 帧率和缩放滤镜
+fn scale_chain(settings: &Settings) -> String {
+    format!("fps={},scale={}:-1:flags=lanczos", settings.fps, settings.width)
+}
+
+/// GIF 第一遍：只统计帧间变化的部分生成调色板，动态画面的颜色更准确
+fn palette_args(input_path: &str, range: &ResolvedRange, settings: &Settings, palette: &str) -> Vec<String> {
+    let mut args = input_args(input_path, range);
+    args.extend([
+        "-vf".into(), format!("{},palettegen=stats_mode=diff", scale_chain(settings)),
+        "-update".into(), "1".into(),
+        palette.into(),
+    ]);
+    args
+}
+
+/// GIF 第二遍：只重绘变化的矩形区域，减小文件体积
+fn gif_args(input_path: &str, range: &ResolvedRange, settings: &Settings, palette: &str, output: &str) -> Vec<String> {
+    let mut args = input_args(input_path, range);
+    args.extend([
+        "-i".into(), palette.into(),
+        "-lavfi".into(), format!(
+            "{}[x];[x][1:v]paletteuse=dither={}:diff_mode=rectangle", scale_chain(settings), settings.dither
+        ),
+        "-loop".into(), settings.loop_arg(),
+        output.into(),
+    ]);
+    args
+}
+
+/// 动态 WebP：单遍有损编码
+fn webp_args(input_path: &str, range: &ResolvedRange, settings: &Settings, output: &str) -> Vec<String> {
+    let mut args = input_args(input_path, range);
+    args.extend([
+        "-vf".into(), scale_chain(settings),
+        "-an".into(),
+        "-c:v".into(), "libwebp".into(),
+        "-quality".into(), WEBP_QUALITY.into(),
+        "-loop".into(), settings.loop_arg(),
+        output.into(),
+    ]);
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::{Path, PathBuf};
+    use crate::backend::{CommandOutput, MockBackend};
+    use crate::binaries::Tool;
+
+    fn mock_source(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("instant-cut-animation-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("clip.mp4");
+        fs::write(&input, b"video").unwrap();
+        (dir, input)
+    }
+
+    /// 源文件探测三次（时钟、时长、尺寸），之后探测输出：480x270、24 帧
+    fn mock_backend() -> MockBackend {
+        mock_backend_with_rotation(0)
+    }
+
+    /// 源文件为带显示矩阵旋转（逆时针角度）的 1920x1080 视频
+    fn mock_backend_with_rotation(rotation: i32) -> MockBackend {
+        let mock = MockBackend::new();
+        let source = CommandOutput::ok(serde_json::json!({
+            "streams": [{ "codec_type": "video", "codec_name": "h264", "width": 1920, "height": 1080,
+                          "r_frame_rate": "25/1", "time_base": "1/12800",
+                          "side_data_list": [{ "side_data_type": "Display Matrix", "rotation": rotation }] }],
+            "format": { "duration": "10.0", "format_name": "mov,mp4,m4a,3gp,3g2,mj2" },
+        }).to_string());
+        for _ in 0..3 {
+            mock.respond(Tool::FFprobe, source.clone());
+        }
+        mock.set_default(Tool::FFprobe, CommandOutput::ok(serde_json::json!({
+            "streams": [{ "width": 480, "height": 270, "nb_read_packets": "24" }],
+        }).to_string()));
+        mock.set_output_contents(b"animation");
+        mock
+    }
+
+    #[test]
+    fn test_settings_defaults_and_validation() {
+        let settings = AnimationOptions::default().settings(1920).unwrap();
+        assert_eq!(settings, Settings {
+            format: "gif".to_string(), fps: 12, width: 480, loop_count: 0, dither: "sierra2_4a".to_string(),
+        });
+
+        // 不放大窄于默认宽度的源视频
+        assert_eq!(AnimationOptions::default().settings(320).unwrap().width, 320);
+
+        let invalid = [
+            AnimationOptions { format: Some("apng".to_string()), ..Default::default() },
+            AnimationOptions { fps: Some(0), ..Default::default() },
+            AnimationOptions { fps: Some(60), ..Default::default() },
+            AnimationOptions { width: Some(0), ..Default::default() },
+            AnimationOptions { dither: Some("random".to_string()), ..Default::default() },
+        ];
+        for options in invalid {
+            assert!(options.settings(1920).is_err(), "{:?}", options);
+        }
+    }
+
+    #[test]
+    fn test_loop_count_is_number_of_plays() {
+        let loop_arg = |format: &str, plays: u32| AnimationOptions {
+            format: Some(format.to_string()), loop_count: Some(plays), ..Default::default()
+        }.settings(1920).unwrap().loop_arg();
+        assert_eq!(["gif", "webp"].map(|f| loop_arg(f, 0)), ["0", "0"]);
+        assert_eq!(["gif", "webp"].map(|f| loop_arg(f, 1)), ["-1", "1"]);
+        assert_eq!(["gif", "webp"].map(|f| loop_arg(f, 3)), ["2", "3"]);
+    }
+
+    #[test]
+    fn test_estimate_animation_with_mock() {
+        let (dir, input) = mock_source("estimate");
+        let mock = mock_backend();
+
+        let options = AnimationOptions { fps: Some(10), width: Some(640), ..Default::default() };
+        let estimate = estimate_animation_with(
+            &mock, input.to_str().unwrap(), &TimePoint::seconds(1.0), &TimePoint::seconds(3.5), &options
+        ).unwrap();
+        assert_eq!(estimate, AnimationEstimate { width: 640, height: 360, frames: 25, estimated_bytes: 864_000 });
+        assert!(mock.calls_to(Tool::FFmpeg).is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_estimate_animation_uses_display_size() {
+        let (dir, input) = mock_source("rotated");
+        let mock = mock_backend_with_rotation(-90);
+
+        // 竖屏手机视频：编码尺寸 1920x1080，显示为 1080x1920
+        let estimate = estimate_animation_with(
+            &mock, input.to_str().unwrap(), &TimePoint::seconds(0.0), &TimePoint::seconds(1.0), &AnimationOptions::default()
+        ).unwrap();
+        assert_eq!((estimate.width, estimate.height), (480, 853));
+
+        // 宽度上限按显示宽度计算
+        let mock = mock_backend_with_rotation(90);
+        let options = AnimationOptions { width: Some(1920), ..Default::default() };
+        let estimate = estimate_animation_with(
+            &mock, input.to_str().unwrap(), &TimePoint::seconds(0.0), &TimePoint::seconds(1.0), &options
+        ).unwrap();
+        assert_eq!((estimate.width, estimate.height), (1080, 1920));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_export_gif_uses_two_passes() {
+        let (dir, input) = mock_source("gif");
+        let mock = mock_backend();
+
+        let options = AnimationOptions { dither: Some("bayer".to_string()), loop_count: Some(2), ..Default::default() };
+        let result = export_animation_with(
+            &mock, input.to_str().unwrap(), &"25f".parse().unwrap(), &TimePoint::seconds(3.0), &options, None, None
+        ).unwrap();
+        assert_eq!(result.output_path, dir.join("clip_1.gif").to_string_lossy());
+        assert!(dir.join("clip_1.gif").exists());
+        assert!(result.matches_estimate, "{}", result.message);
+
+        let calls = mock.calls_to(Tool::FFmpeg);
+        assert_eq!(calls.len(), 2);
+        let palette = calls[0].last().unwrap();
+        assert_eq!(calls[0][..9], ["-y", "-ss", "1", "-t", "2", "-i", input.to_str().unwrap(), "-vf",
+            "fps=12,scale=480:-1:flags=lanczos,palettegen=stats_mode=diff"]);
+        assert_eq!(calls[1][7..], [
+            "-i", palette.as_str(),
+            "-lavfi", "fps=12,scale=480:-1:flags=lanczos[x];[x][1:v]paletteuse=dither=bayer:diff_mode=rectangle",
+            "-loop", "1", calls[1].last().unwrap().as_str(),
+        ]);
+        assert!(!Path::new(palette).exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_export_webp_single_pass() {
+        let (dir, input) = mock_source("webp");
+        let mock = mock_backend();
+
+        let options = AnimationOptions { format: Some("WebP".to_string()), ..Default::default() };
+        let result = export_animation_with(
+            &mock, input.to_str().unwrap(), &TimePoint::seconds(0.0), &TimePoint::seconds(2.0), &options, Some("loop"), None
+        ).unwrap();
+        assert_eq!(result.output_path, dir.join("clip_1_loop.webp").to_string_lossy());
+
+        assert!(result.matches_estimate, "{}", result.message);
+
+        let calls = mock.calls_to(Tool::FFmpeg);
+        assert_eq!(calls.len(), 1);
+        assert!(calls[0].windows(2).any(|w| w == ["-c:v", "libwebp"]));
+        assert!(calls[0].windows(2).any(|w| w == ["-loop", "0"]));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_export_animation_rejects_invalid_range() {
+        let (dir, input) = mock_source("range");
+        let mock = mock_backend();
+
+        let err = export_animation_with(
+            &mock, input.to_str().unwrap(), &TimePoint::seconds(5.0), &TimePoint::seconds(12.0),
+            &AnimationOptions::default(), None, None
+        ).unwrap_err();
+        assert!(matches!(err, crate::error::AppError::ValidationError(_)));
+        assert!(mock.calls_to(Tool::FFmpeg).is_empty());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,528 @@
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::backend::{MediaBackend, ProcessBackend};
+use crate::error::{AppError, AppResult, path_error, validation_error};
+use crate::loudness::{measure_stream_with, LoudnessPreset};
+use crate::media::{check_disk_space_for_output, get_audio_streams_with, get_video_duration_with, reserve_next_output, resolve_optional_range_with, ResolvedRange};
+use crate::timestamp::{Rational, TimePoint};
+use crate::utils::{check_command_success, validate_input_path, validate_output_dir};
+use crate::video::{AudioResult, AudioStream};
+use crate::t;
+
+/// 音频输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioFormat {
+    pub ext: &'static str,
+    pub encoder: &'static str,              // 转码时使用的 ffmpeg 编码器
+    pub codec: &'static str,                // 编码器输出的编码名称
+    pub copy_codecs: &'static [&'static str],   // 可直接流复制到该格式的编码
+    pub default_bitrate: Option<u32>,       // 默认码率 (kbps)，无损格式为 `None`
+}
+
+/// 支持的音频格式，自动选择格式时按此顺序查找可流复制的格式
+pub const AUDIO_FORMATS: &[AudioFormat] = &[
+    AudioFormat { ext: "m4a", encoder: "aac", codec: "aac", copy_codecs: &["aac", "alac"], default_bitrate: Some(192) },
+    AudioFormat { ext: "ogg", encoder: "libopus", codec: "opus", copy_codecs: &["opus", "vorbis", "flac"], default_bitrate: Some(128) },
+    AudioFormat { ext: "mp3", encoder: "libmp3lame", codec: "mp3", copy_codecs: &["mp3"], default_bitrate: Some(192) },
+    AudioFormat { ext: "flac", encoder: "flac", codec: "flac", copy_codecs: &["flac"], default_bitrate: None },
+    AudioFormat { ext: "wav", encoder: "pcm_s16le", codec: "pcm_s16le", copy_codecs: &["pcm_s16le", "pcm_s24le"], default_bitrate: None },
+    AudioFormat { ext: "opus", encoder: "libopus", codec: "opus", copy_codecs: &["opus"], default_bitrate: Some(128) },
+];
+
+/// 源编码无法流复制且未指定格式时转码为 MP3
+const FALLBACK_FORMAT: &str = "mp3";
+
+/// 允许的码率范围 (kbps)
+const MIN_BITRATE: u32 = 8;
+const MAX_BITRATE: u32 = 512;
+
+/// 流复制时容器未记录码率，估算大小使用的码率 (bit/s)
+const FALLBACK_BIT_RATE: u64 = 320_000;
+
+/// 估算大小时的余量（百分比）
+const SIZE_MARGIN_PERCENT: u64 = 110;
+
+/// 音频导出选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioOptions {
+    pub format: Option<String>,     // m4a / ogg / mp3 / flac / wav / opus，未指定时优先流复制
+    pub track: Option<u32>,         // 音频流在文件中的序号，默认第一个音频流
+    pub bitrate: Option<u32>,       // 码率 (kbps)，指定后总是重新编码
+    pub loudness: Option<LoudnessPreset>,   // 两遍 loudnorm 响度标准化，指定后总是重新编码
+}
+
+/// 允许的增益范围 (dB)
+const MAX_GAIN_DB: f64 = 60.0;
+
+/// 声道下混
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Downmix {
+    Stereo,
+    Mono,
+}
+
+impl Downmix {
+    /// 输出声道数
+    pub fn channels(&self) -> u32 {
+        match self {
+            Downmix::Stereo => 2,
+            Downmix::Mono => 1,
+        }
+    }
+}
+
+impl FromStr for Downmix {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "stereo" => Ok(Downmix::Stereo),
+            "mono" => Ok(Downmix::Mono),
+            _ => Err(validation_error(t!("audio.downmix_unknown", value = s))),
+        }
+    }
+}
+
+/// 剪辑导出时的音频处理，默认值表示不做任何处理
+///
+/// 除移除音轨外，任何一项都需要重新编码音频；视频始终流复制
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioAdjustments {
+    pub mute: bool,                         // 整段静音
+    pub gain_db: Option<f64>,               // 增益 (dB)，可为负
+    pub fade_in: Option<f64>,               // 开头淡入时长（秒）
+    pub fade_out: Option<f64>,              // 结尾淡出时长（秒）
+    pub downmix: Option<Downmix>,           // 下混为立体声或单声道
+    pub drop_tracks: Vec<u32>,              // 要移除的音频流序号
+    pub loudness: Option<LoudnessPreset>,   // 两遍 loudnorm 响度标准化
+}
+
+impl AudioAdjustments {
+    /// 是否不做任何处理
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 是否需要重新编码音频
+    pub fn needs_reencode(&self) -> bool {
+        self.mute || self.gain_db.is_some() || self.fade_in.is_some() || self.fade_out.is_some()
+            || self.downmix.is_some() || self.loudness.is_some()
+    }
+
+    /// 校验处理参数，`duration` 为导出范围的时长（秒）
+    pub fn validate(&self, duration: f64, streams: &[AudioStream]) -> AppResult<()> {
+        if let Some(gain) = self.gain_db {
+            if !gain.is_finite() || gain.abs() > MAX_GAIN_DB {
+                return Err(validation_error(t!("audio.invalid_gain", max = MAX_GAIN_DB)));
+            }
+        }
+
+        let fades = [self.fade_in, self.fade_out];
+        if fades.iter().flatten().any(|f| !f.is_finite() || *f < 0.0)
+            || fades.iter().flatten().sum::<f64>() > duration {
+            return Err(validation_error(t!("audio.invalid_fade")));
+        }
+
+        if self.mute && self.loudness.is_some() {
+            return Err(validation_error(t!("audio.mute_with_loudness")));
+        }
+
+        if let Some(index) = self.drop_tracks.iter().find(|i| !streams.iter().any(|s| s.index == **i)) {
+            return Err(validation_error(t!("audio.track_not_found", index = index)));
+        }
+        let kept = streams.iter().filter(|s| !self.drop_tracks.contains(&s.index)).count();
+        if self.needs_reencode() && kept == 0 {
+            return Err(validation_error(t!("audio.no_stream")));
+        }
+        // 响度只测量一个音轨，不能把同一组测量值套用到其他音轨
+        if self.loudness.is_some() && kept > 1 {
+            return Err(validation_error(t!("audio.loudness_multiple_tracks", count = kept)));
+        }
+        Ok(())
+    }
+
+    /// 音频滤镜链，不需要滤镜时为 `None`
+    ///
+    /// 顺序为：响度标准化（`loudness_filter` 为第二遍 loudnorm）、增益或静音、淡入、淡出
+    pub(crate) fn filter_chain(&self, duration: Rational, loudness_filter: Option<&str>) -> Option<String> {
+        let seconds = |s: f64| Rational::from_seconds_f64(s).to_ffmpeg_seconds();
+        let mut filters: Vec<String> = loudness_filter.map(|f| f.to_string()).into_iter().collect();
+
+        if self.mute {
+            filters.push("volume=0".to_string());
+        } else if let Some(gain) = self.gain_db {
+            filters.push(format!("volume={}dB", gain));
+        }
+        if let Some(fade) = self.fade_in.filter(|f| *f > 0.0) {
+            filters.push(format!("afade=t=in:st=0:d={}", seconds(fade)));
+        }
+        if let Some(fade) = self.fade_out.filter(|f| *f > 0.0) {
+            let start = (duration - Rational::from_seconds_f64(fade)).max(Rational::ZERO);
+            filters.push(format!("afade=t=out:st={}:d={}", start.to_ffmpeg_seconds(), seconds(fade)));
+        }
+
+        (!filters.is_empty()).then(|| filters.join(","))
+    }
+}
+
+/// 查找音频格式描述
+pub fn find_audio_format(format: &str) -> AppResult<&'static AudioFormat> {
+    let normalized = format.trim().trim_start_matches('.').to_ascii_lowercase();
+    AUDIO_FORMATS.iter()
+        .find(|f| f.ext == normalized)
+        .ok_or_else(|| validation_error(t!("audio.format_unsupported", format = format)))
+}
+
+/// 导出音频的编码方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Plan {
+    format: &'static AudioFormat,
+    copy: bool,
+    bitrate: Option<u32>,
+}
+
+/// 根据源编码和选项决定输出格式，以及流复制还是转码
+fn plan(stream: &AudioStream, options: &AudioOptions) -> AppResult<Plan> {
+    let format = match options.format.as_deref() {
+        Some(format) => find_audio_format(format)?,
+        // 优先使用源编码所属的格式，指定码率时只考虑有损格式
+        None => match AUDIO_FORMATS.iter().find(|f| {
+            f.copy_codecs.contains(&stream.codec.as_str())
+                && (options.bitrate.is_none() || f.default_bitrate.is_some())
+        }) {
+            Some(format) => format,
+            None => find_audio_format(FALLBACK_FORMAT)?,
+        },
+    };
+
+    if let Some(bitrate) = options.bitrate {
+        if format.default_bitrate.is_none() {
+            return Err(validation_error(t!("audio.bitrate_lossless", format = format.ext)));
+        }
+        if !(MIN_BITRATE..=MAX_BITRATE).contains(&bitrate) {
+            return Err(validation_error(t!("audio.invalid_bitrate", min = MIN_BITRATE, max = MAX_BITRATE)));
+        }
+    }
+
+    let reencode = options.bitrate.is_some() || options.loudness.is_some();
+    let copy = !reencode && format.copy_codecs.contains(&stream.codec.as_str());
+    let bitrate = if copy { None } else { options.bitrate.or(format.default_bitrate) };
+    Ok(Plan { format, copy, bitrate })
+}
+
+/// 提取音频到下一个版本文件
+///
+/// `start` 和 `end` 都未指定时导出整个文件，只指定一端时另一端为文件开头或结尾。
+/// 源编码能放入目标格式时流复制，否则按码率设置转码
+pub fn extract_audio(
+    input_path: &str,
+    start: Option<&TimePoint>,
+    end: Option<&TimePoint>,
+    options: &AudioOptions,
+    notes: Option<&str>,
+    output_dir: Option<&str>
+) -> AppResult<AudioResult> {
+    extract_audio_with(&ProcessBackend, input_path, start, end, options, notes, output_dir)
+}
+
+/// 使用指定后端提取音频，参数含义同 [`extract_audio`]
+pub fn extract_audio_with(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    start: Option<&TimePoint>,
+    end: Option<&TimePoint>,
+    options: &AudioOptions,
+    notes: Option<&str>,
+    output_dir: Option<&str>
+) -> AppResult<AudioResult> {
+    // 验证输入路径和输出目录
+    validate_input_path(input_path)?;
+    let output_dir = output_dir
+        .map(validate_output_dir)
+        .transpose()?;
+
+    // 选择音频流
+    let streams = get_audio_streams_with(backend, input_path)?;
+    let stream = select_audio_stream(&streams, options.track)?;
+    let plan = plan(stream, options)?;
+
+    // 换算并验证时间范围
+    let range = resolve_optional_range_with(backend, input_path, start, end)?;
+    let seconds = match &range {
+        Some(range) => range.duration().to_f64(),
+        None => get_video_duration_with(backend, input_path)?,
+    };
+
+    // 第一遍测量响度
+    let loudness = options.loudness
+        .map(|preset| measure_stream_with(backend, input_path, range.as_ref(), stream.index, preset))
+        .transpose()?;
+    let filter = loudness.as_ref().map(|report| report.normalize_filter(Some(stream.sample_rate)));
+
+    // 预留输出文件并检查磁盘空间
+    let reservation = reserve_next_output(input_path, notes, output_dir.as_deref(), Some(plan.format.ext))?;
+    check_disk_space_for_output(reservation.final_path(), estimate_size(stream, &plan, seconds))?;
+    let output = reservation.temp_path().to_str()
+        .ok_or_else(|| path_error(t!("path.not_utf8")))?;
+
+    // 执行 ffmpeg 导出
+    let args = audio_args(input_path, range.as_ref(), stream.index, &plan, filter.as_deref(), output);
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    let result = backend.ffmpeg(&args)?;
+    check_command_success(&result, "ffmpeg")?;
+
+    // 检查临时文件并重命名为最终文件名
+    let output_path = reservation.commit()?.to_string_lossy().to_string();
+
+    let (codec, message) = if plan.copy {
+        (stream.codec.clone(), t!("audio.success_copy", path = output_path))
+    } else {
+        (plan.format.codec.to_string(), t!("audio.success_transcode", codec = plan.format.codec, path = output_path))
+    };
+    Ok(AudioResult { output_path, message, codec, copied: plan.copy, loudness })
+}
+
+/// 按流序号选择音频流，未指定时使用第一个音频流
+pub(crate) fn select_audio_stream(streams: &[AudioStream], track: Option<u32>) -> AppResult<&AudioStream> {
+    match track {
+        Some(index) => streams.iter().find(|s| s.index == index)
+            .ok_or_else(|| validation_error(t!("audio.track_not_found", index = index))),
+        None => streams.first()
+            .ok_or_else(|| validation_error(t!("audio.no_stream"))),
+    }
+}
+
+/// 估算输出大小：流复制按源码率，有损格式按目标码率，无损格式按 16 位 PCM 计算上限
+fn estimate_size(stream: &AudioStream, plan: &Plan, seconds: f64) -> u64 {
+    let bits_per_second = if plan.copy {
+        stream.bit_rate.unwrap_or(FALLBACK_BIT_RATE)
+    } else {
+        match plan.bitrate {
+            Some(kbps) => kbps as u64 * 1000,
+            None => stream.sample_rate as u64 * stream.channels.max(1) as u64 * 16,
+        }
+    };
+    (bits_per_second as f64 * seconds / 8.0) as u64 * SIZE_MARGIN_PERCENT / 100
+}
+
+/// 构建 ffmpeg 参数
+fn audio_args(
+    input_path: &str,
+    range: Option<&ResolvedRange>,
+    stream_index: u32,
+    plan: &Plan,
+    filter: Option<&str>,
+    output: &str
+) -> Vec<String> {
+    let mut args: Vec<String> = vec!["-y".into()];
+    if let Some(range) = range {
+        args.extend([
+            "-ss".into(), range.start.to_ffmpeg_seconds(),
+            "-t".into(), range.duration().to_ffmpeg_seconds(),
+        ]);
+    }
+    args.extend([
+        "-i".into(), input_path.into(),
+        "-map".into(), format!("0:{}", stream_index),
+    ]);
+
+    if plan.copy {
+        args.extend(["-c:a".into(), "copy".into()]);
+    } else {
+        if let Some(filter) = filter {
+            args.extend(["-af".into(), filter.into()]);
+        }
+        args.extend(["-c:a".into(), plan.format.encoder.into()]);
+        if let Some(kbps) = plan.bitrate {
+            args.extend(["-b:a".into(), format!("{}k", kbps)]);
+        }
+    }
+
+    args.push(output.into());
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use crate::backend::{CommandOutput, MockBackend};
+    use crate::binaries::Tool;
+
+    fn stream(index: u32, codec: &str) -> AudioStream {
+        AudioStream {
+            index, codec: codec.to_string(), channels: 2, sample_rate: 48000, bit_rate: Some(128_000), language: None,
+        }
+    }
+
+    fn options(format: Option<&str>, bitrate: Option<u32>) -> AudioOptions {
+        AudioOptions { format: format.map(|f| f.to_string()), bitrate, ..Default::default() }
+    }
+
+    #[test]
+    fn test_plan_prefers_stream_copy() {
+        let aac = plan(&stream(1, "aac"), &options(None, None)).unwrap();
+        assert_eq!((aac.format.ext, aac.copy, aac.bitrate), ("m4a", true, None));
+
+        let opus = plan(&stream(1, "opus"), &options(None, None)).unwrap();
+        assert_eq!((opus.format.ext, opus.copy), ("ogg", true));
+
+        // 无法流复制的编码转码为 MP3
+        let ac3 = plan(&stream(1, "ac3"), &options(None, None)).unwrap();
+        assert_eq!((ac3.format.ext, ac3.copy, ac3.bitrate), ("mp3", false, Some(192)));
+
+        // 指定码率时总是转码
+        let forced = plan(&stream(1, "aac"), &options(None, Some(96))).unwrap();
+        assert_eq!((forced.format.ext, forced.copy, forced.bitrate), ("m4a", false, Some(96)));
+        let lossless = plan(&stream(1, "flac"), &options(None, Some(96))).unwrap();
+        assert_eq!((lossless.format.ext, lossless.copy), ("ogg", false));
+
+        let flac = plan(&stream(1, "aac"), &options(Some(".FLAC"), None)).unwrap();
+        assert_eq!((flac.format.encoder, flac.copy, flac.bitrate), ("flac", false, None));
+    }
+
+    #[test]
+    fn test_plan_rejects_invalid_options() {
+        assert!(plan(&stream(1, "aac"), &options(Some("aiff"), None)).is_err());
+        assert!(plan(&stream(1, "aac"), &options(Some("wav"), Some(128))).is_err());
+        assert!(plan(&stream(1, "aac"), &options(Some("mp3"), Some(4))).is_err());
+        assert!(plan(&stream(1, "aac"), &options(Some("opus"), Some(1000))).is_err());
+    }
+
+    #[test]
+    fn test_adjustments_filter_chain() {
+        assert!(AudioAdjustments::default().is_empty());
+        assert_eq!(AudioAdjustments::default().filter_chain(Rational::from_integer(10), None), None);
+
+        // 只移除音轨时不需要重新编码
+        let drop_only = AudioAdjustments { drop_tracks: vec![2], ..Default::default() };
+        assert!(!drop_only.is_empty() && !drop_only.needs_reencode());
+
+        let muted = AudioAdjustments { mute: true, gain_db: Some(6.0), fade_out: Some(1.5), ..Default::default() };
+        assert_eq!(
+            muted.filter_chain(Rational::new(10010, 1000).unwrap(), Some("loudnorm=I=-16")).as_deref(),
+            Some("loudnorm=I=-16,volume=0,afade=t=out:st=8.51:d=1.5")
+        );
+    }
+
+    #[test]
+    fn test_adjustments_validation() {
+        let streams = [stream(1, "aac"), stream(2, "ac3")];
+        let valid = AudioAdjustments { gain_db: Some(-12.0), fade_in: Some(1.0), fade_out: Some(1.0), ..Default::default() };
+        assert!(valid.validate(2.0, &streams).is_ok());
+        let loudness = AudioAdjustments { loudness: Some(LoudnessPreset::Podcast), drop_tracks: vec![2], ..Default::default() };
+        assert!(loudness.validate(2.0, &streams).is_ok());
+
+        let invalid = [
+            AudioAdjustments { gain_db: Some(90.0), ..Default::default() },
+            AudioAdjustments { fade_in: Some(-1.0), ..Default::default() },
+            AudioAdjustments { fade_in: Some(1.5), fade_out: Some(1.0), ..Default::default() },
+            AudioAdjustments { mute: true, loudness: Some(LoudnessPreset::Podcast), ..Default::default() },
+            AudioAdjustments { drop_tracks: vec![0], ..Default::default() },
+            AudioAdjustments { drop_tracks: vec![1, 2], mute: true, ..Default::default() },
+            AudioAdjustments { loudness: Some(LoudnessPreset::Podcast), ..Default::default() },
+        ];
+        for adjustments in invalid {
+            assert!(adjustments.validate(2.0, &streams).is_err(), "{:?}", adjustments);
+        }
+    }
+
+    fn mock_source(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("instant-cut-audio-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("talk.mp4");
+        fs::write(&input, b"video").unwrap();
+        (dir, input)
+    }
+
+    fn mock_backend() -> MockBackend {
+        let mock = MockBackend::new();
+        mock.set_default(Tool::FFprobe, CommandOutput::ok(serde_json::json!({
+            "streams": [
+                { "index": 0, "codec_type": "video", "codec_name": "h264", "width": 1920, "height": 1080,
+                  "r_frame_rate": "25/1", "time_base": "1/12800" },
+                { "index": 1, "codec_type": "audio", "codec_name": "aac", "channels": 2,
+                  "sample_rate": "48000", "bit_rate": "128000", "tags": { "language": "eng" } },
+                { "index": 2, "codec_type": "audio", "codec_name": "ac3", "channels": 6, "sample_rate": "48000" },
+            ],
+            "format": { "duration": "60.0", "format_name": "mov,mp4,m4a,3gp,3g2,mj2" },
+        }).to_string()));
+        mock.set_output_contents(b"audio");
+        mock
+    }
+
+    #[test]
+    fn test_extract_whole_file_with_stream_copy() {
+        let (dir, input) = mock_source("copy");
+        let mock = mock_backend();
+
+        let result = extract_audio_with(&mock, input.to_str().unwrap(), None, None, &AudioOptions::default(), None, None).unwrap();
+        assert_eq!(result.output_path, dir.join("talk_1.m4a").to_string_lossy());
+        assert_eq!((result.codec.as_str(), result.copied), ("aac", true));
+
+        let args = &mock.calls_to(Tool::FFmpeg)[0];
+        assert_eq!(args[..7], ["-y", "-i", input.to_str().unwrap(), "-map", "0:1", "-c:a", "copy"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_extract_range_of_selected_track() {
+        let (dir, input) = mock_source("track");
+        let mock = mock_backend();
+
+        let options = AudioOptions { track: Some(2), ..Default::default() };
+        let start: TimePoint = "250f".parse().unwrap();
+        let result = extract_audio_with(&mock, input.to_str().unwrap(), Some(&start), None, &options, Some("talk"), None).unwrap();
+        assert_eq!(result.output_path, dir.join("talk_1_talk.mp3").to_string_lossy());
+        assert_eq!((result.codec.as_str(), result.copied), ("mp3", false));
+
+        let args = &mock.calls_to(Tool::FFmpeg)[0];
+        assert_eq!(args[..13], [
+            "-y", "-ss", "10", "-t", "50", "-i", input.to_str().unwrap(), "-map", "0:2",
+            "-c:a", "libmp3lame", "-b:a", "192k",
+        ]);
+
+        let err = extract_audio_with(
+            &mock, input.to_str().unwrap(), None, None, &AudioOptions { track: Some(0), ..Default::default() }, None, None
+        ).unwrap_err();
+        assert!(matches!(err, crate::error::AppError::ValidationError(_)));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_extract_with_loudness_normalization() {
+        let (dir, input) = mock_source("loudness");
+        let mock = mock_backend();
+        mock.respond(Tool::FFmpeg, CommandOutput {
+            code: Some(0),
+            stdout: Vec::new(),
+            stderr: br#"{ "input_i" : "-30.00", "input_tp" : "-6.00", "input_lra" : "7.00",
+                          "input_thresh" : "-40.00", "target_offset" : "0.20" }"#.to_vec(),
+        });
+
+        let options = AudioOptions { loudness: Some(LoudnessPreset::Podcast), ..Default::default() };
+        let result = extract_audio_with(&mock, input.to_str().unwrap(), None, None, &options, None, None).unwrap();
+        assert_eq!(result.loudness.as_ref().map(|r| r.input_i), Some(-30.0));
+        // 需要滤镜时不能流复制，AAC 源仍输出为 m4a
+        assert_eq!((result.codec.as_str(), result.copied), ("aac", false));
+
+        let calls = mock.calls_to(Tool::FFmpeg);
+        assert_eq!(calls.len(), 2);
+        assert!(calls[0].windows(2).any(|w| w == ["-af", "loudnorm=I=-16:TP=-1:LRA=11:print_format=json"]));
+        assert_eq!(calls[1][5..9], [
+            "-af",
+            "loudnorm=I=-16:TP=-1:LRA=11:measured_I=-30:measured_TP=-6:measured_LRA=7:measured_thresh=-40:\
+             offset=0.2:linear=true,aresample=48000",
+            "-c:a", "aac",
+        ]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
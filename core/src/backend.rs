@@ -0,0 +1,221 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use std::process::Output;
+use std::sync::Mutex;
+use crate::binaries::Tool;
+use crate::capabilities::{self, FFmpegCapabilities};
+use crate::error::AppResult;
+use crate::output::final_name_from_temp;
+use crate::utils::{execute_ffmpeg, execute_ffprobe};
+
+/// 外部命令的执行结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandOutput {
+    /// 退出码，进程被信号终止时为 `None`
+    pub code: Option<i32>,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+impl CommandOutput {
+    /// 退出码为 0 且带有给定标准输出的结果
+    pub fn ok(stdout: impl Into<Vec<u8>>) -> Self {
+        Self { code: Some(0), stdout: stdout.into(), stderr: Vec::new() }
+    }
+
+    /// 以给定退出码和标准错误失败的结果
+    pub fn failed(code: i32, stderr: impl Into<Vec<u8>>) -> Self {
+        Self { code: Some(code), stdout: Vec::new(), stderr: stderr.into() }
+    }
+
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+impl From<Output> for CommandOutput {
+    fn from(output: Output) -> Self {
+        Self {
+            code: output.status.code(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        }
+    }
+}
+
+/// 媒体处理后端：负责运行 ffprobe 和 ffmpeg
+///
+/// 剪辑和探测逻辑只通过该接口调用外部程序，测试时可以替换为 [`MockBackend`]
+pub trait MediaBackend: Send + Sync {
+    /// 以给定参数运行 ffprobe
+    fn ffprobe(&self, args: &[&str]) -> AppResult<CommandOutput>;
+
+    /// 以给定参数运行 ffmpeg
+    fn ffmpeg(&self, args: &[&str]) -> AppResult<CommandOutput>;
+
+    /// ffmpeg 的版本和能力（编码器、滤镜等）
+    fn capabilities(&self) -> AppResult<FFmpegCapabilities>;
+}
+
+/// 启动子进程运行 ffmpeg / ffprobe 的默认后端
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessBackend;
+
+impl MediaBackend for ProcessBackend {
+    fn ffprobe(&self, args: &[&str]) -> AppResult<CommandOutput> {
+        execute_ffprobe(args)
+    }
+
+    fn ffmpeg(&self, args: &[&str]) -> AppResult<CommandOutput> {
+        execute_ffmpeg(args)
+    }
+
+    fn capabilities(&self) -> AppResult<FFmpegCapabilities> {
+        capabilities::capabilities()
+    }
+}
+
+/// 一次被记录的调用
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockCall {
+    pub tool: Tool,
+    pub args: Vec<String>,
+}
+
+/// 用于测试的后端：记录调用参数并按顺序返回预设的结果
+///
+/// 队列为空时返回通过 [`MockBackend::set_default`] 设置的结果（默认为成功且无输出）。
+/// 设置了输出内容时，成功的 ffmpeg 调用会把内容写入参数中的所有临时输出文件，模拟生成输出。
+/// 能力信息默认为空（没有任何编码器和滤镜），可通过 [`MockBackend::set_capabilities`] 设置
+#[derive(Debug, Default)]
+pub struct MockBackend {
+    calls: Mutex<Vec<MockCall>>,
+    ffprobe_responses: Mutex<VecDeque<CommandOutput>>,
+    ffmpeg_responses: Mutex<VecDeque<CommandOutput>>,
+    ffprobe_default: Mutex<Option<CommandOutput>>,
+    ffmpeg_default: Mutex<Option<CommandOutput>>,
+    output_contents: Mutex<Option<Vec<u8>>>,
+    capabilities: Mutex<FFmpegCapabilities>,
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 追加一个结果，供下一次对应工具的调用返回
+    pub fn respond(&self, tool: Tool, output: CommandOutput) {
+        self.responses(tool).lock().unwrap().push_back(output);
+    }
+
+    /// 设置队列为空时返回的结果
+    pub fn set_default(&self, tool: Tool, output: CommandOutput) {
+        *self.default_for(tool).lock().unwrap() = Some(output);
+    }
+
+    /// 设置成功的 ffmpeg 调用写入输出文件的内容
+    pub fn set_output_contents(&self, contents: &[u8]) {
+        *self.output_contents.lock().unwrap() = Some(contents.to_vec());
+    }
+
+    /// 设置 [`MediaBackend::capabilities`] 返回的能力信息
+    pub fn set_capabilities(&self, capabilities: FFmpegCapabilities) {
+        *self.capabilities.lock().unwrap() = capabilities;
+    }
+
+    /// 到目前为止的所有调用
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// 某个工具的所有调用参数
+    pub fn calls_to(&self, tool: Tool) -> Vec<Vec<String>> {
+        self.calls().into_iter()
+            .filter(|c| c.tool == tool)
+            .map(|c| c.args)
+            .collect()
+    }
+
+    fn responses(&self, tool: Tool) -> &Mutex<VecDeque<CommandOutput>> {
+        match tool {
+            Tool::FFmpeg => &self.ffmpeg_responses,
+            Tool::FFprobe => &self.ffprobe_responses,
+        }
+    }
+
+    fn default_for(&self, tool: Tool) -> &Mutex<Option<CommandOutput>> {
+        match tool {
+            Tool::FFmpeg => &self.ffmpeg_default,
+            Tool::FFprobe => &self.ffprobe_default,
+        }
+    }
+
+    fn run(&self, tool: Tool, args: &[&str]) -> AppResult<CommandOutput> {
+        self.calls.lock().unwrap().push(MockCall {
+            tool,
+            args: args.iter().map(|a| a.to_string()).collect(),
+        });
+
+        let output = self.responses(tool).lock().unwrap().pop_front()
+            .or_else(|| self.default_for(tool).lock().unwrap().clone())
+            .unwrap_or_else(|| CommandOutput::ok(Vec::new()));
+
+        if tool == Tool::FFmpeg && output.success() {
+            if let Some(contents) = self.output_contents.lock().unwrap().as_ref() {
+                for path in args.iter().map(Path::new).filter(|p| is_temp_output(p)) {
+                    fs::write(path, contents)?;
+                }
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// 是否为 [`crate::output::OutputReservation`] 预留的临时文件
+fn is_temp_output(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .and_then(final_name_from_temp)
+        .is_some()
+}
+
+impl MediaBackend for MockBackend {
+    fn ffprobe(&self, args: &[&str]) -> AppResult<CommandOutput> {
+        self.run(Tool::FFprobe, args)
+    }
+
+    fn ffmpeg(&self, args: &[&str]) -> AppResult<CommandOutput> {
+        self.run(Tool::FFmpeg, args)
+    }
+
+    fn capabilities(&self) -> AppResult<FFmpegCapabilities> {
+        Ok(self.capabilities.lock().unwrap().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_records_calls_and_returns_responses() {
+        let mock = MockBackend::new();
+        mock.respond(Tool::FFprobe, CommandOutput::ok("{}"));
+        mock.set_default(Tool::FFprobe, CommandOutput::failed(1, "boom"));
+
+        assert_eq!(mock.ffprobe(&["-show_format", "a.mp4"]).unwrap().stdout, b"{}");
+        let fallback = mock.ffprobe(&["b.mp4"]).unwrap();
+        assert!(!fallback.success());
+        assert_eq!(fallback.stderr, b"boom");
+        assert!(mock.ffmpeg(&["-version"]).unwrap().success());
+
+        assert_eq!(mock.calls().len(), 3);
+        assert_eq!(mock.calls_to(Tool::FFprobe), vec![
+            vec!["-show_format".to_string(), "a.mp4".to_string()],
+            vec!["b.mp4".to_string()],
+        ]);
+        assert_eq!(mock.calls_to(Tool::FFmpeg), vec![vec!["-version".to_string()]]);
+    }
+}
@@ -0,0 +1,225 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use crate::settings;
+use crate::capabilities::FFmpegCapabilities;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+#[cfg(target_os = "windows")]
+use crate::utils::CREATE_NO_WINDOW;
+
+/// 可执行文件扩展名
+#[cfg(target_os = "windows")]
+const EXE_SUFFIX: &str = ".exe";
+#[cfg(not(target_os = "windows"))]
+const EXE_SUFFIX: &str = "";
+
+/// 常见的 ffmpeg 安装位置
+#[cfg(target_os = "windows")]
+const COMMON_LOCATIONS: &[&str] = &[
+    "C:\\ffmpeg\\bin",
+    "C:\\Program Files\\ffmpeg\\bin",
+    "C:\\ProgramData\\chocolatey\\bin",
+];
+#[cfg(target_os = "macos")]
+const COMMON_LOCATIONS: &[&str] = &["/opt/homebrew/bin", "/usr/local/bin", "/opt/local/bin"];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const COMMON_LOCATIONS: &[&str] = &["/usr/bin", "/usr/local/bin", "/snap/bin", "/opt/ffmpeg/bin"];
+
+/// 需要查找的工具
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    FFmpeg,
+    FFprobe,
+}
+
+impl Tool {
+    /// 可执行文件名（不含扩展名）
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tool::FFmpeg => "ffmpeg",
+            Tool::FFprobe => "ffprobe",
+        }
+    }
+
+    fn file_name(&self) -> String {
+        format!("{}{}", self.name(), EXE_SUFFIX)
+    }
+}
+
+/// 可执行文件的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BinarySource {
+    /// 设置中指定的路径
+    Setting,
+    /// 随应用打包的 sidecar (Tauri externalBin)
+    Sidecar,
+    /// PATH 环境变量
+    Path,
+    /// 常见安装位置
+    CommonLocation,
+}
+
+/// 找到的可执行文件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BinaryLocation {
+    pub path: PathBuf,
+    pub source: BinarySource,
+}
+
+/// ffmpeg 和 ffprobe 的查找结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FFmpegStatus {
+    pub installed: bool,                    // 两者是否都已找到
+    pub ffmpeg: Option<BinaryLocation>,     // ffmpeg 的位置
+    pub ffprobe: Option<BinaryLocation>,    // ffprobe 的位置
+    pub capabilities: Option<FFmpegCapabilities>,   // ffmpeg 的版本和能力
+    pub message: Option<String>,            // 未找到，或设置中的路径无法运行时的提示信息
+}
+
+/// 查找结果缓存，设置变更时清空
+static RESOLVED: RwLock<Option<(Option<BinaryLocation>, Option<BinaryLocation>)>> = RwLock::new(None);
+
+/// 清空查找结果缓存
+pub fn invalidate() {
+    *RESOLVED.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// 获取工具的位置（带缓存）
+pub fn locate(tool: Tool) -> Option<BinaryLocation> {
+    if let Some((ffmpeg, ffprobe)) = RESOLVED.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return match tool {
+            Tool::FFmpeg => ffmpeg.clone(),
+            Tool::FFprobe => ffprobe.clone(),
+        };
+    }
+
+    let ffmpeg = discover(Tool::FFmpeg);
+    let ffprobe = discover(Tool::FFprobe);
+    let result = match tool {
+        Tool::FFmpeg => ffmpeg.clone(),
+        Tool::FFprobe => ffprobe.clone(),
+    };
+    *RESOLVED.write().unwrap_or_else(|e| e.into_inner()) = Some((ffmpeg, ffprobe));
+    result
+}
+
+/// 获取用于执行的程序路径，未找到时回退到工具名（交由系统 PATH 解析）
+pub fn program(tool: Tool) -> PathBuf {
+    locate(tool)
+        .map(|l| l.path)
+        .unwrap_or_else(|| PathBuf::from(tool.name()))
+}
+
+/// 按顺序查找工具：设置 → sidecar → PATH → 常见安装位置
+pub fn discover(tool: Tool) -> Option<BinaryLocation> {
+    candidates(tool, configured_path(tool).as_deref())
+        .into_iter()
+        .find(|c| is_runnable(&c.path))
+}
+
+/// 设置中指定但无法运行、因而被跳过的路径
+pub fn rejected_setting(tool: Tool) -> Option<String> {
+    configured_path(tool).filter(|path| !is_runnable(Path::new(path)))
+}
+
+/// 设置中指定的路径，空白时为 `None`
+fn configured_path(tool: Tool) -> Option<String> {
+    let configured = settings::current();
+    let path = match tool {
+        Tool::FFmpeg => configured.ffmpeg_path,
+        Tool::FFprobe => configured.ffprobe_path,
+    };
+    path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty())
+}
+
+/// 生成候选路径列表
+fn candidates(tool: Tool, configured_path: Option<&str>) -> Vec<BinaryLocation> {
+    let file_name = tool.file_name();
+    let mut list = Vec::new();
+
+    if let Some(path) = configured_path.filter(|p| !p.trim().is_empty()) {
+        list.push(BinaryLocation { path: PathBuf::from(path.trim()), source: BinarySource::Setting });
+    }
+
+    // tauri.conf.json 的 externalBin 声明了 binaries/ffmpeg 和 binaries/ffprobe，
+    // 打包时 Tauri 去掉文件名中的目标三元组后放在主程序同一目录下，即 ffmpeg[.exe]
+    if let Some(exe_dir) = env::current_exe().ok().as_deref().and_then(Path::parent) {
+        list.push(BinaryLocation { path: exe_dir.join(&file_name), source: BinarySource::Sidecar });
+    }
+
+    if let Some(path_var) = env::var_os("PATH") {
+        for dir in env::split_paths(&path_var) {
+            list.push(BinaryLocation { path: dir.join(&file_name), source: BinarySource::Path });
+        }
+    }
+
+    for dir in common_locations() {
+        list.push(BinaryLocation { path: dir.join(&file_name), source: BinarySource::CommonLocation });
+    }
+
+    list
+}
+
+/// 常见安装位置，Windows 上额外包含 winget 和 scoop 的目录
+fn common_locations() -> Vec<PathBuf> {
+    #[allow(unused_mut)]
+    let mut dirs: Vec<PathBuf> = COMMON_LOCATIONS.iter().map(PathBuf::from).collect();
+
+    #[cfg(target_os = "windows")]
+    {
+        if let Some(local) = env::var_os("LOCALAPPDATA") {
+            dirs.push(PathBuf::from(local).join("Microsoft").join("WinGet").join("Links"));
+        }
+        if let Some(home) = env::var_os("USERPROFILE") {
+            dirs.push(PathBuf::from(home).join("scoop").join("shims"));
+        }
+    }
+
+    dirs
+}
+
+/// 检查文件存在且能执行 `-version`
+fn is_runnable(path: &Path) -> bool {
+    if !path.is_file() {
+        return false;
+    }
+
+    let mut cmd = Command::new(path);
+    cmd.arg("-version");
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+
+    cmd.output().map(|o| o.status.success()).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates_order() {
+        let list = candidates(Tool::FFprobe, Some("/opt/custom/ffprobe"));
+        assert_eq!(list[0], BinaryLocation {
+            path: PathBuf::from("/opt/custom/ffprobe"),
+            source: BinarySource::Setting,
+        });
+        assert_eq!(list[1].source, BinarySource::Sidecar);
+        assert_eq!(list.last().unwrap().source, BinarySource::CommonLocation);
+
+        // 空白设置被忽略
+        let list = candidates(Tool::FFmpeg, Some("  "));
+        assert_eq!(list[0].source, BinarySource::Sidecar);
+        assert!(list[0].path.ends_with(Tool::FFmpeg.file_name()));
+    }
+
+    #[test]
+    fn test_missing_binary_not_runnable() {
+        assert!(!is_runnable(Path::new("/nonexistent/ffmpeg")));
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use crate::error::AppResult;
+use crate::utils::{execute_ffmpeg, check_command_success};
+use crate::binaries::{self, Tool};
+
+/// 按可执行文件路径缓存的能力信息
+static CACHE: Mutex<Option<HashMap<PathBuf, FFmpegCapabilities>>> = Mutex::new(None);
+
+/// H.264 编码器，按优先级排列
+pub const H264_ENCODERS: &[&str] = &["libx264", "h264_videotoolbox", "h264_nvenc", "h264_qsv", "h264_amf"];
+/// HEVC 编码器，按优先级排列
+pub const HEVC_ENCODERS: &[&str] = &["libx265", "hevc_videotoolbox", "hevc_nvenc", "hevc_qsv", "hevc_amf"];
+/// AV1 编码器，按优先级排列
+pub const AV1_ENCODERS: &[&str] = &["libsvtav1", "libaom-av1", "librav1e", "av1_nvenc", "av1_qsv"];
+
+/// 已安装 ffmpeg 的版本和能力
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FFmpegCapabilities {
+    pub version: String,                // 版本字符串 (例如 "6.1.1-3ubuntu5")
+    pub version_major: Option<u32>,     // 主版本号，开发版为空
+    pub version_minor: Option<u32>,     // 次版本号
+    pub configuration: Vec<String>,     // 编译配置参数 (例如 "--enable-libx264")
+    pub encoders: Vec<String>,
+    pub decoders: Vec<String>,
+    pub muxers: Vec<String>,
+    pub filters: Vec<String>,
+    pub features: FeatureSupport,       // 根据上述信息得出的功能支持情况
+}
+
+/// 导出预设和分析功能依赖的能力
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeatureSupport {
+    pub h264_encoder: Option<String>,   // 可用的 H.264 编码器
+    pub hevc_encoder: Option<String>,   // 可用的 HEVC 编码器
+    pub av1_encoder: Option<String>,    // 可用的 AV1 编码器
+    pub loudnorm: bool,                 // 响度标准化
+    pub vidstab: bool,                  // 防抖 (vidstabdetect + vidstabtransform)
+    pub cropdetect: bool,               // 黑边检测
+    pub lut3d: bool,                    // 3D LUT 调色
+    pub subtitles: bool,                // 字幕烧录 (libass)
+    pub drawtext: bool,                 // 文字叠加 (libfreetype)
+}
+
+impl FFmpegCapabilities {
+    pub fn has_encoder(&self, name: &str) -> bool {
+        self.encoders.iter().any(|e| e == name)
+    }
+
+    pub fn has_decoder(&self, name: &str) -> bool {
+        self.decoders.iter().any(|d| d == name)
+    }
+
+    pub fn has_muxer(&self, name: &str) -> bool {
+        self.muxers.iter().any(|m| m == name)
+    }
+
+    pub fn has_filter(&self, name: &str) -> bool {
+        self.filters.iter().any(|f| f == name)
+    }
+
+    /// 返回列表中第一个可用的编码器
+    pub fn first_encoder(&self, candidates: &[&str]) -> Option<String> {
+        candidates.iter()
+            .find(|c| self.has_encoder(c))
+            .map(|c| c.to_string())
+    }
+
+    fn detect_features(&self) -> FeatureSupport {
+        FeatureSupport {
+            h264_encoder: self.first_encoder(H264_ENCODERS),
+            hevc_encoder: self.first_encoder(HEVC_ENCODERS),
+            av1_encoder: self.first_encoder(AV1_ENCODERS),
+            loudnorm: self.has_filter("loudnorm"),
+            vidstab: self.has_filter("vidstabdetect") && self.has_filter("vidstabtransform"),
+            cropdetect: self.has_filter("cropdetect"),
+            lut3d: self.has_filter("lut3d"),
+            subtitles: self.has_filter("subtitles"),
+            drawtext: self.has_filter("drawtext"),
+        }
+    }
+}
+
+/// 获取当前 ffmpeg 的能力（按可执行文件路径缓存）
+pub fn capabilities() -> AppResult<FFmpegCapabilities> {
+    let program = binaries::program(Tool::FFmpeg);
+
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(caps) = cache.as_ref().and_then(|c| c.get(&program)) {
+        return Ok(caps.clone());
+    }
+
+    let caps = probe_capabilities()?;
+    cache.get_or_insert_with(HashMap::new).insert(program, caps.clone());
+    Ok(caps)
+}
+
+/// 运行 ffmpeg 并解析版本、编码器、解码器、封装器和滤镜列表
+fn probe_capabilities() -> AppResult<FFmpegCapabilities> {
+    let version_output = run(&["-version"])?;
+    let (version, configuration) = parse_version_output(&version_output);
+    let (version_major, version_minor) = parse_version_number(&version);
+
+    let mut caps = FFmpegCapabilities {
+        version,
+        version_major,
+        version_minor,
+        configuration,
+        encoders: parse_list(&run(&["-hide_banner", "-encoders"])?),
+        decoders: parse_list(&run(&["-hide_banner", "-decoders"])?),
+        muxers: parse_list(&run(&["-hide_banner", "-muxers"])?),
+        filters: parse_list(&run(&["-hide_banner", "-filters"])?),
+        features: FeatureSupport::default(),
+    };
+    caps.features = caps.detect_features();
+    Ok(caps)
+}
+
+fn run(args: &[&str]) -> AppResult<String> {
+    let output = execute_ffmpeg(args)?;
+    check_command_success(&output, "ffmpeg")?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// 解析 `-version` 输出，返回版本字符串和编译配置参数
+pub fn parse_version_output(output: &str) -> (String, Vec<String>) {
+    let version = output.lines()
+        .find_map(|l| l.trim().strip_prefix("ffmpeg version "))
+        .and_then(|rest| rest.split_whitespace().next())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let configuration = output.lines()
+        .find_map(|l| l.trim().strip_prefix("configuration:"))
+        .map(|rest| rest.split_whitespace().map(|s| s.to_string()).collect())
+        .unwrap_or_default();
+
+    (version, configuration)
+}
+
+/// 从版本字符串中提取主次版本号 (例如 "n6.1.1" -> (6, 1))，开发版返回空
+pub fn parse_version_number(version: &str) -> (Option<u32>, Option<u32>) {
+    let trimmed = version.trim_start_matches('n');
+    let mut parts = trimmed
+        .split(|c: char| !c.is_ascii_digit())
+        .map(|p| p.parse::<u32>().ok());
+
+    // 开发版形如 "N-112345-g..."，首字符不是数字
+    if !trimmed.starts_with(|c: char| c.is_ascii_digit()) {
+        return (None, None);
+    }
+
+    let major = parts.next().flatten();
+    let minor = parts.next().flatten();
+    (major, minor)
+}
+
+/// 解析 `-encoders`、`-decoders`、`-muxers`、`-filters` 的列表输出
+///
+/// 条目行以标志列开头（仅含大写字母、`.` 和 `|`），后跟名称；
+/// 图例行的第二列是 `=`，会被跳过
+pub fn parse_list(output: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for line in output.lines() {
+        let mut tokens = line.split_whitespace();
+        let (Some(flags), Some(name)) = (tokens.next(), tokens.next()) else { continue };
+
+        let is_flags = flags.chars().all(|c| c.is_ascii_uppercase() || c == '.' || c == '|');
+        if !is_flags || name == "=" {
+            continue;
+        }
+
+        for n in name.split(',') {
+            if !n.is_empty() && !names.iter().any(|existing| existing == n) {
+                names.push(n.to_string());
+            }
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version_output() {
+        let output = "\
+ffmpeg version 6.1.1-3ubuntu5 Copyright (c) 2000-2023 the FFmpeg developers
+built with gcc 13 (Ubuntu 13.2.0-23ubuntu3)
+configuration: --prefix=/usr --enable-gpl --enable-libx264 --enable-libvidstab
+libavutil      58. 29.100 / 58. 29.100
+";
+        let (version, configuration) = parse_version_output(output);
+        assert_eq!(version, "6.1.1-3ubuntu5");
+        assert_eq!(configuration, vec!["--prefix=/usr", "--enable-gpl", "--enable-libx264", "--enable-libvidstab"]);
+        assert_eq!(parse_version_number(&version), (Some(6), Some(1)));
+        assert_eq!(parse_version_number("n7.0"), (Some(7), Some(0)));
+        assert_eq!(parse_version_number("N-112345-gabcdef"), (None, None));
+    }
+
+    #[test]
+    fn test_parse_encoders() {
+        let output = "\
+Encoders:
+ V..... = Video
+ A..... = Audio
+ S..... = Subtitle
+ .F.... = Frame-level multithreading
+ ------
+ V....D libx264              libx264 H.264 / AVC / MPEG-4 AVC / MPEG-4 part 10 (codec h264)
+ V....D libsvtav1            SVT-AV1(Scalable Video Technology for AV1) encoder (codec av1)
+ A....D aac                  AAC (Advanced Audio Coding)
+";
+        let caps = FFmpegCapabilities {
+            encoders: parse_list(output),
+            ..Default::default()
+        };
+        assert_eq!(caps.encoders, vec!["libx264", "libsvtav1", "aac"]);
+        assert_eq!(caps.first_encoder(H264_ENCODERS), Some("libx264".to_string()));
+        assert_eq!(caps.first_encoder(HEVC_ENCODERS), None);
+    }
+
+    #[test]
+    fn test_parse_muxers_and_filters() {
+        let muxers = "\
+ File formats:
+ D. = Demuxing supported
+ .E = Muxing supported
+ --
+  E matroska        Matroska
+  E mp4             MP4 (MPEG-4 Part 14)
+";
+        assert_eq!(parse_list(muxers), vec!["matroska", "mp4"]);
+
+        let filters = "\
+Filters:
+  T.. = Timeline support
+  .S. = Slice threading
+  ..C = Command support
+  A = Audio input/output
+  | = Source or sink filter
+ ... acopy             A->A       Copy the input audio unchanged to the output.
+ TSC loudnorm          A->A       EBU R128 loudness normalization
+ T.. vidstabdetect     V->V       Extract relative transformations, pass 1 of 2 for stabilization (see vidstabtransform for pass 2).
+ T.. vidstabtransform  V->V       Transform the frames, pass 2 of 2 for stabilization (see vidstabdetect for pass 1).
+";
+        let mut caps = FFmpegCapabilities {
+            filters: parse_list(filters),
+            ..Default::default()
+        };
+        caps.features = caps.detect_features();
+        assert_eq!(caps.filters, vec!["acopy", "loudnorm", "vidstabdetect", "vidstabtransform"]);
+        assert!(caps.features.loudnorm);
+        assert!(caps.features.vidstab);
+        assert!(!caps.features.cropdetect);
+    }
+}
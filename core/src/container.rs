@@ -0,0 +1,120 @@
+use crate::error::{AppError, validation_error};
+use crate::t;
+
+/// 容器格式的流复制兼容性描述
+///
+/// `None` 表示该类型的流不做限制，空切片表示该容器不支持此类型的流
+pub struct ContainerSpec {
+    pub ext: &'static str,
+    pub video: Option<&'static [&'static str]>,
+    pub audio: Option<&'static [&'static str]>,
+    pub subtitle: Option<&'static [&'static str]>,
+}
+
+const MP4_VIDEO: &[&str] = &["h264", "hevc", "av1", "vp9", "mpeg4", "mpeg2video", "mjpeg"];
+const MP4_AUDIO: &[&str] = &["aac", "mp3", "ac3", "eac3", "alac", "opus", "flac"];
+const MOV_VIDEO: &[&str] = &["h264", "hevc", "av1", "vp9", "mpeg4", "mpeg2video", "mjpeg", "prores", "dnxhd"];
+const MOV_AUDIO: &[&str] = &["aac", "mp3", "ac3", "eac3", "alac", "opus", "flac", "pcm_s16le", "pcm_s24le", "pcm_s16be", "pcm_s24be"];
+const WEBM_VIDEO: &[&str] = &["vp8", "vp9", "av1"];
+const WEBM_AUDIO: &[&str] = &["vorbis", "opus"];
+const TS_VIDEO: &[&str] = &["h264", "hevc", "mpeg2video", "mpeg1video"];
+const TS_AUDIO: &[&str] = &["aac", "mp3", "mp2", "ac3", "eac3", "opus"];
+
+/// 支持作为输出的容器格式
+pub const CONTAINERS: &[ContainerSpec] = &[
+    ContainerSpec { ext: "mp4", video: Some(MP4_VIDEO), audio: Some(MP4_AUDIO), subtitle: Some(&["mov_text"]) },
+    ContainerSpec { ext: "m4v", video: Some(MP4_VIDEO), audio: Some(MP4_AUDIO), subtitle: Some(&["mov_text"]) },
+    ContainerSpec { ext: "mov", video: Some(MOV_VIDEO), audio: Some(MOV_AUDIO), subtitle: Some(&["mov_text"]) },
+    ContainerSpec { ext: "mkv", video: None, audio: None, subtitle: Some(&["subrip", "ass", "ssa", "webvtt", "hdmv_pgs_subtitle", "dvd_subtitle"]) },
+    ContainerSpec { ext: "webm", video: Some(WEBM_VIDEO), audio: Some(WEBM_AUDIO), subtitle: Some(&["webvtt"]) },
+    ContainerSpec { ext: "ts", video: Some(TS_VIDEO), audio: Some(TS_AUDIO), subtitle: Some(&["dvb_subtitle"]) },
+];
+
+/// 规范化容器名称 (例如 ".MP4" -> "mp4")，并确认其受支持
+pub fn normalize_container(container: &str) -> Result<String, AppError> {
+    let normalized = container.trim().trim_start_matches('.').to_ascii_lowercase();
+    if find_container(&normalized).is_none() {
+        return Err(validation_error(t!("container.unsupported", container = container)));
+    }
+    Ok(normalized)
+}
+
+/// 查找容器格式描述
+pub fn find_container(ext: &str) -> Option<&'static ContainerSpec> {
+    CONTAINERS.iter().find(|c| c.ext.eq_ignore_ascii_case(ext))
+}
+
+fn codec_allowed(allowed: Option<&[&str]>, codec: &str) -> bool {
+    match allowed {
+        Some(list) => list.contains(&codec),
+        None => true,
+    }
+}
+
+/// 检查流编码能否以流复制的方式写入目标容器
+///
+/// 视频和音频不兼容时返回错误；字幕不兼容时返回 `Ok(false)`，
+/// 表示调用方应丢弃字幕流
+pub fn check_codec_compatibility(ext: &str, streams: &[(String, String)]) -> Result<bool, AppError> {
+    let spec = find_container(ext)
+        .ok_or_else(|| validation_error(t!("container.unsupported", container = ext)))?;
+
+    let mut keep_subtitles = true;
+    for (codec_type, codec_name) in streams {
+        let allowed = match codec_type.as_str() {
+            "video" => spec.video,
+            "audio" => spec.audio,
+            "subtitle" => {
+                if !codec_allowed(spec.subtitle, codec_name) {
+                    keep_subtitles = false;
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        if !codec_allowed(allowed, codec_name) {
+            return Err(validation_error(t!(
+                "container.codec_incompatible",
+                codec_type = codec_type, codec = codec_name, container = spec.ext
+            )));
+        }
+    }
+
+    Ok(keep_subtitles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn streams(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter().map(|(t, c)| (t.to_string(), c.to_string())).collect()
+    }
+
+    #[test]
+    fn test_normalize_container() {
+        assert_eq!(normalize_container(".MP4").unwrap(), "mp4");
+        assert_eq!(normalize_container("mkv").unwrap(), "mkv");
+        assert!(normalize_container("exe").is_err());
+    }
+
+    #[test]
+    fn test_check_codec_compatibility() {
+        // MKV 中的 H.264 + AAC 可以直接封装为 MP4
+        let s = streams(&[("video", "h264"), ("audio", "aac")]);
+        assert!(check_codec_compatibility("mp4", &s).unwrap());
+
+        // Vorbis 音频无法放入 MP4
+        let s = streams(&[("video", "h264"), ("audio", "vorbis")]);
+        assert!(check_codec_compatibility("mp4", &s).is_err());
+
+        // SRT 字幕无法放入 MP4，应丢弃字幕而不是报错
+        let s = streams(&[("video", "h264"), ("subtitle", "subrip")]);
+        assert!(!check_codec_compatibility("mp4", &s).unwrap());
+
+        // MKV 接受任意音视频编码
+        let s = streams(&[("video", "prores"), ("audio", "pcm_s24le")]);
+        assert!(check_codec_compatibility("mkv", &s).unwrap());
+    }
+}
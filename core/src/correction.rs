@@ -0,0 +1,347 @@
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::backend::{MediaBackend, ProcessBackend};
+use crate::capabilities::FFmpegCapabilities;
+use crate::error::{AppError, AppResult, validation_error};
+use crate::export::{audio_streams, reencode_export, AudioPlan, Encode, ExportRequest, VideoFilter};
+use crate::media::{get_stream_codecs_with, get_video_info_with, resolve_optional_range_with};
+use crate::subtitle::escape_filter_value;
+use crate::timestamp::TimePoint;
+use crate::utils::validate_input_path;
+use crate::video::CorrectionResult;
+use crate::t;
+
+/// hqdn3d 空域强度上限
+const MAX_DENOISE: f64 = 30.0;
+
+/// 反交错方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Deinterlace {
+    #[default]
+    Yadif,
+    Bwdif,      // 质量更好，速度较慢
+}
+
+impl FromStr for Deinterlace {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "yadif" => Ok(Deinterlace::Yadif),
+            "bwdif" => Ok(Deinterlace::Bwdif),
+            _ => Err(validation_error(t!("correction.deinterlace_unknown", method = s))),
+        }
+    }
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+fn default_denoise() -> f64 {
+    4.0
+}
+
+/// 滤镜链中的一步，按列表顺序应用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CorrectionFilter {
+    /// 反交错，只能作为第一步（其他滤镜会混合两场）
+    Deinterlace {
+        #[serde(default)]
+        method: Deinterlace,
+    },
+    /// hqdn3d 降噪，`strength` 为亮度空域强度，其余强度按 hqdn3d 默认比例换算
+    Denoise {
+        #[serde(default = "default_denoise")]
+        strength: f64,
+    },
+    /// 应用 `.cube` 3D LUT
+    Lut {
+        path: String,
+    },
+    /// eq 调整亮度、对比度、饱和度和伽马
+    Eq {
+        #[serde(default)]
+        brightness: f64,    // [-1, 1]，0 为不变
+        #[serde(default = "one")]
+        contrast: f64,      // [-1000, 1000]，1 为不变
+        #[serde(default = "one")]
+        saturation: f64,    // [0, 3]，1 为不变
+        #[serde(default = "one")]
+        gamma: f64,         // [0.1, 10]，1 为不变
+    },
+}
+
+impl FromStr for CorrectionFilter {
+    type Err = AppError;
+
+    /// 解析 `deinterlace[=yadif|bwdif]`、`denoise[=强度]`、`lut=文件.cube`
+    /// 和 `eq=brightness=0.05:contrast=1.1:saturation=1.2:gamma=1`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || validation_error(t!("correction.invalid_filter", filter = s));
+        let (name, value) = match s.trim().split_once('=') {
+            Some((name, value)) => (name.trim().to_ascii_lowercase(), Some(value.trim())),
+            None => (s.trim().to_ascii_lowercase(), None),
+        };
+        match (name.as_str(), value) {
+            ("deinterlace", method) => Ok(CorrectionFilter::Deinterlace {
+                method: method.map(str::parse).transpose()?.unwrap_or_default(),
+            }),
+            ("denoise", strength) => Ok(CorrectionFilter::Denoise {
+                strength: strength.map(|v| v.parse().map_err(|_| invalid())).transpose()?.unwrap_or(default_denoise()),
+            }),
+            ("lut", Some(path)) if !path.is_empty() => Ok(CorrectionFilter::Lut { path: path.to_string() }),
+            ("eq", Some(params)) => {
+                let (mut brightness, mut contrast, mut saturation, mut gamma) = (0.0, 1.0, 1.0, 1.0);
+                for param in params.split(':') {
+                    let (key, value) = param.split_once('=').ok_or_else(invalid)?;
+                    let value: f64 = value.trim().parse().map_err(|_| invalid())?;
+                    match key.trim().to_ascii_lowercase().as_str() {
+                        "brightness" => brightness = value,
+                        "contrast" => contrast = value,
+                        "saturation" => saturation = value,
+                        "gamma" => gamma = value,
+                        _ => return Err(invalid()),
+                    }
+                }
+                Ok(CorrectionFilter::Eq { brightness, contrast, saturation, gamma })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl CorrectionFilter {
+    /// 依赖的 ffmpeg 滤镜名
+    fn ffmpeg_filter(&self) -> &'static str {
+        match self {
+            CorrectionFilter::Deinterlace { method: Deinterlace::Yadif } => "yadif",
+            CorrectionFilter::Deinterlace { method: Deinterlace::Bwdif } => "bwdif",
+            CorrectionFilter::Denoise { .. } => "hqdn3d",
+            CorrectionFilter::Lut { .. } => "lut3d",
+            CorrectionFilter::Eq { .. } => "eq",
+        }
+    }
+
+    /// 校验参数并生成滤镜
+    fn filter(&self) -> AppResult<String> {
+        let out_of_range = |name: &str, min: f64, max: f64| {
+            validation_error(t!("correction.out_of_range", name = name, min = min, max = max))
+        };
+        match self {
+            // 每帧输出一帧，保持帧率和时长
+            CorrectionFilter::Deinterlace { .. } => Ok(format!("{}=mode=send_frame", self.ffmpeg_filter())),
+            CorrectionFilter::Denoise { strength } => {
+                if !(*strength > 0.0 && *strength <= MAX_DENOISE) {
+                    return Err(out_of_range("denoise", 0.0, MAX_DENOISE));
+                }
+                // hqdn3d 默认强度 4:3:6:4.5
+                Ok(format!("hqdn3d={}:{}:{}:{}", strength, strength * 0.75, strength * 1.5, strength * 1.125))
+            }
+            CorrectionFilter::Lut { path } => {
+                let path = validate_input_path(path)?;
+                let is_cube = path.extension().and_then(|e| e.to_str())
+                    .is_some_and(|e| e.eq_ignore_ascii_case("cube"));
+                if !is_cube {
+                    return Err(validation_error(t!("correction.lut_not_cube", path = path.display())));
+                }
+                // LUT 输出为 RGB，转回 4:2:0 以便编码
+                Ok(format!("lut3d=file={},format=yuv420p", escape_filter_value(&path.to_string_lossy())))
+            }
+            CorrectionFilter::Eq { brightness, contrast, saturation, gamma } => {
+                for (name, value, min, max) in [
+                    ("brightness", *brightness, -1.0, 1.0),
+                    ("contrast", *contrast, -1000.0, 1000.0),
+                    ("saturation", *saturation, 0.0, 3.0),
+                    ("gamma", *gamma, 0.1, 10.0),
+                ] {
+                    if !(min..=max).contains(&value) {
+                        return Err(out_of_range(name, min, max));
+                    }
+                }
+                Ok(format!("eq=brightness={}:contrast={}:saturation={}:gamma={}", brightness, contrast, saturation, gamma))
+            }
+        }
+    }
+}
+
+/// 按顺序校验滤镜并与 ffmpeg 的滤镜列表核对，返回 -vf 滤镜链
+fn filter_chain(filters: &[CorrectionFilter], caps: &FFmpegCapabilities) -> AppResult<String> {
+    if filters.is_empty() {
+        return Err(validation_error(t!("correction.empty")));
+    }
+    let mut chain = Vec::new();
+    for (i, filter) in filters.iter().enumerate() {
+        if i > 0 && matches!(filter, CorrectionFilter::Deinterlace { .. }) {
+            return Err(validation_error(t!("correction.deinterlace_first")));
+        }
+        if !caps.has_filter(filter.ffmpeg_filter()) {
+            return Err(validation_error(t!("correction.filter_unavailable", filter = filter.ffmpeg_filter())));
+        }
+        chain.push(filter.filter()?);
+    }
+    Ok(chain.join(","))
+}
+
+/// 对时间范围（或整个文件）应用调色和修复滤镜，输出到下一个版本文件
+///
+/// 视频重新编码，音频能放入输出容器时流复制
+#[allow(clippy::too_many_arguments)]
+pub fn correct_video(
+    input_path: &str,
+    start: Option<&TimePoint>,
+    end: Option<&TimePoint>,
+    filters: &[CorrectionFilter],
+    notes: Option<&str>,
+    output_dir: Option<&str>,
+    container: Option<&str>
+) -> AppResult<CorrectionResult> {
+    correct_video_with(&ProcessBackend, input_path, start, end, filters, notes, output_dir, container)
+}
+
+/// 使用指定后端应用滤镜，参数含义同 [`correct_video`]
+#[allow(clippy::too_many_arguments)]
+pub fn correct_video_with(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    start: Option<&TimePoint>,
+    end: Option<&TimePoint>,
+    filters: &[CorrectionFilter],
+    notes: Option<&str>,
+    output_dir: Option<&str>,
+    container: Option<&str>
+) -> AppResult<CorrectionResult> {
+    let request = ExportRequest::new(input_path, notes, output_dir, container)?;
+
+    // 先校验滤镜，避免探测和预留输出
+    let caps = backend.capabilities()?;
+    let chain = filter_chain(filters, &caps)?;
+
+    let info = get_video_info_with(backend, input_path)?;
+    let audio = audio_streams(get_stream_codecs_with(backend, input_path)?);
+    let range = resolve_optional_range_with(backend, input_path, start, end)?;
+    let span = range.map(|r| (r.start, r.end));
+    let duration = range.map(|r| r.duration().to_f64()).unwrap_or(info.duration);
+
+    let target = request.reserve(request.estimate_size(span, info.duration)?)?;
+    let encode = Encode {
+        input_path,
+        span,
+        extra_inputs: Vec::new(),
+        video: VideoFilter::Chain(chain.clone()),
+        audio: AudioPlan::Keep,
+    };
+    let exported = reencode_export(backend, &caps, target, &encode, &audio, duration)?;
+
+    let message = exported.message("correction.success", "correction.verification_failed");
+    Ok(CorrectionResult {
+        output_path: exported.output_path,
+        message,
+        verification: exported.verification,
+        filter_chain: chain,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::backend::{CommandOutput, MockBackend};
+    use crate::binaries::Tool;
+
+    fn caps(filters: &[&str]) -> FFmpegCapabilities {
+        FFmpegCapabilities {
+            encoders: vec!["libx264".into()],
+            filters: filters.iter().map(|f| f.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_filters() {
+        assert_eq!("deinterlace".parse::<CorrectionFilter>().unwrap(), CorrectionFilter::Deinterlace { method: Deinterlace::Yadif });
+        assert_eq!("deinterlace=BWDIF".parse::<CorrectionFilter>().unwrap(), CorrectionFilter::Deinterlace { method: Deinterlace::Bwdif });
+        assert_eq!("denoise=6".parse::<CorrectionFilter>().unwrap(), CorrectionFilter::Denoise { strength: 6.0 });
+        assert_eq!("lut=C:\\luts\\film.cube".parse::<CorrectionFilter>().unwrap(), CorrectionFilter::Lut { path: "C:\\luts\\film.cube".into() });
+        assert_eq!(
+            "eq=contrast=1.1:saturation=1.3".parse::<CorrectionFilter>().unwrap(),
+            CorrectionFilter::Eq { brightness: 0.0, contrast: 1.1, saturation: 1.3, gamma: 1.0 }
+        );
+        assert!("eq=hue=10".parse::<CorrectionFilter>().is_err());
+        assert!("lut".parse::<CorrectionFilter>().is_err());
+        assert!("sharpen".parse::<CorrectionFilter>().is_err());
+
+        // 前端传入的 JSON
+        let filter: CorrectionFilter = serde_json::from_value(serde_json::json!({ "type": "eq", "gamma": 1.2 })).unwrap();
+        assert_eq!(filter, CorrectionFilter::Eq { brightness: 0.0, contrast: 1.0, saturation: 1.0, gamma: 1.2 });
+    }
+
+    #[test]
+    fn test_filter_chain() {
+        let all = caps(&["yadif", "bwdif", "hqdn3d", "lut3d", "eq"]);
+        let filters = [
+            CorrectionFilter::Deinterlace { method: Deinterlace::Bwdif },
+            CorrectionFilter::Denoise { strength: 4.0 },
+            CorrectionFilter::Eq { brightness: 0.05, contrast: 1.1, saturation: 1.0, gamma: 1.0 },
+        ];
+        assert_eq!(
+            filter_chain(&filters, &all).unwrap(),
+            "bwdif=mode=send_frame,hqdn3d=4:3:6:4.5,eq=brightness=0.05:contrast=1.1:saturation=1:gamma=1"
+        );
+
+        // 反交错必须在最前面
+        let reordered = [filters[1].clone(), filters[0].clone()];
+        assert!(filter_chain(&reordered, &all).is_err());
+        // ffmpeg 不支持的滤镜
+        assert!(filter_chain(&filters, &caps(&["yadif", "hqdn3d", "eq"])).is_err());
+        // 参数越界
+        assert!(filter_chain(&[CorrectionFilter::Denoise { strength: 0.0 }], &all).is_err());
+        assert!(filter_chain(&[CorrectionFilter::Eq { brightness: 0.0, contrast: 1.0, saturation: 4.0, gamma: 1.0 }], &all).is_err());
+        assert!(filter_chain(&[], &all).is_err());
+    }
+
+    #[test]
+    fn test_correct_video_with_mock() {
+        let dir = std::env::temp_dir().join(format!("instant-cut-correction-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("clip.mp4");
+        fs::write(&input, vec![0u8; 1024]).unwrap();
+        let lut = dir.join("film.cube");
+        fs::write(&lut, b"LUT_3D_SIZE 2\n").unwrap();
+        fs::write(dir.join("film.3dl"), b"").unwrap();
+
+        let mock = MockBackend::new();
+        mock.set_default(Tool::FFprobe, CommandOutput::ok(serde_json::json!({
+            "streams": [
+                { "index": 0, "codec_type": "video", "codec_name": "h264", "width": 1280, "height": 720, "r_frame_rate": "25/1" },
+                { "index": 1, "codec_type": "audio", "codec_name": "aac" },
+            ],
+            "format": { "duration": "30.0" },
+        }).to_string()));
+        mock.set_output_contents(b"corrected");
+        mock.set_capabilities(caps(&["lut3d", "eq"]));
+
+        // 只接受 .cube
+        let other = [CorrectionFilter::Lut { path: dir.join("film.3dl").to_string_lossy().to_string() }];
+        assert!(correct_video_with(&mock, input.to_str().unwrap(), None, None, &other, None, None, None).is_err());
+        assert!(mock.calls_to(Tool::FFmpeg).is_empty());
+
+        let filters = [
+            CorrectionFilter::Lut { path: lut.to_string_lossy().to_string() },
+            CorrectionFilter::Eq { brightness: 0.0, contrast: 1.0, saturation: 1.2, gamma: 1.0 },
+        ];
+        let result = correct_video_with(&mock, input.to_str().unwrap(), None, None, &filters, None, None, None).unwrap();
+        assert!(result.verification.passed, "{:?}", result.verification);
+        assert!(result.filter_chain.starts_with("lut3d=file="));
+        assert!(result.filter_chain.ends_with(",format=yuv420p,eq=brightness=0:contrast=1:saturation=1.2:gamma=1"));
+
+        let args = &mock.calls_to(Tool::FFmpeg)[0];
+        assert!(args.windows(2).any(|w| w[0] == "-vf" && w[1] == result.filter_chain));
+        assert!(args.windows(2).any(|w| w == ["-c:a", "copy"]));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+use crate::backend::{MediaBackend, ProcessBackend};
+use crate::error::{AppResult, ffmpeg_error, validation_error};
+use crate::media::{get_video_info_with, resolve_optional_range_with};
+use crate::timestamp::TimePoint;
+use crate::transform::CropRect;
+use crate::utils::{check_command_success, validate_input_path};
+use crate::t;
+
+/// 黑色判定阈值（8 位亮度）
+const BLACK_LIMIT: u32 = 24;
+
+/// 裁剪区域与最常见结果相差不超过该像素数时视为一致
+const EDGE_TOLERANCE: i64 = 4;
+
+/// 黑边检测结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CropDetection {
+    pub crop: Option<CropRect>,     // 去掉黑边的裁剪区域，没有黑边时为空
+    pub confidence: f64,            // 与该区域一致的采样比例 (0-1)
+    pub samples: u32,               // 有效采样帧数
+    pub source_width: u32,          // 源的显示宽度
+    pub source_height: u32,         // 源的显示高度
+}
+
+/// 一帧的检测结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Rect {
+    width: i64,
+    height: i64,
+    x: i64,
+    y: i64,
+}
+
+impl Rect {
+    fn right(&self) -> i64 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> i64 {
+        self.y + self.height
+    }
+
+    fn is_close_to(&self, other: &Rect) -> bool {
+        (self.x - other.x).abs() <= EDGE_TOLERANCE
+            && (self.y - other.y).abs() <= EDGE_TOLERANCE
+            && (self.right() - other.right()).abs() <= EDGE_TOLERANCE
+            && (self.bottom() - other.bottom()).abs() <= EDGE_TOLERANCE
+    }
+}
+
+/// 检测时间范围（或整个文件）内的黑边
+///
+/// 只解码关键帧并逐帧运行 cropdetect，以最常见的裁剪区域为准，
+/// 合并与其一致的结果（取并集，避免暗场景裁掉画面内容）
+pub fn detect_crop(input_path: &str, start: Option<&TimePoint>, end: Option<&TimePoint>) -> AppResult<CropDetection> {
+    detect_crop_with(&ProcessBackend, input_path, start, end)
+}
+
+/// 使用指定后端检测黑边，参数含义同 [`detect_crop`]
+pub fn detect_crop_with(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    start: Option<&TimePoint>,
+    end: Option<&TimePoint>
+) -> AppResult<CropDetection> {
+    validate_input_path(input_path)?;
+    if !backend.capabilities()?.features.cropdetect {
+        return Err(validation_error(t!("cropdetect.unavailable")));
+    }
+
+    let info = get_video_info_with(backend, input_path)?;
+    let (source_width, source_height) = info.display_size();
+    let range = resolve_optional_range_with(backend, input_path, start, end)?;
+
+    let mut args: Vec<String> = vec!["-hide_banner".into(), "-nostats".into(), "-skip_frame".into(), "nokey".into()];
+    if let Some(range) = &range {
+        args.extend([
+            "-ss".into(), range.start.to_ffmpeg_seconds(),
+            "-t".into(), range.duration().to_ffmpeg_seconds(),
+        ]);
+    }
+    args.extend([
+        "-i".into(), input_path.into(),
+        "-map".into(), "0:v:0".into(),
+        // cropdetect 默认丢弃前两帧，只解码关键帧时会少掉两个样本
+        "-vf".into(), format!("cropdetect=limit={}:round=2:reset=1:skip=0", BLACK_LIMIT),
+        "-f".into(), "null".into(), "-".into(),
+    ]);
+
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    let output = backend.ffmpeg(&args)?;
+    check_command_success(&output, "ffmpeg")?;
+
+    let rects = parse_cropdetect_output(&String::from_utf8_lossy(&output.stderr));
+    let (rect, confidence) = aggregate(&rects)
+        .ok_or_else(|| ffmpeg_error(t!("cropdetect.no_samples")))?;
+
+    // 与整个画面相同时说明没有黑边
+    let full_frame = rect.x <= 0 && rect.y <= 0
+        && rect.right() >= source_width as i64 && rect.bottom() >= source_height as i64;
+    let crop = (!full_frame).then_some(CropRect {
+        width: rect.width as u32,
+        height: rect.height as u32,
+        x: Some(rect.x as u32),
+        y: Some(rect.y as u32),
+    });
+
+    Ok(CropDetection { crop, confidence, samples: rects.len() as u32, source_width, source_height })
+}
+
+/// 解析 cropdetect 每帧输出的 `crop=W:H:X:Y`，忽略全黑帧产生的无效区域
+fn parse_cropdetect_output(stderr: &str) -> Vec<Rect> {
+    stderr.lines()
+        .filter(|l| l.contains("cropdetect"))
+        .filter_map(|l| l.rsplit_once("crop="))
+        .filter_map(|(_, value)| {
+            let parts: Vec<i64> = value.trim().split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+            match parts[..] {
+                [width, height, x, y] if width > 0 && height > 0 && x >= 0 && y >= 0 => Some(Rect { width, height, x, y }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// 以出现次数最多的区域为准，合并与其一致的区域，返回合并结果和一致的比例
+fn aggregate(rects: &[Rect]) -> Option<(Rect, f64)> {
+    let mode = rects.iter()
+        .max_by_key(|candidate| rects.iter().filter(|r| r == candidate).count())?;
+    let agreeing: Vec<&Rect> = rects.iter().filter(|r| r.is_close_to(mode)).collect();
+
+    let x = agreeing.iter().map(|r| r.x).min()?;
+    let y = agreeing.iter().map(|r| r.y).min()?;
+    let right = agreeing.iter().map(|r| r.right()).max()?;
+    let bottom = agreeing.iter().map(|r| r.bottom()).max()?;
+    // 并集的宽高可能为奇数，向下取偶数以便直接用于 4:2:0 编码
+    let (width, height) = (right - x, bottom - y);
+    let rect = Rect { width: width - width % 2, height: height - height % 2, x, y };
+
+    Some((rect, agreeing.len() as f64 / rects.len() as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::backend::{CommandOutput, MockBackend};
+    use crate::binaries::Tool;
+    use crate::capabilities::{FeatureSupport, FFmpegCapabilities};
+
+    fn line(crop: &str) -> String {
+        format!("[Parsed_cropdetect_0 @ 0x55d0] x1:0 x2:1919 y1:140 y2:939 w:1920 h:800 x:0 y:140 pts:0 t:0.000000 limit:0.094118 crop={}\n", crop)
+    }
+
+    #[test]
+    fn test_parse_and_aggregate() {
+        let stderr = [
+            "Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'in.mp4':\n".to_string(),
+            line("1920:800:0:140"),
+            line("1920:800:0:140"),
+            // 暗场景裁得更多，并入一致的结果
+            line("1920:796:0:142"),
+            // 全黑帧
+            line("-1904:-1056:1912:1064"),
+            // 片头字幕
+            line("1280:200:320:440"),
+        ].concat();
+
+        let rects = parse_cropdetect_output(&stderr);
+        assert_eq!(rects.len(), 4);
+
+        let (rect, confidence) = aggregate(&rects).unwrap();
+        assert_eq!(rect, Rect { width: 1920, height: 800, x: 0, y: 140 });
+        assert_eq!(confidence, 0.75);
+        assert!(aggregate(&[]).is_none());
+    }
+
+    #[test]
+    fn test_detect_crop_with_mock() {
+        let dir = std::env::temp_dir().join(format!("instant-cut-cropdetect-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("clip.mp4");
+        fs::write(&input, b"video").unwrap();
+
+        let mock = MockBackend::new();
+        mock.set_default(Tool::FFprobe, CommandOutput::ok(serde_json::json!({
+            "streams": [{ "codec_type": "video", "codec_name": "h264", "width": 1920, "height": 1080, "r_frame_rate": "25/1" }],
+            "format": { "duration": "30.0" },
+        }).to_string()));
+        mock.respond(Tool::FFmpeg, CommandOutput {
+            code: Some(0),
+            stdout: Vec::new(),
+            stderr: [line("1920:800:0:140"), line("1920:800:0:140")].concat().into_bytes(),
+        });
+
+        // 不支持 cropdetect 时直接报错
+        let err = detect_crop_with(&mock, input.to_str().unwrap(), None, None);
+        assert!(err.is_err());
+        assert!(mock.calls_to(Tool::FFmpeg).is_empty());
+
+        mock.set_capabilities(FFmpegCapabilities {
+            features: FeatureSupport { cropdetect: true, ..Default::default() },
+            ..Default::default()
+        });
+        let detection = detect_crop_with(&mock, input.to_str().unwrap(), None, None).unwrap();
+        assert_eq!(detection.crop, Some(CropRect { width: 1920, height: 800, x: Some(0), y: Some(140) }));
+        assert_eq!((detection.confidence, detection.samples), (1.0, 2));
+
+        let args = &mock.calls_to(Tool::FFmpeg)[0];
+        assert!(args.windows(2).any(|w| w == ["-skip_frame", "nokey"]));
+        assert!(args.iter().any(|a| a.starts_with("cropdetect=") && a.ends_with(":skip=0")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
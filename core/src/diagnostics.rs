@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+use crate::error::stderr_tail;
+
+/// 识别出的 ffmpeg 失败类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticKind {
+    /// 编码无法放入目标容器
+    CodecNotSupportedInContainer,
+    /// 时间戳不单调递增
+    NonMonotonicTimestamps,
+    /// 没有读写权限
+    PermissionDenied,
+    /// 磁盘空间已满
+    NoSpaceLeft,
+    /// 输入数据损坏或格式无法识别
+    InvalidData,
+    /// 编码器不存在
+    UnknownEncoder,
+    /// 无法识别
+    Unknown,
+}
+
+/// 各失败类型在 stderr 中的特征（均为小写）
+///
+/// 按优先级排列：同一份 stderr 匹配多种类型时取第一个
+const PATTERNS: &[(DiagnosticKind, &[&str])] = &[
+    (DiagnosticKind::NoSpaceLeft, &["no space left on device"]),
+    (DiagnosticKind::PermissionDenied, &["permission denied", "operation not permitted"]),
+    (DiagnosticKind::UnknownEncoder, &["unknown encoder", "encoder not found", ") not found for output stream"]),
+    (DiagnosticKind::CodecNotSupportedInContainer, &[
+        "codec not currently supported in container",
+        "could not find tag for codec",
+        "are supported for webm",
+        "codec is not supported",
+        "subtitle codec",
+        "incorrect codec parameters",
+    ]),
+    (DiagnosticKind::NonMonotonicTimestamps, &[
+        "non monotonically increasing dts",
+        "non-monotonous dts",
+        "non-monotonic",
+    ]),
+    (DiagnosticKind::InvalidData, &[
+        "invalid data found when processing input",
+        "moov atom not found",
+        "invalid nal unit size",
+        "error while decoding",
+    ]),
+];
+
+/// 无法识别时，用于挑选相关行的关键词（均为小写）
+const GENERIC_MARKERS: &[&str] = &["error", "failed", "invalid", "cannot", "could not", "unable"];
+
+impl DiagnosticKind {
+    /// 稳定的错误代码
+    pub fn code(&self) -> &'static str {
+        match self {
+            DiagnosticKind::CodecNotSupportedInContainer => "FFMPEG_CODEC_NOT_SUPPORTED",
+            DiagnosticKind::NonMonotonicTimestamps => "FFMPEG_NON_MONOTONIC_TIMESTAMPS",
+            DiagnosticKind::PermissionDenied => "FFMPEG_PERMISSION_DENIED",
+            DiagnosticKind::NoSpaceLeft => "FFMPEG_NO_SPACE_LEFT",
+            DiagnosticKind::InvalidData => "FFMPEG_INVALID_DATA",
+            DiagnosticKind::UnknownEncoder => "FFMPEG_UNKNOWN_ENCODER",
+            DiagnosticKind::Unknown => "FFMPEG_ERROR",
+        }
+    }
+
+    /// 问题描述的消息键
+    pub fn summary_key(&self) -> &'static str {
+        match self {
+            DiagnosticKind::CodecNotSupportedInContainer => "diagnostics.codec_not_supported.summary",
+            DiagnosticKind::NonMonotonicTimestamps => "diagnostics.non_monotonic.summary",
+            DiagnosticKind::PermissionDenied => "diagnostics.permission_denied.summary",
+            DiagnosticKind::NoSpaceLeft => "diagnostics.no_space_left.summary",
+            DiagnosticKind::InvalidData => "diagnostics.invalid_data.summary",
+            DiagnosticKind::UnknownEncoder => "diagnostics.unknown_encoder.summary",
+            DiagnosticKind::Unknown => "diagnostics.unknown.summary",
+        }
+    }
+
+    /// 修复建议的消息键
+    pub fn suggestion_key(&self) -> &'static str {
+        match self {
+            DiagnosticKind::CodecNotSupportedInContainer => "diagnostics.codec_not_supported.suggestion",
+            DiagnosticKind::NonMonotonicTimestamps => "diagnostics.non_monotonic.suggestion",
+            DiagnosticKind::PermissionDenied => "diagnostics.permission_denied.suggestion",
+            DiagnosticKind::NoSpaceLeft => "diagnostics.no_space_left.suggestion",
+            DiagnosticKind::InvalidData => "diagnostics.invalid_data.suggestion",
+            DiagnosticKind::UnknownEncoder => "diagnostics.unknown_encoder.suggestion",
+            DiagnosticKind::Unknown => "diagnostics.unknown.suggestion",
+        }
+    }
+}
+
+/// stderr 诊断结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnosis {
+    pub kind: DiagnosticKind,
+    pub relevant_lines: Vec<String>,
+}
+
+impl Diagnosis {
+    /// 相关行拼接为一段文本
+    pub fn excerpt(&self) -> String {
+        self.relevant_lines.join("\n")
+    }
+}
+
+/// 分析 ffmpeg 的 stderr，识别失败类型并保留相关行
+pub fn diagnose(stderr: &str) -> Diagnosis {
+    let lines: Vec<&str> = stderr.lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .collect();
+
+    for (kind, patterns) in PATTERNS {
+        let matched = matching_lines(&lines, patterns);
+        if !matched.is_empty() {
+            return Diagnosis { kind: *kind, relevant_lines: matched };
+        }
+    }
+
+    // 无法识别：保留看起来像错误的行，都没有时保留末尾几行
+    let mut relevant = matching_lines(&lines, GENERIC_MARKERS);
+    if relevant.is_empty() {
+        relevant = stderr_tail(stderr).lines().map(|l| l.to_string()).collect();
+    }
+
+    Diagnosis { kind: DiagnosticKind::Unknown, relevant_lines: relevant }
+}
+
+fn matching_lines(lines: &[&str], patterns: &[&str]) -> Vec<String> {
+    let mut matched: Vec<String> = Vec::new();
+    for line in lines {
+        let lower = line.to_ascii_lowercase();
+        if patterns.iter().any(|p| lower.contains(p)) && !matched.iter().any(|m| m == line) {
+            matched.push(line.to_string());
+        }
+    }
+    matched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 所有 stderr 样本共有的版本信息头
+    const BANNER: &str = "ffmpeg version 6.1.1 Copyright (c) 2000-2023 the FFmpeg developers
+  built with gcc 13 (GCC)
+  configuration: --enable-gpl --enable-libx264
+  libavutil      58. 29.100 / 58. 29.100
+Input #0, matroska,webm, from 'input.mkv':
+  Duration: 00:01:00.02, start: 0.000000, bitrate: 1200 kb/s
+  Stream #0:0: Video: h264 (High), yuv420p(progressive), 1920x1080, 30 fps
+  Stream #0:1: Audio: vorbis, 48000 Hz, stereo, fltp";
+
+    fn sample(tail: &str) -> String {
+        format!("{}\n{}\n", BANNER, tail)
+    }
+
+    #[test]
+    fn test_codec_not_supported() {
+        let stderr = sample("\
+[mp4 @ 0x55d5c8a3c2c0] Could not find tag for codec vorbis in stream #1, codec not currently supported in container
+[out#0/mp4 @ 0x55d5c8a3b940] Could not write header (incorrect codec parameters ?): Invalid argument
+Conversion failed!");
+        let diagnosis = diagnose(&stderr);
+        assert_eq!(diagnosis.kind, DiagnosticKind::CodecNotSupportedInContainer);
+        assert_eq!(diagnosis.relevant_lines.len(), 2);
+        assert!(!diagnosis.excerpt().contains("ffmpeg version"));
+    }
+
+    #[test]
+    fn test_webm_codec_not_supported() {
+        let stderr = sample("\
+[webm @ 0x5581c3a0e700] Only VP8 or VP9 or AV1 video and Vorbis or Opus audio and WebVTT subtitles are supported for WebM.
+[out#0/webm @ 0x5581c3a0d3c0] Could not write header (incorrect codec parameters ?): Invalid argument");
+        assert_eq!(diagnose(&stderr).kind, DiagnosticKind::CodecNotSupportedInContainer);
+    }
+
+    #[test]
+    fn test_non_monotonic_timestamps() {
+        let stderr = sample("\
+[mp4 @ 0x5632b1e0a6c0] Application provided invalid, non monotonically increasing dts to muxer in stream 0: 1536 >= 1024
+[out#0/mp4 @ 0x5632b1e09f40] Error muxing a packet
+Conversion failed!");
+        let diagnosis = diagnose(&stderr);
+        assert_eq!(diagnosis.kind, DiagnosticKind::NonMonotonicTimestamps);
+        assert_eq!(diagnosis.relevant_lines.len(), 1);
+    }
+
+    #[test]
+    fn test_permission_denied() {
+        let stderr = sample("\
+[out#0/mp4 @ 0x55f1d3f5a740] Error opening output /media/card/clip_1.mp4: Permission denied
+Error opening output file /media/card/clip_1.mp4.
+Error opening output files: Permission denied");
+        let diagnosis = diagnose(&stderr);
+        assert_eq!(diagnosis.kind, DiagnosticKind::PermissionDenied);
+        assert_eq!(diagnosis.relevant_lines.len(), 2);
+    }
+
+    #[test]
+    fn test_no_space_left() {
+        let stderr = sample("\
+[mp4 @ 0x55a0f0b7e5c0] Error writing trailer: No space left on device
+[out#0/mp4 @ 0x55a0f0b7d9c0] Error closing file: No space left on device
+Conversion failed!");
+        assert_eq!(diagnose(&stderr).kind, DiagnosticKind::NoSpaceLeft);
+    }
+
+    #[test]
+    fn test_invalid_data() {
+        let stderr = "\
+ffmpeg version 6.1.1 Copyright (c) 2000-2023 the FFmpeg developers
+[mov,mp4,m4a,3gp,3g2,mj2 @ 0x5600d8e4f3c0] moov atom not found
+broken.mp4: Invalid data found when processing input
+";
+        let diagnosis = diagnose(stderr);
+        assert_eq!(diagnosis.kind, DiagnosticKind::InvalidData);
+        assert_eq!(diagnosis.relevant_lines.len(), 2);
+    }
+
+    #[test]
+    fn test_unknown_encoder() {
+        let stderr = sample("\
+[vost#0:0 @ 0x55e3c6b2a200] Unknown encoder 'libsvtav1'
+Error selecting an encoder");
+        let diagnosis = diagnose(&stderr);
+        assert_eq!(diagnosis.kind, DiagnosticKind::UnknownEncoder);
+        assert_eq!(diagnosis.excerpt(), "[vost#0:0 @ 0x55e3c6b2a200] Unknown encoder 'libsvtav1'");
+    }
+
+    #[test]
+    fn test_unknown_failure_keeps_error_lines() {
+        let stderr = sample("\
+[out#0/mp4 @ 0x55e3c6b2a200] Something unexpected happened
+Error while filtering: Resource temporarily unavailable");
+        let diagnosis = diagnose(&stderr);
+        assert_eq!(diagnosis.kind, DiagnosticKind::Unknown);
+        assert_eq!(diagnosis.relevant_lines, vec!["Error while filtering: Resource temporarily unavailable"]);
+    }
+}
@@ -0,0 +1,241 @@
+use std::fmt;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::{json, Value};
+use crate::t;
+use crate::diagnostics::DiagnosticKind;
+
+/// stderr 摘要保留的最大行数
+const STDERR_TAIL_LINES: usize = 10;
+
+/// 应用程序的统一错误类型
+///
+/// 序列化为前端可识别的对象: `{ code, params, message }`
+#[derive(Debug)]
+pub enum AppError {
+    /// FFmpeg 相关错误
+    FFmpegError {
+        message: String,
+        stderr_tail: Option<String>
+    },
+
+    /// 经过诊断、可识别原因的 FFmpeg 执行失败
+    FFmpegFailure {
+        kind: DiagnosticKind,
+        stderr_tail: String
+    },
+
+    /// FFprobe 相关错误
+    FFprobeError {
+        message: String,
+        stderr_tail: Option<String>
+    },
+
+    /// 文件系统错误
+    FilesystemError(String),
+
+    /// 存储空间不足错误
+    InsufficientSpace {
+        needed_gb: f64,
+        available_gb: f64,
+        path: String
+    },
+
+    /// JSON 解析错误
+    JsonError(String),
+
+    /// 输入验证错误
+    ValidationError(String),
+
+    /// IO 错误
+    IoError(std::io::Error),
+
+    /// 路径相关错误
+    PathError(String),
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AppError::FFmpegError { message, .. } => write!(f, "{}", t!("error.ffmpeg", message = message)),
+            AppError::FFmpegFailure { kind, .. } => {
+                write!(f, "{}", t!("error.ffmpeg_failure",
+                                   summary = t!(kind.summary_key()),
+                                   suggestion = t!(kind.suggestion_key())))
+            }
+            AppError::FFprobeError { message, .. } => write!(f, "{}", t!("error.ffprobe", message = message)),
+            AppError::FilesystemError(msg) => write!(f, "{}", t!("error.filesystem", message = msg)),
+            AppError::InsufficientSpace { needed_gb, available_gb, path } => {
+                write!(f, "{}", t!("error.insufficient_space",
+                                   needed_gb = format!("{:.2}", needed_gb),
+                                   available_gb = format!("{:.2}", available_gb),
+                                   path = path))
+            }
+            AppError::JsonError(msg) => write!(f, "{}", t!("error.json", message = msg)),
+            AppError::ValidationError(msg) => write!(f, "{}", t!("error.validation", message = msg)),
+            AppError::IoError(err) => write!(f, "{}", t!("error.io", message = err)),
+            AppError::PathError(msg) => write!(f, "{}", t!("error.path", message = msg)),
+        }
+    }
+}
+
+impl AppError {
+    /// 稳定的错误代码，供前端区分错误类型
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::FFmpegError { .. } => "FFMPEG_ERROR",
+            AppError::FFmpegFailure { kind, .. } => kind.code(),
+            AppError::FFprobeError { .. } => "FFPROBE_ERROR",
+            AppError::FilesystemError(_) => "FILESYSTEM_ERROR",
+            AppError::InsufficientSpace { .. } => "INSUFFICIENT_SPACE",
+            AppError::JsonError(_) => "JSON_ERROR",
+            AppError::ValidationError(_) => "VALIDATION_ERROR",
+            AppError::IoError(_) => "IO_ERROR",
+            AppError::PathError(_) => "PATH_ERROR",
+        }
+    }
+
+    /// 错误参数，供前端展示或处理
+    pub fn params(&self) -> Value {
+        match self {
+            AppError::FFmpegError { stderr_tail, .. } | AppError::FFprobeError { stderr_tail, .. } => {
+                json!({ "stderr_tail": stderr_tail })
+            }
+            AppError::FFmpegFailure { kind, stderr_tail } => {
+                json!({ "kind": kind, "suggestion": t!(kind.suggestion_key()), "stderr_tail": stderr_tail })
+            }
+            AppError::InsufficientSpace { needed_gb, available_gb, path } => {
+                json!({ "needed_gb": needed_gb, "available_gb": available_gb, "path": path })
+            }
+            AppError::IoError(err) => json!({ "kind": format!("{:?}", err.kind()) }),
+            _ => json!({}),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("params", &self.params())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
+        AppError::IoError(err)
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(err: serde_json::Error) -> Self {
+        AppError::JsonError(t!("json.parse_failed", error = err))
+    }
+}
+
+/// 用于 Tauri 命令的 Result 类型别名
+pub type AppResult<T> = Result<T, AppError>;
+
+/// 创建 FFmpeg 相关错误
+pub fn ffmpeg_error<S: Into<String>>(msg: S) -> AppError {
+    AppError::FFmpegError { message: msg.into(), stderr_tail: None }
+}
+
+/// 创建 FFprobe 相关错误
+pub fn ffprobe_error<S: Into<String>>(msg: S) -> AppError {
+    AppError::FFprobeError { message: msg.into(), stderr_tail: None }
+}
+
+/// 截取 stderr 的最后几行
+pub fn stderr_tail(stderr: &str) -> String {
+    let lines: Vec<&str> = stderr.lines()
+        .map(|l| l.trim_end())
+        .filter(|l| !l.is_empty())
+        .collect();
+    let start = lines.len().saturating_sub(STDERR_TAIL_LINES);
+    lines[start..].join("\n")
+}
+
+/// 创建文件系统相关错误
+pub fn filesystem_error<S: Into<String>>(msg: S) -> AppError {
+    AppError::FilesystemError(msg.into())
+}
+
+/// 创建验证错误
+pub fn validation_error<S: Into<String>>(msg: S) -> AppError {
+    AppError::ValidationError(msg.into())
+}
+
+/// 创建路径相关错误
+pub fn path_error<S: Into<String>>(msg: S) -> AppError {
+    AppError::PathError(msg.into())
+}
+
+/// 将字节转换为 GB
+pub fn bytes_to_gb(bytes: u64) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_display() {
+        let err = ffmpeg_error("执行失败");
+        assert_eq!(err.to_string(), "FFmpeg 错误: 执行失败");
+    }
+
+    #[test]
+    fn test_error_serialize() {
+        let err = AppError::InsufficientSpace {
+            needed_gb: 10.5,
+            available_gb: 5.0,
+            path: "/tmp".to_string()
+        };
+        let value = serde_json::to_value(&err).unwrap();
+        assert_eq!(value["code"], "INSUFFICIENT_SPACE");
+        assert_eq!(value["params"]["needed_gb"], 10.5);
+        assert_eq!(value["params"]["available_gb"], 5.0);
+        assert_eq!(value["params"]["path"], "/tmp");
+        assert_eq!(value["message"], err.to_string());
+
+        let err = AppError::FFmpegError {
+            message: "ffmpeg 执行失败".to_string(),
+            stderr_tail: Some("Invalid data found".to_string())
+        };
+        let value = serde_json::to_value(&err).unwrap();
+        assert_eq!(value["code"], "FFMPEG_ERROR");
+        assert_eq!(value["params"]["stderr_tail"], "Invalid data found");
+    }
+
+    #[test]
+    fn test_stderr_tail() {
+        let stderr = (1..=15).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n");
+        let tail = stderr_tail(&stderr);
+        assert!(tail.starts_with("line 6"));
+        assert!(tail.ends_with("line 15"));
+    }
+
+    #[test]
+    fn test_insufficient_space_error() {
+        let err = AppError::InsufficientSpace {
+            needed_gb: 10.5,
+            available_gb: 5.0,
+            path: "/tmp".to_string()
+        };
+        let display = err.to_string();
+        assert!(display.contains("磁盘空间不足"));
+        assert!(display.contains("10.50 GB"));
+        assert!(display.contains("5.00 GB"));
+    }
+
+    #[test]
+    fn test_bytes_to_gb() {
+        assert_eq!(bytes_to_gb(1024 * 1024 * 1024), 1.0);
+        assert_eq!(bytes_to_gb(2 * 1024 * 1024 * 1024), 2.0);
+    }
+}
\ No newline at end of file
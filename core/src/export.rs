@@ -0,0 +1,240 @@
+use std::path::PathBuf;
+use crate::backend::MediaBackend;
+use crate::capabilities::FFmpegCapabilities;
+use crate::container::{check_codec_compatibility, normalize_container};
+use crate::error::{AppResult, path_error};
+use crate::join::{full_encoder, run_ffmpeg, Encoder};
+use crate::media::{check_disk_space_for_output, estimate_output_size, reserve_next_output};
+use crate::output::OutputReservation;
+use crate::timestamp::Rational;
+use crate::utils::{validate_input_path, validate_output_dir};
+use crate::verify::{verify_output, StreamCounts, VerificationReport};
+use crate::t;
+
+/// 导出的输入文件和输出位置，创建时即校验路径和容器
+pub(crate) struct ExportRequest<'a> {
+    pub(crate) input_path: &'a str,
+    validated_path: PathBuf,
+    notes: Option<&'a str>,
+    output_dir: Option<PathBuf>,
+    container: Option<String>,
+}
+
+impl<'a> ExportRequest<'a> {
+    pub(crate) fn new(
+        input_path: &'a str,
+        notes: Option<&'a str>,
+        output_dir: Option<&str>,
+        container: Option<&str>
+    ) -> AppResult<Self> {
+        Ok(ExportRequest {
+            input_path,
+            validated_path: validate_input_path(input_path)?,
+            notes,
+            output_dir: output_dir.map(validate_output_dir).transpose()?,
+            container: container.map(normalize_container).transpose()?,
+        })
+    }
+
+    /// 用户指定的输出容器
+    pub(crate) fn container(&self) -> Option<&str> {
+        self.container.as_deref()
+    }
+
+    /// 按源文件大小估算 `span` 范围（为空时为整个文件）的输出大小
+    pub(crate) fn estimate_size(&self, span: Option<(Rational, Rational)>, total_duration: f64) -> AppResult<u64> {
+        let (start, end) = span.map(|(s, e)| (s.to_f64(), e.to_f64())).unwrap_or((0.0, total_duration));
+        estimate_output_size(&self.validated_path, start, end, total_duration)
+    }
+
+    /// 预留下一个版本文件并检查磁盘空间
+    pub(crate) fn reserve(&self, estimated_size: u64) -> AppResult<ExportTarget> {
+        let reservation = reserve_next_output(
+            self.input_path, self.notes, self.output_dir.as_deref(), self.container.as_deref()
+        )?;
+        check_disk_space_for_output(reservation.final_path(), estimated_size)?;
+        let ext = reservation.final_path().extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        Ok(ExportTarget { reservation, ext })
+    }
+}
+
+/// 已预留的导出目标
+pub(crate) struct ExportTarget {
+    reservation: OutputReservation,
+    pub(crate) ext: String,     // 输出扩展名（小写）
+}
+
+impl ExportTarget {
+    /// ffmpeg 的写入目标（临时文件）
+    pub(crate) fn output(&self) -> AppResult<&str> {
+        self.reservation.temp_path().to_str()
+            .ok_or_else(|| path_error(t!("path.not_utf8")))
+    }
+
+    /// 检查临时文件并重命名为最终文件名，然后校验时长和流
+    pub(crate) fn finish(
+        self,
+        backend: &dyn MediaBackend,
+        expected_duration: f64,
+        expected_streams: StreamCounts,
+        reencoded: bool
+    ) -> AppResult<Exported> {
+        let output_path = self.reservation.commit()?.to_string_lossy().to_string();
+        let verification = verify_output(backend, &output_path, expected_duration, expected_streams, reencoded, false)?;
+        Ok(Exported { output_path, verification })
+    }
+}
+
+/// 已提交并校验的输出
+pub(crate) struct Exported {
+    pub(crate) output_path: String,
+    pub(crate) verification: VerificationReport,
+}
+
+impl Exported {
+    /// 按校验结果选择成功或校验失败的消息
+    pub(crate) fn message(&self, success: &str, failed: &str) -> String {
+        let key = if self.verification.passed { success } else { failed };
+        t!(key, path = self.output_path)
+    }
+}
+
+/// 视频滤镜
+pub(crate) enum VideoFilter {
+    Chain(String),      // -vf 滤镜链，作用于第一路视频
+    Graph(String),      // -filter_complex 滤镜图，输出到 [vout]
+}
+
+/// 音频处理方式，总是保留所有音频流
+pub(crate) enum AudioPlan {
+    Keep,               // 能放入输出容器时流复制，否则重新编码
+    Filter(String),     // 经 -af 滤镜后重新编码
+    Drop,               // 去掉音频
+}
+
+/// 一次重新编码的输入和滤镜
+pub(crate) struct Encode<'a> {
+    pub(crate) input_path: &'a str,
+    pub(crate) span: Option<(Rational, Rational)>,     // 输入的时间范围，为空时为整个文件
+    pub(crate) extra_inputs: Vec<String>,               // 其他输入，滤镜图中从 [1:v] 起编号
+    pub(crate) video: VideoFilter,
+    pub(crate) audio: AudioPlan,
+}
+
+impl Encode<'_> {
+    /// ffmpeg 参数；`copy_audio` 为真时 [`AudioPlan::Keep`] 流复制音频，`format` 插在输出路径之前
+    pub(crate) fn args(&self, encoder: &Encoder, copy_audio: bool, format: &[&str], output: &str) -> Vec<String> {
+        let mut args: Vec<String> = vec!["-y".into()];
+        if let Some((start, end)) = self.span {
+            args.extend([
+                "-ss".into(), start.to_ffmpeg_seconds(),
+                "-t".into(), (end - start).to_ffmpeg_seconds(),
+            ]);
+        }
+        args.extend(["-i".into(), self.input_path.into()]);
+        for input in &self.extra_inputs {
+            args.extend(["-i".into(), input.clone()]);
+        }
+        match &self.video {
+            VideoFilter::Chain(chain) => args.extend([
+                "-map".into(), "0:v:0".into(),
+                "-vf".into(), chain.clone(),
+            ]),
+            VideoFilter::Graph(graph) => args.extend([
+                "-filter_complex".into(), graph.clone(),
+                "-map".into(), "[vout]".into(),
+            ]),
+        }
+        args.extend(encoder.video.iter().cloned());
+        match &self.audio {
+            AudioPlan::Keep => {
+                args.extend(["-map".into(), "0:a?".into()]);
+                if copy_audio {
+                    args.extend(["-c:a".into(), "copy".into()]);
+                } else {
+                    args.extend(encoder.audio.iter().cloned());
+                }
+            }
+            AudioPlan::Filter(filter) => {
+                args.extend(["-map".into(), "0:a?".into(), "-af".into(), filter.clone()]);
+                args.extend(encoder.audio.iter().cloned());
+            }
+            AudioPlan::Drop => args.push("-an".into()),
+        }
+        args.push("-sn".into());
+        args.extend(format.iter().map(|a| a.to_string()));
+        args.push(output.into());
+        args
+    }
+}
+
+/// 重新编码到已预留的目标，提交并按 `expected_duration` 校验
+///
+/// 视频用整体重新编码的编码器，输出一路视频和 `source_audio` 中的所有音频流（去掉音频时没有音频流），不含字幕
+pub(crate) fn reencode_export(
+    backend: &dyn MediaBackend,
+    caps: &FFmpegCapabilities,
+    target: ExportTarget,
+    encode: &Encode,
+    source_audio: &[(String, String)],
+    expected_duration: f64
+) -> AppResult<Exported> {
+    let encoder = full_encoder(caps, &target.ext)?;
+    let copy_audio = check_codec_compatibility(&target.ext, source_audio).is_ok();
+    run_ffmpeg(backend, encode.args(&encoder, copy_audio, &[], target.output()?))?;
+
+    let audio = match encode.audio {
+        AudioPlan::Drop => 0,
+        _ => source_audio.len() as u32,
+    };
+    let expected_streams = StreamCounts { video: 1, audio, subtitle: 0 };
+    target.finish(backend, expected_duration, expected_streams, true)
+}
+
+/// 源文件中的音频流
+pub(crate) fn audio_streams(streams: Vec<(String, String)>) -> Vec<(String, String)> {
+    streams.into_iter().filter(|(t, _)| t == "audio").collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoder() -> Encoder {
+        Encoder {
+            video: vec!["-c:v".into(), "libx264".into()],
+            audio: vec!["-c:a".into(), "aac".into()],
+        }
+    }
+
+    #[test]
+    fn test_encode_args() {
+        let encode = Encode {
+            input_path: "in.mp4",
+            span: Some((Rational::new(1, 2).unwrap(), Rational::from_integer(3))),
+            extra_inputs: vec!["logo.png".into()],
+            video: VideoFilter::Graph("[0:v][1:v]overlay[vout]".into()),
+            audio: AudioPlan::Keep,
+        };
+        assert_eq!(encode.args(&encoder(), true, &[], "out.mp4"), [
+            "-y", "-ss", "0.5", "-t", "2.5", "-i", "in.mp4", "-i", "logo.png",
+            "-filter_complex", "[0:v][1:v]overlay[vout]", "-map", "[vout]", "-c:v", "libx264",
+            "-map", "0:a?", "-c:a", "copy", "-sn", "out.mp4",
+        ]);
+
+        let encode = Encode {
+            input_path: "in.mp4",
+            span: None,
+            extra_inputs: Vec::new(),
+            video: VideoFilter::Chain("hflip".into()),
+            audio: AudioPlan::Drop,
+        };
+        assert_eq!(encode.args(&encoder(), true, &["-f", "matroska"], "out.mkv"), [
+            "-y", "-i", "in.mp4", "-map", "0:v:0", "-vf", "hflip", "-c:v", "libx264",
+            "-an", "-sn", "-f", "matroska", "out.mkv",
+        ]);
+    }
+}
@@ -0,0 +1,623 @@
+use std::sync::RwLock;
+
+/// 支持的界面语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    ZhCn,
+}
+
+impl Locale {
+    /// 所有支持的语言
+    pub const ALL: &'static [Locale] = &[Locale::En, Locale::ZhCn];
+
+    /// 语言代码 (例如 "zh-CN")
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::ZhCn => "zh-CN",
+        }
+    }
+
+    /// 解析语言代码，接受 "en"、"en-US"、"zh"、"zh_CN" 等形式
+    pub fn parse(code: &str) -> Option<Self> {
+        let lang = code.trim().split(['-', '_', '.']).next()?.to_ascii_lowercase();
+        match lang.as_str() {
+            "en" => Some(Locale::En),
+            "zh" => Some(Locale::ZhCn),
+            _ => None,
+        }
+    }
+
+    /// 根据系统语言选择，无法识别时使用英文
+    pub fn system() -> Self {
+        sys_locale::get_locale()
+            .and_then(|code| Self::parse(&code))
+            .unwrap_or(Locale::En)
+    }
+
+    fn catalog(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::ZhCn => ZH_CN,
+        }
+    }
+}
+
+/// 当前语言，启动时由设置或系统语言决定
+static LOCALE: RwLock<Locale> = RwLock::new(Locale::ZhCn);
+
+/// 设置当前语言
+pub fn set_locale(locale: Locale) {
+    *LOCALE.write().unwrap_or_else(|e| e.into_inner()) = locale;
+}
+
+/// 获取当前语言
+pub fn current_locale() -> Locale {
+    *LOCALE.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 按指定语言翻译消息，`{name}` 形式的占位符由 `args` 替换
+///
+/// 缺失的键回退到英文，仍缺失时返回键本身
+pub fn translate_in(locale: Locale, key: &str, args: &[(&str, String)]) -> String {
+    let template = lookup(locale.catalog(), key)
+        .or_else(|| lookup(EN, key))
+        .unwrap_or(key);
+
+    let mut message = template.to_string();
+    for (name, value) in args {
+        message = message.replace(&format!("{{{}}}", name), value);
+    }
+    message
+}
+
+/// 按当前语言翻译消息
+pub fn translate(key: &str, args: &[(&str, String)]) -> String {
+    translate_in(current_locale(), key, args)
+}
+
+fn lookup(catalog: &'static [(&'static str, &'static str)], key: &str) -> Option<&'static str> {
+    catalog.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+/// 翻译消息: `t!("key")` 或 `t!("key", name = value, ...)`
+#[macro_export]
+macro_rules! t {
+    ($key:expr) => {
+        $crate::i18n::translate($key, &[])
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::translate($key, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
+
+/// 所有消息键
+pub const KEYS: &[&str] = &[
+    "error.ffmpeg",
+    "error.ffmpeg_failure",
+    "error.ffprobe",
+    "error.filesystem",
+    "error.insufficient_space",
+    "error.json",
+    "error.validation",
+    "error.io",
+    "error.path",
+    "json.parse_failed",
+    "app.temp_cleanup_failed",
+    "ffmpeg.not_installed",
+    "ffmpeg.setting_rejected",
+    "ffmpeg.spawn_failed",
+    "ffprobe.spawn_failed",
+    "command.failed",
+    "probe.output_invalid",
+    "probe.json_invalid",
+    "probe.no_streams",
+    "probe.no_video_stream",
+    "probe.no_duration",
+    "container.unsupported",
+    "container.codec_incompatible",
+    "path.not_found",
+    "path.not_file",
+    "path.output_dir_not_found",
+    "path.output_not_dir",
+    "path.output_dir_readonly",
+    "path.no_output_dir",
+    "path.no_parent_dir",
+    "path.no_file_name",
+    "path.not_utf8",
+    "path.canonicalize_failed",
+    "disk.query_failed",
+    "file.size_unreadable",
+    "naming.invalid_version",
+    "output.reserve_failed",
+    "output.temp_create_failed",
+    "output.not_found",
+    "output.empty",
+    "output.exists",
+    "output.rename_failed",
+    "validation.start_negative",
+    "validation.end_before_start",
+    "validation.start_out_of_range",
+    "validation.end_out_of_range",
+    "validation.time_negative",
+    "validation.time_out_of_range",
+    "time.invalid",
+    "time.invalid_timecode",
+    "time.drop_frame_unsupported",
+    "time.frame_rate_unknown",
+    "time.before_source_start",
+    "verify.decode_failed",
+    "cut.success",
+    "cut.verification_failed",
+    "still.format_unsupported",
+    "still.no_times",
+    "still.invalid_size",
+    "still.success",
+    "animation.format_unsupported",
+    "animation.invalid_fps",
+    "animation.invalid_width",
+    "animation.dither_unsupported",
+    "animation.success",
+    "animation.mismatch",
+    "audio.no_stream",
+    "audio.track_not_found",
+    "audio.format_unsupported",
+    "audio.invalid_bitrate",
+    "audio.bitrate_lossless",
+    "audio.success_copy",
+    "audio.success_transcode",
+    "audio.downmix_unknown",
+    "audio.invalid_gain",
+    "audio.invalid_fade",
+    "audio.mute_with_loudness",
+    "audio.loudness_multiple_tracks",
+    "loudness.preset_unknown",
+    "loudness.measure_failed",
+    "loudness.silent",
+    "join.no_ranges",
+    "join.invalid_range",
+    "join.transition_unsupported",
+    "join.invalid_effect",
+    "join.range_too_short",
+    "join.no_encoder",
+    "join.success",
+    "join.verification_failed",
+    "transform.empty",
+    "transform.invalid_crop",
+    "transform.crop_out_of_bounds",
+    "transform.invalid_rotation",
+    "transform.invalid_size",
+    "transform.odd_size",
+    "transform.pad_requires_fit",
+    "transform.invalid_color",
+    "transform.scale_mode_unknown",
+    "transform.flip_unknown",
+    "transform.success",
+    "transform.size_mismatch",
+    "transform.verification_failed",
+    "cropdetect.unavailable",
+    "cropdetect.no_samples",
+    "speed.invalid",
+    "speed.no_change",
+    "speed.success",
+    "speed.verification_failed",
+    "subtitle.format_unsupported",
+    "subtitle.read_failed",
+    "subtitle.write_failed",
+    "subtitle.not_utf8",
+    "subtitle.sidecar_exists",
+    "subtitle.track_not_found",
+    "subtitle.no_stream",
+    "subtitle.bitmap",
+    "subtitle.extract_success",
+    "subtitle.burn_unavailable",
+    "subtitle.burn_success",
+    "subtitle.burn_verification_failed",
+    "overlay.position_unknown",
+    "overlay.preset_not_found",
+    "overlay.timecode_not_last",
+    "overlay.invalid_opacity",
+    "overlay.invalid_scale",
+    "overlay.invalid_font_size",
+    "overlay.empty",
+    "overlay.drawtext_unavailable",
+    "overlay.success",
+    "overlay.verification_failed",
+    "settings.invalid",
+    "settings.preset_skipped",
+    "correction.deinterlace_unknown",
+    "correction.invalid_filter",
+    "correction.out_of_range",
+    "correction.lut_not_cube",
+    "correction.empty",
+    "correction.deinterlace_first",
+    "correction.filter_unavailable",
+    "correction.success",
+    "correction.verification_failed",
+    "diagnostics.codec_not_supported.summary",
+    "diagnostics.codec_not_supported.suggestion",
+    "diagnostics.non_monotonic.summary",
+    "diagnostics.non_monotonic.suggestion",
+    "diagnostics.permission_denied.summary",
+    "diagnostics.permission_denied.suggestion",
+    "diagnostics.no_space_left.summary",
+    "diagnostics.no_space_left.suggestion",
+    "diagnostics.invalid_data.summary",
+    "diagnostics.invalid_data.suggestion",
+    "diagnostics.unknown_encoder.summary",
+    "diagnostics.unknown_encoder.suggestion",
+    "diagnostics.unknown.summary",
+    "diagnostics.unknown.suggestion",
+];
+
+const EN: &[(&str, &str)] = &[
+    ("error.ffmpeg", "FFmpeg error: {message}"),
+    ("error.ffmpeg_failure", "FFmpeg failed: {summary}. Suggestion: {suggestion}"),
+    ("error.ffprobe", "FFprobe error: {message}"),
+    ("error.filesystem", "Filesystem error: {message}"),
+    ("error.insufficient_space", "Insufficient disk space. Needed: {needed_gb} GB, available: {available_gb} GB, path: {path}"),
+    ("error.json", "JSON error: {message}"),
+    ("error.validation", "Invalid input: {message}"),
+    ("error.io", "IO error: {message}"),
+    ("error.path", "Path error: {message}"),
+    ("json.parse_failed", "Failed to parse JSON: {error}"),
+    ("app.temp_cleanup_failed", "Failed to clean up temporary files: {error}"),
+    ("ffmpeg.not_installed", "ffmpeg or ffprobe is not installed. Please install ffmpeg first."),
+    ("ffmpeg.setting_rejected", "The configured {tool} path cannot be run and was ignored: {path}"),
+    ("ffmpeg.spawn_failed", "Failed to run FFmpeg: {error}"),
+    ("ffprobe.spawn_failed", "Failed to run FFprobe: {error}"),
+    ("command.failed", "{command} failed: {stderr}"),
+    ("probe.output_invalid", "Failed to read output: {error}"),
+    ("probe.json_invalid", "Failed to parse JSON: {error}"),
+    ("probe.no_streams", "No stream information found"),
+    ("probe.no_video_stream", "No video stream found"),
+    ("probe.no_duration", "Could not determine the video duration"),
+    ("container.unsupported", "Unsupported output container: {container}"),
+    ("container.codec_incompatible", "The {codec_type} codec ({codec}) cannot be copied into a {container} container"),
+    ("path.not_found", "File not found: {path}"),
+    ("path.not_file", "Path is not a file: {path}"),
+    ("path.output_dir_not_found", "Output directory not found: {path}"),
+    ("path.output_not_dir", "Output path is not a directory: {path}"),
+    ("path.output_dir_readonly", "Output directory is not writable: {path}"),
+    ("path.no_output_dir", "Could not determine the output directory"),
+    ("path.no_parent_dir", "Could not determine the file's directory"),
+    ("path.no_file_name", "Could not determine the file name"),
+    ("path.not_utf8", "Path conversion failed"),
+    ("path.canonicalize_failed", "Could not resolve the canonical path: {error}"),
+    ("disk.query_failed", "Failed to query disk space: {error}"),
+    ("file.size_unreadable", "Could not read the file size: {error}"),
+    ("naming.invalid_version", "Invalid version number: {version}"),
+    ("output.reserve_failed", "Could not reserve an output file name"),
+    ("output.temp_create_failed", "Could not create the temporary file: {error}"),
+    ("output.not_found", "Output file not found: {error}"),
+    ("output.empty", "Output file is empty"),
+    ("output.exists", "Target file already exists: {path}"),
+    ("output.rename_failed", "Failed to rename the output file: {error}"),
+    ("validation.start_negative", "Start time cannot be negative"),
+    ("validation.end_before_start", "End time must be greater than start time"),
+    ("validation.start_out_of_range", "Start time exceeds the video duration"),
+    ("validation.end_out_of_range", "End time exceeds the video duration"),
+    ("validation.time_negative", "Time cannot be negative"),
+    ("validation.time_out_of_range", "Time must be before the end of the video"),
+    ("time.invalid", "Invalid time: {value}. Use seconds, a frame number such as 1234f, or a timecode such as 00:01:02:03"),
+    ("time.invalid_timecode", "Invalid timecode: {value}"),
+    ("time.drop_frame_unsupported", "Drop-frame timecode requires 29.97 or 59.94 fps, but the video is {fps} fps"),
+    ("time.frame_rate_unknown", "The video frame rate is unknown, so frame numbers and timecodes cannot be used"),
+    ("time.before_source_start", "Timecode {value} is before the source start timecode {start}"),
+    ("verify.decode_failed", "Decode check failed"),
+    ("cut.success", "Cut complete. The new file was saved as: {path}"),
+    ("cut.verification_failed", "Cut complete, but output verification failed. The new file was saved as: {path}"),
+    ("still.format_unsupported", "Unsupported image format: {format}"),
+    ("still.no_times", "No time points specified"),
+    ("still.invalid_size", "Width and height must be greater than 0"),
+    ("still.success", "Exported {count} still(s). First file: {path}"),
+    ("animation.format_unsupported", "Unsupported animation format: {format}"),
+    ("animation.invalid_fps", "Frame rate must be between {min} and {max}"),
+    ("animation.invalid_width", "Width must be greater than 0"),
+    ("animation.dither_unsupported", "Unsupported dither mode: {dither}"),
+    ("animation.success", "Animation exported: {path}"),
+    ("animation.mismatch", "Animation {path} has {actual}, but {expected} was estimated"),
+    ("audio.no_stream", "The file has no audio stream"),
+    ("audio.track_not_found", "Audio stream #{index} not found"),
+    ("audio.format_unsupported", "Unsupported audio format: {format}"),
+    ("audio.invalid_bitrate", "Bitrate must be between {min} and {max} kbps"),
+    ("audio.bitrate_lossless", "{format} is lossless and does not take a bitrate"),
+    ("audio.success_copy", "Audio extracted without re-encoding: {path}"),
+    ("audio.success_transcode", "Audio extracted as {codec}: {path}"),
+    ("audio.downmix_unknown", "Unknown downmix: {value} (use stereo or mono)"),
+    ("audio.invalid_gain", "Gain must be between -{max} and {max} dB"),
+    ("audio.invalid_fade", "Fade lengths must not be negative or longer than the range"),
+    ("audio.mute_with_loudness", "Muted audio cannot be loudness-normalized"),
+    ("audio.loudness_multiple_tracks", "Loudness normalization applies to a single audio track; {count} tracks are kept, drop the others first"),
+    ("loudness.preset_unknown", "Unknown loudness preset: {preset} (use streaming, podcast or broadcast)"),
+    ("loudness.measure_failed", "Could not read the loudness measurement from ffmpeg"),
+    ("loudness.silent", "The audio is silent and cannot be normalized"),
+    ("join.no_ranges", "No ranges to export"),
+    ("join.invalid_range", "Invalid range: {value} (use START..END)"),
+    ("join.transition_unsupported", "Unsupported transition: {transition}"),
+    ("join.invalid_effect", "Fade and transition durations must be positive numbers of seconds"),
+    ("join.range_too_short", "Range {index} is shorter than its fades and transitions"),
+    ("join.no_encoder", "No suitable encoder is available to render fades and transitions"),
+    ("join.success", "Joined {count} ranges into: {path}"),
+    ("join.verification_failed", "Export finished but verification failed: {path}"),
+    ("transform.empty", "No transform was requested"),
+    ("transform.invalid_crop", "Invalid crop: {value} (use W:H or W:H:X:Y)"),
+    ("transform.crop_out_of_bounds", "The crop area must lie within the {width}x{height} frame"),
+    ("transform.invalid_rotation", "Rotation must be 90, 180 or 270 degrees, got {degrees}"),
+    ("transform.invalid_size", "Width and height must be even numbers between 2 and {max}"),
+    ("transform.odd_size", "The output size {width}x{height} must have an even width and height"),
+    ("transform.pad_requires_fit", "Padding needs both a width and a height with the fit scale mode"),
+    ("transform.invalid_color", "Invalid pad colour: {color}"),
+    ("transform.scale_mode_unknown", "Unknown scale mode: {mode} (use fit, fill or stretch)"),
+    ("transform.flip_unknown", "Unknown flip: {flip} (use horizontal, vertical or both)"),
+    ("transform.success", "Exported {width}x{height} to: {path}"),
+    ("transform.size_mismatch", "Output size {actual} does not match the expected {expected}"),
+    ("transform.verification_failed", "Export finished but verification failed: {path}"),
+    ("cropdetect.unavailable", "This ffmpeg build has no cropdetect filter"),
+    ("cropdetect.no_samples", "No frames could be analysed for black bars"),
+    ("speed.invalid", "Speed must be between {min}x and {max}x"),
+    ("speed.no_change", "Set a speed other than 1x or enable reverse"),
+    ("speed.success", "Exported to: {path}"),
+    ("speed.verification_failed", "Export finished but verification failed: {path}"),
+    ("subtitle.format_unsupported", "Unsupported subtitle format: {format} (use srt or ass)"),
+    ("subtitle.read_failed", "Failed to read subtitle {path}: {error}"),
+    ("subtitle.write_failed", "Failed to write subtitle {path}: {error}"),
+    ("subtitle.not_utf8", "Subtitle {path} is not UTF-8 text and was not copied to the output"),
+    ("subtitle.sidecar_exists", "Subtitle {path} already exists and was not overwritten"),
+    ("subtitle.track_not_found", "Subtitle stream {track} not found"),
+    ("subtitle.no_stream", "The file has no subtitle streams"),
+    ("subtitle.bitmap", "{codec} is an image-based subtitle and cannot be converted to text; burn it into the video instead"),
+    ("subtitle.extract_success", "Extracted {count} subtitles to: {path}"),
+    ("subtitle.burn_unavailable", "This FFmpeg build has no subtitles filter (libass)"),
+    ("subtitle.burn_success", "Exported to: {path}"),
+    ("subtitle.burn_verification_failed", "Export finished but verification failed: {path}"),
+    ("overlay.position_unknown", "Unknown position: {position} (use top-left, top-right, bottom-left, bottom-right or center)"),
+    ("overlay.preset_not_found", "Overlay preset not found: {name}"),
+    ("overlay.timecode_not_last", "{timecode} can only be used at the end of the text"),
+    ("overlay.invalid_opacity", "Watermark opacity must be between 0 and 1"),
+    ("overlay.invalid_scale", "Watermark scale must be greater than 0 and at most 1"),
+    ("overlay.invalid_font_size", "Font size must be greater than 0"),
+    ("overlay.empty", "Specify a watermark image or text"),
+    ("overlay.drawtext_unavailable", "This FFmpeg build has no drawtext filter (libfreetype)"),
+    ("overlay.success", "Exported to: {path}"),
+    ("overlay.verification_failed", "Export finished but verification failed: {path}"),
+    ("settings.invalid", "Settings file {path} is invalid and was ignored: {error}"),
+    ("settings.preset_skipped", "Skipped overlay preset #{index} in the settings: {error}"),
+    ("correction.deinterlace_unknown", "Unknown deinterlace method: {method} (use yadif or bwdif)"),
+    ("correction.invalid_filter", "Invalid filter: {filter} (use deinterlace[=yadif/bwdif], denoise[=STRENGTH], lut=FILE.cube or eq=brightness=B:contrast=C:saturation=S:gamma=G)"),
+    ("correction.out_of_range", "{name} must be between {min} and {max}"),
+    ("correction.lut_not_cube", "Only .cube LUT files are supported: {path}"),
+    ("correction.empty", "Specify at least one filter"),
+    ("correction.deinterlace_first", "Deinterlacing must be the first filter"),
+    ("correction.filter_unavailable", "This FFmpeg build has no {filter} filter"),
+    ("correction.success", "Exported to: {path}"),
+    ("correction.verification_failed", "Export finished but verification failed: {path}"),
+    ("diagnostics.codec_not_supported.summary", "a stream's codec is not supported by the output container"),
+    ("diagnostics.codec_not_supported.suggestion", "choose a container that supports the codec (MKV accepts almost everything) or re-encode the stream"),
+    ("diagnostics.non_monotonic.summary", "the source has non-monotonic timestamps"),
+    ("diagnostics.non_monotonic.suggestion", "move the start point to a nearby keyframe, or remux the source to MKV first"),
+    ("diagnostics.permission_denied.summary", "permission denied"),
+    ("diagnostics.permission_denied.suggestion", "choose an output directory you can write to, or check the file permissions"),
+    ("diagnostics.no_space_left.summary", "no space left on the device"),
+    ("diagnostics.no_space_left.suggestion", "free up disk space or choose an output directory on another drive"),
+    ("diagnostics.invalid_data.summary", "the input data is corrupt or in an unrecognized format"),
+    ("diagnostics.invalid_data.suggestion", "check that the file is complete and plays correctly, or try a different source"),
+    ("diagnostics.unknown_encoder.summary", "the required encoder is not available in this ffmpeg build"),
+    ("diagnostics.unknown_encoder.suggestion", "install an ffmpeg build that includes the encoder, or choose a different output format"),
+    ("diagnostics.unknown.summary", "unknown error"),
+    ("diagnostics.unknown.suggestion", "see the ffmpeg output for details"),
+];
+
+const ZH_CN: &[(&str, &str)] = &[
+    ("error.ffmpeg", "FFmpeg 错误: {message}"),
+    ("error.ffmpeg_failure", "FFmpeg 执行失败: {summary}。建议: {suggestion}"),
+    ("error.ffprobe", "FFprobe 错误: {message}"),
+    ("error.filesystem", "文件系统错误: {message}"),
+    ("error.insufficient_space", "磁盘空间不足。需要: {needed_gb} GB，可用: {available_gb} GB，路径: {path}"),
+    ("error.json", "JSON 解析错误: {message}"),
+    ("error.validation", "输入验证错误: {message}"),
+    ("error.io", "IO 错误: {message}"),
+    ("error.path", "路径错误: {message}"),
+    ("json.parse_failed", "JSON 解析失败: {error}"),
+    ("app.temp_cleanup_failed", "清理临时文件失败: {error}"),
+    ("ffmpeg.not_installed", "ffmpeg 或 ffprobe 未安装。请先安装 ffmpeg。"),
+    ("ffmpeg.setting_rejected", "设置中指定的 {tool} 路径无法运行，已忽略：{path}"),
+    ("ffmpeg.spawn_failed", "执行 FFmpeg 命令失败: {error}"),
+    ("ffprobe.spawn_failed", "执行 FFprobe 命令失败: {error}"),
+    ("command.failed", "{command} 执行失败: {stderr}"),
+    ("probe.output_invalid", "解析输出失败: {error}"),
+    ("probe.json_invalid", "解析 JSON 失败: {error}"),
+    ("probe.no_streams", "未找到流信息"),
+    ("probe.no_video_stream", "未找到视频流"),
+    ("probe.no_duration", "无法获取视频时长"),
+    ("container.unsupported", "不支持的输出容器格式: {container}"),
+    ("container.codec_incompatible", "{codec_type} 编码 ({codec}) 无法直接复制到 {container} 容器中"),
+    ("path.not_found", "文件不存在: {path}"),
+    ("path.not_file", "路径不是文件: {path}"),
+    ("path.output_dir_not_found", "输出目录不存在: {path}"),
+    ("path.output_not_dir", "输出路径不是目录: {path}"),
+    ("path.output_dir_readonly", "输出目录不可写: {path}"),
+    ("path.no_output_dir", "无法获取输出目录"),
+    ("path.no_parent_dir", "无法获取文件目录"),
+    ("path.no_file_name", "无法获取文件名"),
+    ("path.not_utf8", "路径转换失败"),
+    ("path.canonicalize_failed", "无法获取规范路径: {error}"),
+    ("disk.query_failed", "获取磁盘空间失败: {error}"),
+    ("file.size_unreadable", "无法读取文件大小: {error}"),
+    ("naming.invalid_version", "无效的版本号: {version}"),
+    ("output.reserve_failed", "无法预留输出文件名"),
+    ("output.temp_create_failed", "无法创建临时文件: {error}"),
+    ("output.not_found", "输出文件未找到: {error}"),
+    ("output.empty", "输出文件为空"),
+    ("output.exists", "目标文件已存在: {path}"),
+    ("output.rename_failed", "重命名输出文件失败: {error}"),
+    ("validation.start_negative", "开始时间不能为负数"),
+    ("validation.end_before_start", "结束时间必须大于开始时间"),
+    ("validation.start_out_of_range", "开始时间超出视频时长"),
+    ("validation.end_out_of_range", "结束时间超出视频时长"),
+    ("validation.time_negative", "时间不能为负数"),
+    ("validation.time_out_of_range", "时间必须早于视频结尾"),
+    ("time.invalid", "无效的时间: {value}。请使用秒数、帧号（如 1234f）或时间码（如 00:01:02:03）"),
+    ("time.invalid_timecode", "无效的时间码: {value}"),
+    ("time.drop_frame_unsupported", "丢帧时间码只适用于 29.97 或 59.94 fps，当前视频为 {fps} fps"),
+    ("time.frame_rate_unknown", "无法获取视频帧率，不能使用帧号或时间码"),
+    ("time.before_source_start", "时间码 {value} 早于源文件的起始时间码 {start}"),
+    ("verify.decode_failed", "解码检查失败"),
+    ("cut.success", "视频剪辑完成。新文件已保存为: {path}"),
+    ("cut.verification_failed", "视频剪辑完成，但输出校验未通过。新文件已保存为: {path}"),
+    ("still.format_unsupported", "不支持的图片格式: {format}"),
+    ("still.no_times", "未指定时间点"),
+    ("still.invalid_size", "宽度和高度必须大于 0"),
+    ("still.success", "已导出 {count} 张静帧。第一个文件: {path}"),
+    ("animation.format_unsupported", "不支持的动图格式: {format}"),
+    ("animation.invalid_fps", "帧率必须在 {min} 到 {max} 之间"),
+    ("animation.invalid_width", "宽度必须大于 0"),
+    ("animation.dither_unsupported", "不支持的抖动算法: {dither}"),
+    ("animation.success", "动图导出成功: {path}"),
+    ("animation.mismatch", "动图 {path} 为 {actual}，与估算的 {expected} 不符"),
+    ("audio.no_stream", "文件中没有音频流"),
+    ("audio.track_not_found", "未找到音频流 #{index}"),
+    ("audio.format_unsupported", "不支持的音频格式: {format}"),
+    ("audio.invalid_bitrate", "码率必须在 {min} 到 {max} kbps 之间"),
+    ("audio.bitrate_lossless", "{format} 为无损格式，不能设置码率"),
+    ("audio.success_copy", "音频已提取（未重新编码）: {path}"),
+    ("audio.success_transcode", "音频已转码为 {codec} 并提取: {path}"),
+    ("audio.downmix_unknown", "未知的下混方式: {value}（可选 stereo、mono）"),
+    ("audio.invalid_gain", "增益必须在 -{max} 到 {max} dB 之间"),
+    ("audio.invalid_fade", "淡入淡出时长不能为负数或超过剪辑范围"),
+    ("audio.mute_with_loudness", "静音的音频不能进行响度标准化"),
+    ("audio.loudness_multiple_tracks", "响度标准化只适用于单个音轨，当前保留了 {count} 个音轨，请先移除其他音轨"),
+    ("loudness.preset_unknown", "未知的响度预设: {preset}（可选 streaming、podcast、broadcast）"),
+    ("loudness.measure_failed", "无法从 ffmpeg 输出中读取响度测量结果"),
+    ("loudness.silent", "音频为静音，无法进行响度标准化"),
+    ("join.no_ranges", "没有要导出的范围"),
+    ("join.invalid_range", "无效的范围: {value}（格式为 开始..结束）"),
+    ("join.transition_unsupported", "不支持的转场: {transition}"),
+    ("join.invalid_effect", "淡入淡出和转场时长必须为正的秒数"),
+    ("join.range_too_short", "第 {index} 个范围比其淡入淡出和转场时长更短"),
+    ("join.no_encoder", "没有可用于渲染淡入淡出和转场的编码器"),
+    ("join.success", "已将 {count} 个范围导出到: {path}"),
+    ("join.verification_failed", "导出完成但校验未通过: {path}"),
+    ("transform.empty", "没有指定任何变换"),
+    ("transform.invalid_crop", "无效的裁剪区域: {value}（格式为 宽:高 或 宽:高:X:Y）"),
+    ("transform.crop_out_of_bounds", "裁剪区域必须位于 {width}x{height} 的画面之内"),
+    ("transform.invalid_rotation", "旋转角度必须为 90、180 或 270 度，当前为 {degrees}"),
+    ("transform.invalid_size", "宽度和高度必须为 2 到 {max} 之间的偶数"),
+    ("transform.odd_size", "输出尺寸 {width}x{height} 的宽高必须为偶数"),
+    ("transform.pad_requires_fit", "补边需要同时指定宽度和高度，且缩放方式为 fit"),
+    ("transform.invalid_color", "无效的补边颜色: {color}"),
+    ("transform.scale_mode_unknown", "未知的缩放方式: {mode}（可选 fit、fill、stretch）"),
+    ("transform.flip_unknown", "未知的翻转方向: {flip}（可选 horizontal、vertical、both）"),
+    ("transform.success", "已导出 {width}x{height} 到: {path}"),
+    ("transform.size_mismatch", "输出尺寸 {actual} 与预期的 {expected} 不符"),
+    ("transform.verification_failed", "导出完成但校验未通过: {path}"),
+    ("cropdetect.unavailable", "当前 ffmpeg 不包含 cropdetect 滤镜"),
+    ("cropdetect.no_samples", "没有可用于检测黑边的画面"),
+    ("speed.invalid", "速度必须在 {min} 到 {max} 倍之间"),
+    ("speed.no_change", "请设置 1 倍以外的速度或启用倒放"),
+    ("speed.success", "已导出到: {path}"),
+    ("speed.verification_failed", "导出完成但校验未通过: {path}"),
+    ("subtitle.format_unsupported", "不支持的字幕格式: {format}（请使用 srt 或 ass）"),
+    ("subtitle.read_failed", "读取字幕 {path} 失败: {error}"),
+    ("subtitle.write_failed", "写入字幕 {path} 失败: {error}"),
+    ("subtitle.not_utf8", "字幕 {path} 不是 UTF-8 文本，未随剪辑输出"),
+    ("subtitle.sidecar_exists", "字幕 {path} 已存在，未覆盖"),
+    ("subtitle.track_not_found", "找不到字幕流 {track}"),
+    ("subtitle.no_stream", "文件中没有字幕流"),
+    ("subtitle.bitmap", "{codec} 是图形字幕，无法转换为文本，请改为烧录到画面中"),
+    ("subtitle.extract_success", "已提取 {count} 条字幕到: {path}"),
+    ("subtitle.burn_unavailable", "当前 FFmpeg 不支持 subtitles 滤镜（libass）"),
+    ("subtitle.burn_success", "已导出到: {path}"),
+    ("subtitle.burn_verification_failed", "导出完成但校验未通过: {path}"),
+    ("overlay.position_unknown", "未知的位置: {position}（可用 top-left、top-right、bottom-left、bottom-right、center）"),
+    ("overlay.preset_not_found", "找不到叠加预设: {name}"),
+    ("overlay.timecode_not_last", "{timecode} 只能位于文字末尾"),
+    ("overlay.invalid_opacity", "水印不透明度必须在 0 到 1 之间"),
+    ("overlay.invalid_scale", "水印比例必须大于 0 且不超过 1"),
+    ("overlay.invalid_font_size", "字号必须大于 0"),
+    ("overlay.empty", "请指定水印图片或文字"),
+    ("overlay.drawtext_unavailable", "当前 FFmpeg 不支持 drawtext 滤镜（libfreetype）"),
+    ("overlay.success", "已导出到: {path}"),
+    ("overlay.verification_failed", "导出完成但校验未通过: {path}"),
+    ("settings.invalid", "设置文件 {path} 无效，已忽略: {error}"),
+    ("settings.preset_skipped", "已跳过设置中的第 {index} 个叠加预设: {error}"),
+    ("correction.deinterlace_unknown", "未知的反交错方式: {method}（可用 yadif、bwdif）"),
+    ("correction.invalid_filter", "无效的滤镜: {filter}（可用 deinterlace[=yadif/bwdif]、denoise[=强度]、lut=文件.cube、eq=brightness=B:contrast=C:saturation=S:gamma=G）"),
+    ("correction.out_of_range", "{name} 必须在 {min} 到 {max} 之间"),
+    ("correction.lut_not_cube", "只支持 .cube 格式的 LUT 文件: {path}"),
+    ("correction.empty", "请至少指定一个滤镜"),
+    ("correction.deinterlace_first", "反交错必须是第一个滤镜"),
+    ("correction.filter_unavailable", "当前 FFmpeg 不支持 {filter} 滤镜"),
+    ("correction.success", "已导出到: {path}"),
+    ("correction.verification_failed", "导出完成但校验未通过: {path}"),
+    ("diagnostics.codec_not_supported.summary", "流编码不受输出容器支持"),
+    ("diagnostics.codec_not_supported.suggestion", "选择支持该编码的容器（MKV 几乎支持所有编码），或重新编码该流"),
+    ("diagnostics.non_monotonic.summary", "源文件的时间戳不是单调递增的"),
+    ("diagnostics.non_monotonic.suggestion", "将开始时间移动到附近的关键帧，或先将源文件封装为 MKV"),
+    ("diagnostics.permission_denied.summary", "没有访问权限"),
+    ("diagnostics.permission_denied.suggestion", "选择有写入权限的输出目录，或检查文件权限"),
+    ("diagnostics.no_space_left.summary", "磁盘空间已满"),
+    ("diagnostics.no_space_left.suggestion", "清理磁盘空间，或选择其他磁盘上的输出目录"),
+    ("diagnostics.invalid_data.summary", "输入数据损坏或格式无法识别"),
+    ("diagnostics.invalid_data.suggestion", "检查文件是否完整、能否正常播放，或更换源文件"),
+    ("diagnostics.unknown_encoder.summary", "当前 ffmpeg 不包含所需的编码器"),
+    ("diagnostics.unknown_encoder.suggestion", "安装包含该编码器的 ffmpeg 版本，或选择其他输出格式"),
+    ("diagnostics.unknown.summary", "未知错误"),
+    ("diagnostics.unknown.suggestion", "请查看 ffmpeg 输出了解详情"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 提取模板中的占位符名称
+    fn placeholders(template: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = template.split('{')
+            .skip(1)
+            .filter_map(|part| part.split('}').next())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_catalogs_cover_all_keys() {
+        for locale in Locale::ALL {
+            let catalog = locale.catalog();
+            for key in KEYS {
+                assert!(lookup(catalog, key).is_some(), "{} 缺少键: {}", locale.code(), key);
+            }
+            for (key, _) in catalog {
+                assert!(KEYS.contains(key), "{} 包含未声明的键: {}", locale.code(), key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_catalog_placeholders_match() {
+        for key in KEYS {
+            let expected = placeholders(lookup(EN, key).unwrap());
+            for locale in Locale::ALL {
+                let actual = placeholders(lookup(locale.catalog(), key).unwrap());
+                assert_eq!(actual, expected, "{} 的占位符与英文不一致: {}", locale.code(), key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_translate_in() {
+        let args = [("path", "/tmp/a.mp4".to_string())];
+        assert_eq!(translate_in(Locale::En, "path.not_found", &args), "File not found: /tmp/a.mp4");
+        assert_eq!(translate_in(Locale::ZhCn, "path.not_found", &args), "文件不存在: /tmp/a.mp4");
+        assert_eq!(translate_in(Locale::En, "missing.key", &[]), "missing.key");
+    }
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!(Locale::parse("en-US"), Some(Locale::En));
+        assert_eq!(Locale::parse("zh_CN.UTF-8"), Some(Locale::ZhCn));
+        assert_eq!(Locale::parse("zh-Hans"), Some(Locale::ZhCn));
+        assert_eq!(Locale::parse("fr"), None);
+    }
+}
@@ -0,0 +1,906 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::backend::{MediaBackend, ProcessBackend};
+use crate::capabilities::{FFmpegCapabilities, H264_ENCODERS};
+use crate::container::check_codec_compatibility;
+use serde_json::Value;
+use crate::error::{AppError, AppResult, ffprobe_error, path_error, validation_error};
+use crate::export::{Exported, ExportRequest};
+use crate::media::{get_stream_codecs_with, list_keyframes_with, resolve_range_with};
+use crate::output::WorkDir;
+use crate::timestamp::{Rational, TimePoint};
+use crate::utils::check_command_success;
+use crate::verify::StreamCounts;
+use crate::video::{JoinMode, JoinResult};
+use crate::t;
+
+/// 支持的 xfade 转场类型
+pub const TRANSITIONS: &[&str] = &[
+    "fade", "dissolve", "fadeblack", "fadewhite", "wipeleft", "wiperight", "wipeup", "wipedown",
+    "slideleft", "slideright", "slideup", "slidedown", "circleopen", "circleclose", "radial", "pixelize",
+];
+
+/// 只指定转场时长时使用的转场类型
+const DEFAULT_TRANSITION: &str = "fade";
+
+/// 只指定转场类型时使用的转场时长（秒）
+const DEFAULT_TRANSITION_SECONDS: f64 = 1.0;
+
+/// 重新编码片段的 x264 / x265 质量参数，尽量接近源画质
+const REENCODE_CRF: &str = "18";
+
+/// NVENC / QSV / AMF 恒定质量模式下与 CRF 18 接近的量化参数
+const HARDWARE_QUALITY: &str = "19";
+
+/// VideoToolbox 只在 Apple 芯片上支持恒定质量，统一使用较高的平均码率
+const VIDEOTOOLBOX_BITRATE: &str = "20M";
+
+/// 重新编码音频的码率
+const REENCODE_AUDIO_BITRATE: &str = "192k";
+
+/// 一个导出范围
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimeRange {
+    pub start: TimePoint,
+    pub end: TimePoint,
+}
+
+impl FromStr for TimeRange {
+    type Err = AppError;
+
+    /// 解析 `开始..结束`，两端格式同 [`TimePoint`]，例如 `10..25` 或 `00:01:00:00..1800f`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s.split_once("..")
+            .ok_or_else(|| validation_error(t!("join.invalid_range", value = s)))?;
+        Ok(TimeRange { start: start.parse()?, end: end.parse()? })
+    }
+}
+
+/// 多段导出的淡入淡出和转场选项
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JoinOptions {
+    pub fade_in: Option<f64>,               // 输出开头的画面和声音淡入时长（秒）
+    pub fade_out: Option<f64>,              // 输出结尾的画面和声音淡出时长（秒）
+    pub transition: Option<String>,         // 相邻范围之间的 xfade 转场类型，例如 fade / dissolve / wipeleft
+    pub transition_duration: Option<f64>,   // 转场时长（秒），只指定类型时为 1 秒
+}
+
+/// 校验后的效果参数
+#[derive(Debug, Clone, PartialEq)]
+struct Effects {
+    fade_in: Rational,
+    fade_out: Rational,
+    transition: Option<(String, Rational)>,
+}
+
+impl Effects {
+    fn is_empty(&self) -> bool {
+        self.fade_in == Rational::ZERO && self.fade_out == Rational::ZERO && self.transition.is_none()
+    }
+
+    fn transition_duration(&self) -> Rational {
+        self.transition.as_ref().map(|(_, d)| *d).unwrap_or(Rational::ZERO)
+    }
+}
+
+impl JoinOptions {
+    /// 校验选项，`ranges` 为各范围的 (开始, 结束)
+    fn effects(&self, ranges: &[(Rational, Rational)]) -> AppResult<Effects> {
+        let seconds = |value: Option<f64>| -> AppResult<Rational> {
+            match value {
+                Some(v) if !v.is_finite() || v < 0.0 => Err(validation_error(t!("join.invalid_effect"))),
+                Some(v) => Ok(Rational::from_seconds_f64(v)),
+                None => Ok(Rational::ZERO),
+            }
+        };
+
+        let transition = match (&self.transition, self.transition_duration) {
+            (None, None) => None,
+            (kind, duration) => {
+                let kind = kind.as_deref().unwrap_or(DEFAULT_TRANSITION).trim().to_ascii_lowercase();
+                if !TRANSITIONS.contains(&kind.as_str()) {
+                    return Err(validation_error(t!("join.transition_unsupported", transition = kind)));
+                }
+                let duration = seconds(Some(duration.unwrap_or(DEFAULT_TRANSITION_SECONDS)))?;
+                if duration == Rational::ZERO {
+                    return Err(validation_error(t!("join.invalid_effect")));
+                }
+                // 只有一个范围时没有转场
+                (ranges.len() > 1).then_some((kind, duration))
+            }
+        };
+
+        let effects = Effects { fade_in: seconds(self.fade_in)?, fade_out: seconds(self.fade_out)?, transition };
+
+        // 每个范围都要容纳两端的淡入淡出或转场
+        for (i, (start, end)) in ranges.iter().enumerate() {
+            let (head, tail) = edge_effects(&effects, i, ranges.len());
+            if head + tail > *end - *start {
+                return Err(validation_error(t!("join.range_too_short", index = i + 1)));
+            }
+        }
+        Ok(effects)
+    }
+}
+
+/// 第 `index` 个范围开头和结尾需要重新编码的效果时长
+fn edge_effects(effects: &Effects, index: usize, count: usize) -> (Rational, Rational) {
+    let transition = effects.transition_duration();
+    let head = if index == 0 { effects.fade_in } else { transition };
+    let tail = if index + 1 == count { effects.fade_out } else { transition };
+    (head, tail)
+}
+
+/// 需要重新编码的一段源视频
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Piece {
+    start: Rational,
+    end: Rational,
+}
+
+impl Piece {
+    fn duration(&self) -> Rational {
+        self.end - self.start
+    }
+}
+
+/// 输出中的一段
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    /// 从关键帧开始到下一个关键帧之前流复制
+    Copy(Piece),
+    /// 重新编码的片段，相邻片段来自相邻的范围，按转场（未设置时直接拼接）连接
+    Encode { pieces: Vec<Piece>, fade_in: bool, fade_out: bool },
+}
+
+/// 规划输出的各段
+///
+/// `keyframes` 为各范围内的关键帧，为 `None` 时整体重新编码。否则每个范围只重新编码开头到第一个
+/// 可用关键帧、最后一个可用关键帧到结尾这两部分，中间按完整的 GOP 流复制
+fn plan_segments(ranges: &[(Rational, Rational)], keyframes: Option<&[Vec<Rational>]>, effects: &Effects) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut pieces: Vec<Piece> = Vec::new();
+    let mut fade_in = false;
+
+    for (i, &(start, end)) in ranges.iter().enumerate() {
+        let (head, tail) = edge_effects(effects, i, ranges.len());
+        if i == 0 {
+            fade_in = effects.fade_in > Rational::ZERO;
+        }
+
+        // 流复制部分：[不早于效果结束的第一个关键帧, 不晚于效果开始的最后一个关键帧)
+        let copy = keyframes.and_then(|all| {
+            let first = all[i].iter().copied().find(|k| *k >= start + head)?;
+            let last = all[i].iter().copied().filter(|k| *k <= end - tail).max()?;
+            (first < last).then_some(Piece { start: first, end: last })
+        });
+
+        match copy {
+            Some(copy) => {
+                if copy.start > start {
+                    pieces.push(Piece { start, end: copy.start });
+                }
+                flush(&mut pieces, &mut fade_in, false, &mut segments);
+                segments.push(Segment::Copy(copy));
+                if copy.end < end {
+                    pieces.push(Piece { start: copy.end, end });
+                }
+            }
+            None => pieces.push(Piece { start, end }),
+        }
+    }
+    flush(&mut pieces, &mut fade_in, effects.fade_out > Rational::ZERO, &mut segments);
+    segments
+}
+
+/// 把累积的重新编码片段合并为一段输出，开头的淡入只作用于第一段
+fn flush(pieces: &mut Vec<Piece>, fade_in: &mut bool, fade_out: bool, segments: &mut Vec<Segment>) {
+    if !pieces.is_empty() {
+        segments.push(Segment::Encode { pieces: std::mem::take(pieces), fade_in: *fade_in, fade_out });
+        *fade_in = false;
+    }
+}
+
+/// 导出多个范围并拼接为一个文件，输出到下一个版本文件
+///
+/// 没有淡入淡出和转场时整体流复制；否则源为 H.264 / HEVC + AAC、有对应编码器且能匹配源的
+/// profile、level 和像素格式时只重新编码范围两端和转场附近的片段，其余按 GOP 流复制，
+/// 不满足条件时整体重新编码
+pub fn export_ranges(
+    input_path: &str,
+    ranges: &[TimeRange],
+    options: &JoinOptions,
+    notes: Option<&str>,
+    output_dir: Option<&str>,
+    container: Option<&str>
+) -> AppResult<JoinResult> {
+    export_ranges_with(&ProcessBackend, input_path, ranges, options, notes, output_dir, container)
+}
+
+/// 使用指定后端导出多个范围，参数含义同 [`export_ranges`]
+pub fn export_ranges_with(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    ranges: &[TimeRange],
+    options: &JoinOptions,
+    notes: Option<&str>,
+    output_dir: Option<&str>,
+    container: Option<&str>
+) -> AppResult<JoinResult> {
+    // 验证输入路径和输出目录
+    let request = ExportRequest::new(input_path, notes, output_dir, container)?;
+
+    if ranges.is_empty() {
+        return Err(validation_error(t!("join.no_ranges")));
+    }
+
+    // 输出第一路视频和所有音频流
+    let source_streams = get_stream_codecs_with(backend, input_path)?;
+    let video = source_streams.iter().find(|(t, _)| t == "video").cloned();
+    let audio = source_streams.iter().filter(|(t, _)| t == "audio").cloned();
+    let kept: Vec<(String, String)> = video.into_iter().chain(audio).collect();
+    let audio_tracks = kept.iter().filter(|(t, _)| t == "audio").count();
+    let has_audio = audio_tracks > 0;
+    if let Some(ext) = request.container() {
+        check_codec_compatibility(ext, &kept)?;
+    }
+
+    // 换算并验证各范围，校验效果参数
+    let mut resolved = Vec::with_capacity(ranges.len());
+    let mut total_duration = 0.0;
+    let mut start_time = Rational::ZERO;
+    for range in ranges {
+        let r = resolve_range_with(backend, input_path, &range.start, &range.end)?;
+        total_duration = r.total_duration;
+        start_time = r.start_time;
+        resolved.push((r.start, r.end));
+    }
+    let effects = options.effects(&resolved)?;
+
+    // 预留输出文件并检查磁盘空间
+    let mut estimated_size = 0;
+    for span in &resolved {
+        estimated_size += request.estimate_size(Some(*span), total_duration)?;
+    }
+    let target = request.reserve(estimated_size)?;
+    let output = target.output()?;
+
+    let input_abs = Path::new(input_path).canonicalize().unwrap_or_else(|_| PathBuf::from(input_path));
+    let work = WorkDir::new("join")?;
+    let transition = effects.transition_duration();
+
+    let (mode, reencoded) = if effects.is_empty() {
+        // 整体流复制：concat 分离器按 inpoint / outpoint 直接读取源文件的各个范围
+        let mut list = String::new();
+        for (start, end) in &resolved {
+            let _ = writeln!(list, "file {}", concat_quote(&input_abs));
+            let _ = writeln!(list, "inpoint {}", start.to_ffmpeg_seconds());
+            let _ = writeln!(list, "outpoint {}", end.to_ffmpeg_seconds());
+        }
+        run_ffmpeg(backend, concat_args(&work.write_list(&list)?, has_audio, output))?;
+        (JoinMode::Copy, Rational::ZERO)
+    } else {
+        let caps = backend.capabilities()?;
+        let format = probe_video_format(backend, input_path)?;
+        match smart_encoder(&caps, &kept, &format, &target.ext) {
+            Some(encoder) => {
+                // 只重新编码两端和转场附近的片段，中间按 GOP 流复制到 MPEG-TS 中间文件后拼接
+                let mut keyframes = Vec::with_capacity(resolved.len());
+                for (start, end) in &resolved {
+                    keyframes.push(list_keyframes_with(backend, input_path, start_time, *start, *end)?);
+                }
+                let segments = plan_segments(&resolved, Some(&keyframes), &effects);
+
+                let mut list = String::new();
+                let mut reencoded = Rational::ZERO;
+                for (i, segment) in segments.iter().enumerate() {
+                    let path = work.path(&format!("segment_{}.ts", i));
+                    let path_str = path.to_str().ok_or_else(|| path_error(t!("path.not_utf8")))?;
+                    let args = match segment {
+                        Segment::Copy(piece) => copy_segment_args(input_path, piece, has_audio, path_str),
+                        Segment::Encode { pieces, fade_in, fade_out } => {
+                            reencoded = reencoded + pieces.iter().map(|p| p.duration()).fold(Rational::ZERO, |a, b| a + b);
+                            let graph = filter_graph(pieces, &effects, *fade_in, *fade_out, audio_tracks);
+                            encode_args(input_path, pieces, &graph, audio_tracks, &encoder, &["-f", "mpegts"], path_str)
+                        }
+                    };
+                    run_ffmpeg(backend, args)?;
+                    let _ = writeln!(list, "file {}", concat_quote(&path));
+                }
+                run_ffmpeg(backend, concat_args(&work.write_list(&list)?, has_audio, output))?;
+                (JoinMode::Smart, reencoded)
+            }
+            None => {
+                // 整体重新编码
+                let encoder = full_encoder(&caps, &target.ext)?;
+                let segments = plan_segments(&resolved, None, &effects);
+                let Some(Segment::Encode { pieces, fade_in, fade_out }) = segments.first() else {
+                    unreachable!("整体重新编码时只有一个片段");
+                };
+                let graph = filter_graph(pieces, &effects, *fade_in, *fade_out, audio_tracks);
+                run_ffmpeg(backend, encode_args(input_path, pieces, &graph, audio_tracks, &encoder, &[], output))?;
+                let total = pieces.iter().map(|p| p.duration()).fold(Rational::ZERO, |a, b| a + b);
+                (JoinMode::Reencode, total)
+            }
+        }
+    };
+
+    // 提交并校验时长和流：每个转场使输出缩短一个转场时长
+    let joints = Rational::from_integer(resolved.len() as i64 - 1);
+    let expected_duration = resolved.iter().map(|(s, e)| *e - *s).fold(Rational::ZERO, |a, b| a + b) - transition * joints;
+    let expected_streams = StreamCounts::from_streams(&kept);
+    let Exported { output_path, verification } = target.finish(
+        backend, expected_duration.to_f64(), expected_streams, mode != JoinMode::Copy
+    )?;
+
+    let message = if verification.passed {
+        t!("join.success", count = resolved.len(), path = output_path)
+    } else {
+        t!("join.verification_failed", path = output_path)
+    };
+
+    Ok(JoinResult { output_path, message, verification, mode, reencoded_duration: reencoded.to_f64() })
+}
+
+/// 视频和音频编码参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Encoder {
+    pub(crate) video: Vec<String>,
+    pub(crate) audio: Vec<String>,
+}
+
+impl Encoder {
+    /// 整体重新编码的 H.264：每种编码器使用各自的码率控制参数，硬件编码器不接受 -crf / -preset
+    fn h264(video_encoder: &str) -> Self {
+        let rate_control: &[&str] = match video_encoder {
+            "h264_nvenc" => &["-rc", "vbr", "-cq", HARDWARE_QUALITY, "-b:v", "0"],
+            "h264_qsv" => &["-global_quality", HARDWARE_QUALITY],
+            "h264_amf" => &["-rc", "cqp", "-qp_i", HARDWARE_QUALITY, "-qp_p", HARDWARE_QUALITY],
+            "h264_videotoolbox" => &["-b:v", VIDEOTOOLBOX_BITRATE],
+            _ => &["-crf", REENCODE_CRF, "-preset", "medium"],
+        };
+        let mut video = vec!["-c:v", video_encoder];
+        video.extend(rate_control);
+        video.extend(["-pix_fmt", "yuv420p"]);
+        Encoder {
+            video: video.iter().map(|a| a.to_string()).collect(),
+            audio: ["-c:a", "aac", "-b:a", REENCODE_AUDIO_BITRATE].iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    /// 按源的像素格式、profile 和 level 编码，输出能与源的 GOP 拼接
+    fn matching(video_encoder: &str, profile: &str, level: &str, pix_fmt: &str) -> Self {
+        let mut video: Vec<String> = [
+            "-c:v", video_encoder, "-crf", REENCODE_CRF, "-preset", "medium", "-pix_fmt", pix_fmt, "-profile:v", profile,
+        ].iter().map(|a| a.to_string()).collect();
+        // libx265 不读取通用的 -level 选项
+        if video_encoder == "libx265" {
+            video.extend(["-x265-params".to_string(), format!("level-idc={}", level)]);
+        } else {
+            video.extend(["-level".to_string(), level.to_string()]);
+        }
+        Encoder {
+            video,
+            audio: ["-c:a", "aac", "-b:a", REENCODE_AUDIO_BITRATE].iter().map(|a| a.to_string()).collect(),
+        }
+    }
+}
+
+/// 源视频流的编码参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct VideoFormat {
+    codec: String,
+    profile: String,    // ffprobe 的 profile 名称，例如 "High" / "Main 10"
+    level: i64,         // H.264 为 level × 10，HEVC 为 level × 30
+    pix_fmt: String,
+    width: u32,
+    height: u32,
+}
+
+/// 探测第一路视频流的编码、profile、level、像素格式和分辨率
+fn probe_video_format(backend: &dyn MediaBackend, path: &str) -> AppResult<VideoFormat> {
+    let output = backend.ffprobe(&[
+        "-v", "quiet",
+        "-print_format", "json",
+        "-select_streams", "v:0",
+        "-show_entries", "stream=codec_name,profile,level,pix_fmt,width,height",
+        path
+    ])?;
+    check_command_success(&output, "ffprobe")?;
+
+    let data: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| ffprobe_error(t!("probe.json_invalid", error = e)))?;
+    let stream = &data["streams"][0];
+    if !stream.is_object() {
+        return Err(ffprobe_error(t!("probe.no_video_stream")));
+    }
+
+    let text = |key: &str| stream[key].as_str().unwrap_or("").to_string();
+    Ok(VideoFormat {
+        codec: text("codec_name"),
+        profile: text("profile"),
+        level: stream["level"].as_i64().unwrap_or(-1),
+        pix_fmt: text("pix_fmt"),
+        width: stream["width"].as_u64().unwrap_or(0) as u32,
+        height: stream["height"].as_u64().unwrap_or(0) as u32,
+    })
+}
+
+/// 部分重新编码的编码器：重新编码的片段需要能和流复制的片段拼接，
+/// 只支持用 libx264 / libx265 以源的 profile、level 和像素格式（8 位 4:2:0）重新编码 H.264 / HEVC，
+/// 音频为 AAC，且能放入 MPEG-TS 中间文件。无法匹配源的参数时返回 `None`，改为整体重新编码
+fn smart_encoder(
+    caps: &FFmpegCapabilities,
+    streams: &[(String, String)],
+    format: &VideoFormat,
+    output_ext: &str
+) -> Option<Encoder> {
+    let (encoder, profile, level) = match format.codec.as_str() {
+        "h264" => {
+            let profile = match format.profile.as_str() {
+                "Baseline" | "Constrained Baseline" => "baseline",
+                "Main" => "main",
+                "High" => "high",
+                _ => return None,
+            };
+            // ffprobe 的 H.264 level 为 level × 10，例如 31 表示 3.1
+            let level = (format.level >= 10).then(|| format!("{}.{}", format.level / 10, format.level % 10))?;
+            ("libx264", profile, level)
+        }
+        "hevc" => {
+            let profile = match format.profile.as_str() {
+                "Main" => "main",
+                _ => return None,
+            };
+            // ffprobe 的 HEVC level 为 level × 30，例如 93 表示 3.1
+            let tenths = (format.level > 0 && format.level % 3 == 0).then_some(format.level / 3)?;
+            ("libx265", profile, format!("{}.{}", tenths / 10, tenths % 10))
+        }
+        _ => return None,
+    };
+    let pix_fmt_ok = matches!(format.pix_fmt.as_str(), "yuv420p" | "yuvj420p");
+    let size_ok = format.width > 0 && format.height > 0 && format.width.is_multiple_of(2) && format.height.is_multiple_of(2);
+    let audio_ok = streams.iter().filter(|(t, _)| t == "audio").all(|(_, c)| c == "aac");
+    let ts_ok = check_codec_compatibility("ts", streams).is_ok();
+    (caps.has_encoder(encoder) && pix_fmt_ok && size_ok && audio_ok && ts_ok && output_ext != "webm")
+        .then(|| Encoder::matching(encoder, profile, &level, &format.pix_fmt))
+}
+
+/// 整体重新编码的编码器：WebM 使用 VP9 + Opus，其余容器使用可用的 H.264 编码器 + AAC
+pub(crate) fn full_encoder(caps: &FFmpegCapabilities, output_ext: &str) -> AppResult<Encoder> {
+    let no_encoder = || validation_error(t!("join.no_encoder"));
+    if output_ext == "webm" {
+        if !caps.has_encoder("libvpx-vp9") || !caps.has_encoder("libopus") {
+            return Err(no_encoder());
+        }
+        return Ok(Encoder {
+            video: ["-c:v", "libvpx-vp9", "-crf", "32", "-b:v", "0"].iter().map(|a| a.to_string()).collect(),
+            audio: ["-c:a", "libopus", "-b:a", "128k"].iter().map(|a| a.to_string()).collect(),
+        });
+    }
+    let encoder = caps.first_encoder(H264_ENCODERS).ok_or_else(no_encoder)?;
+    Ok(Encoder::h264(&encoder))
+}
+
+/// 重新编码片段的滤镜图
+///
+/// 每个片段作为一路输入，相邻片段用 xfade / acrossfade 转场或 concat 直接拼接，
+/// 再在开头和结尾加上淡入淡出，输出 `[v]` 和每条音轨的 `[a0]`、`[a1]`……
+fn filter_graph(pieces: &[Piece], effects: &Effects, fade_in: bool, fade_out: bool, audio_tracks: usize) -> String {
+    // 第一个片段直接作为拼接链的起点 `[vx0]` / `[a0_x0]`
+    let mut graph: Vec<String> = Vec::new();
+    for i in 0..pieces.len() {
+        let suffix = if i == 0 { "x0".to_string() } else { i.to_string() };
+        graph.push(format!("[{}:v:0]setpts=PTS-STARTPTS[v{}]", i, suffix));
+        for track in 0..audio_tracks {
+            graph.push(format!("[{i}:a:{track}]asetpts=PTS-STARTPTS[a{track}_{suffix}]"));
+        }
+    }
+
+    let mut length = pieces[0].duration();
+    for (i, piece) in pieces.iter().enumerate().skip(1) {
+        match &effects.transition {
+            Some((kind, duration)) => {
+                let offset = length - *duration;
+                graph.push(format!(
+                    "[vx{prev}][v{i}]xfade=transition={kind}:duration={d}:offset={o}[vx{i}]",
+                    prev = i - 1, d = duration.to_ffmpeg_seconds(), o = offset.to_ffmpeg_seconds(),
+                ));
+                for track in 0..audio_tracks {
+                    graph.push(format!(
+                        "[a{track}_x{prev}][a{track}_{i}]acrossfade=d={d}[a{track}_x{i}]",
+                        prev = i - 1, d = duration.to_ffmpeg_seconds(),
+                    ));
+                }
+                length = length + piece.duration() - *duration;
+            }
+            None => {
+                // concat 的输入和输出按片段排列：视频在前，之后为各条音轨
+                let labels = |piece: String| -> String {
+                    let audio: String = (0..audio_tracks).map(|track| format!("[a{}_{}]", track, piece)).collect();
+                    format!("[v{}]{}", piece, audio)
+                };
+                graph.push(format!(
+                    "{}{}concat=n=2:v=1:a={}{}",
+                    labels(format!("x{}", i - 1)), labels(i.to_string()), audio_tracks, labels(format!("x{}", i)),
+                ));
+                length = length + piece.duration();
+            }
+        }
+    }
+    let graph = graph.join(";");
+    let last = pieces.len() - 1;
+
+    let mut video_tail = Vec::new();
+    let mut audio_tail = Vec::new();
+    if fade_in {
+        let d = effects.fade_in.to_ffmpeg_seconds();
+        video_tail.push(format!("fade=t=in:st=0:d={}", d));
+        audio_tail.push(format!("afade=t=in:st=0:d={}", d));
+    }
+    if fade_out {
+        let st = (length - effects.fade_out).max(Rational::ZERO).to_ffmpeg_seconds();
+        let d = effects.fade_out.to_ffmpeg_seconds();
+        video_tail.push(format!("fade=t=out:st={}:d={}", st, d));
+        audio_tail.push(format!("afade=t=out:st={}:d={}", st, d));
+    }
+    let chain = |filters: &[String], passthrough: &str| {
+        if filters.is_empty() { passthrough.to_string() } else { filters.join(",") }
+    };
+
+    let mut graph = format!("{};[vx{}]{}[v]", graph, last, chain(&video_tail, "null"));
+    for track in 0..audio_tracks {
+        graph.push_str(&format!(";[a{track}_x{last}]{}[a{track}]", chain(&audio_tail, "anull")));
+    }
+    graph
+}
+
+/// 重新编码片段的 ffmpeg 参数：每个片段一路精确定位的输入
+fn encode_args(
+    input_path: &str,
+    pieces: &[Piece],
+    graph: &str,
+    audio_tracks: usize,
+    encoder: &Encoder,
+    format: &[&str],
+    output: &str
+) -> Vec<String> {
+    let mut args: Vec<String> = vec!["-y".into()];
+    for piece in pieces {
+        args.extend([
+            "-ss".into(), piece.start.to_ffmpeg_seconds(),
+            "-t".into(), piece.duration().to_ffmpeg_seconds(),
+            "-i".into(), input_path.into(),
+        ]);
+    }
+    args.extend(["-filter_complex".into(), graph.into(), "-map".into(), "[v]".into()]);
+    for track in 0..audio_tracks {
+        args.extend(["-map".into(), format!("[a{}]", track)]);
+    }
+    args.extend(encoder.video.iter().cloned());
+    if audio_tracks > 0 {
+        args.extend(encoder.audio.iter().cloned());
+    }
+    args.extend(format.iter().map(|a| a.to_string()));
+    args.push(output.into());
+    args
+}
+
+/// 流复制片段的 ffmpeg 参数：从关键帧开始，到下一段重新编码的片段之前结束
+fn copy_segment_args(input_path: &str, piece: &Piece, has_audio: bool, output: &str) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        "-y".into(),
+        "-ss".into(), piece.start.to_ffmpeg_seconds(),
+        "-i".into(), input_path.into(),
+        "-t".into(), piece.duration().to_ffmpeg_seconds(),
+        "-map".into(), "0:v:0".into(),
+    ];
+    if has_audio {
+        args.extend(["-map".into(), "0:a".into()]);
+    }
+    args.extend(["-c".into(), "copy".into(), "-f".into(), "mpegts".into(), output.into()]);
+    args
+}
+
+/// 用 concat 分离器拼接列表中的文件，流复制到输出
+pub(crate) fn concat_args(list: &Path, has_audio: bool, output: &str) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        "-y".into(),
+        "-f".into(), "concat".into(),
+        "-safe".into(), "0".into(),
+        "-i".into(), list.to_string_lossy().to_string(),
+        "-map".into(), "0:v:0".into(),
+    ];
+    if has_audio {
+        args.extend(["-map".into(), "0:a".into()]);
+    }
+    args.extend([
+        "-c".into(), "copy".into(),
+        "-avoid_negative_ts".into(), "1".into(),
+        output.into(),
+    ]);
+    args
+}
+
+/// concat 列表中的文件路径：用单引号包围，路径中的单引号写作 `'\''`
+pub(crate) fn concat_quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', "'\\''"))
+}
+
+pub(crate) fn run_ffmpeg(backend: &dyn MediaBackend, args: Vec<String>) -> AppResult<()> {
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    let output = backend.ffmpeg(&args)?;
+    check_command_success(&output, "ffmpeg")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use crate::backend::{CommandOutput, MockBackend};
+    use crate::binaries::Tool;
+
+    fn s(seconds: i64) -> Rational {
+        Rational::from_integer(seconds)
+    }
+
+    fn piece(start: i64, end: i64) -> Piece {
+        Piece { start: s(start), end: s(end) }
+    }
+
+    fn effects(fade_in: i64, fade_out: i64, transition: Option<i64>) -> Effects {
+        Effects {
+            fade_in: s(fade_in),
+            fade_out: s(fade_out),
+            transition: transition.map(|d| ("fade".to_string(), s(d))),
+        }
+    }
+
+    #[test]
+    fn test_time_range_parse() {
+        let range: TimeRange = "10..00:00:25".parse().unwrap();
+        assert_eq!(range.end, "25".parse().unwrap());
+        assert!("10-25".parse::<TimeRange>().is_err());
+    }
+
+    #[test]
+    fn test_options_validation() {
+        let ranges = [(s(0), s(5)), (s(10), s(12))];
+        let options = JoinOptions { transition: Some("Dissolve".to_string()), ..Default::default() };
+        assert_eq!(options.effects(&ranges).unwrap().transition, Some(("dissolve".to_string(), s(1))));
+
+        // 单个范围没有转场
+        let single = options.effects(&ranges[..1]).unwrap();
+        assert!(single.is_empty());
+
+        let invalid = [
+            JoinOptions { transition: Some("spin".to_string()), ..Default::default() },
+            JoinOptions { transition_duration: Some(0.0), ..Default::default() },
+            JoinOptions { fade_in: Some(-1.0), ..Default::default() },
+            // 第二个范围只有 2 秒，容纳不了 1.5 秒的转场和 1 秒的淡出
+            JoinOptions { transition_duration: Some(1.5), fade_out: Some(1.0), ..Default::default() },
+        ];
+        for options in invalid {
+            assert!(options.effects(&ranges).is_err(), "{:?}", options);
+        }
+    }
+
+    #[test]
+    fn test_plan_segments_smart() {
+        let ranges = [(s(1), s(9)), (s(20), s(26))];
+        let keyframes = [vec![s(0), s(2), s(4), s(6), s(8)], vec![s(20), s(22), s(24)]];
+        let segments = plan_segments(&ranges, Some(&keyframes), &effects(1, 0, Some(1)));
+
+        assert_eq!(segments, vec![
+            // 淡入到第一个不早于 2 秒的关键帧
+            Segment::Encode { pieces: vec![piece(1, 2)], fade_in: true, fade_out: false },
+            Segment::Copy(piece(2, 8)),
+            // 转场：第一个范围的结尾和第二个范围开头到 22 秒的关键帧
+            Segment::Encode { pieces: vec![piece(8, 9), piece(20, 22)], fade_in: false, fade_out: false },
+            // 没有淡出时结尾在关键帧之后的部分仍需重新编码，流复制不能在关键帧之间结束
+            Segment::Copy(piece(22, 24)),
+            Segment::Encode { pieces: vec![piece(24, 26)], fade_in: false, fade_out: false },
+        ]);
+    }
+
+    #[test]
+    fn test_plan_segments_without_keyframes() {
+        let ranges = [(s(1), s(3)), (s(5), s(9))];
+        // 第一个范围内没有可用的关键帧，整段与转场一起重新编码
+        let keyframes = [vec![s(0)], vec![s(5), s(7), s(9)]];
+        let segments = plan_segments(&ranges, Some(&keyframes), &effects(0, 1, None));
+        assert_eq!(segments, vec![
+            Segment::Encode { pieces: vec![piece(1, 3)], fade_in: false, fade_out: false },
+            Segment::Copy(piece(5, 7)),
+            Segment::Encode { pieces: vec![piece(7, 9)], fade_in: false, fade_out: true },
+        ]);
+
+        let full = plan_segments(&ranges, None, &effects(0, 1, None));
+        assert_eq!(full, vec![
+            Segment::Encode { pieces: vec![piece(1, 3), piece(5, 9)], fade_in: false, fade_out: true },
+        ]);
+    }
+
+    #[test]
+    fn test_filter_graph() {
+        let graph = filter_graph(&[piece(8, 10), piece(20, 23)], &effects(0, 1, Some(1)), false, true, 1);
+        assert_eq!(graph, [
+            "[0:v:0]setpts=PTS-STARTPTS[vx0]",
+            "[0:a:0]asetpts=PTS-STARTPTS[a0_x0]",
+            "[1:v:0]setpts=PTS-STARTPTS[v1]",
+            "[1:a:0]asetpts=PTS-STARTPTS[a0_1]",
+            "[vx0][v1]xfade=transition=fade:duration=1:offset=1[vx1]",
+            "[a0_x0][a0_1]acrossfade=d=1[a0_x1]",
+            "[vx1]fade=t=out:st=3:d=1[v]",
+            "[a0_x1]afade=t=out:st=3:d=1[a0]",
+        ].join(";"));
+
+        let graph = filter_graph(&[piece(0, 2), piece(5, 6)], &effects(0, 0, None), false, false, 0);
+        assert_eq!(graph, [
+            "[0:v:0]setpts=PTS-STARTPTS[vx0]",
+            "[1:v:0]setpts=PTS-STARTPTS[v1]",
+            "[vx0][v1]concat=n=2:v=1:a=0[vx1]",
+            "[vx1]null[v]",
+        ].join(";"));
+
+        // 每条音轨各自拼接和淡入
+        let graph = filter_graph(&[piece(0, 2), piece(5, 6)], &effects(1, 0, None), true, false, 2);
+        assert_eq!(graph, [
+            "[0:v:0]setpts=PTS-STARTPTS[vx0]",
+            "[0:a:0]asetpts=PTS-STARTPTS[a0_x0]",
+            "[0:a:1]asetpts=PTS-STARTPTS[a1_x0]",
+            "[1:v:0]setpts=PTS-STARTPTS[v1]",
+            "[1:a:0]asetpts=PTS-STARTPTS[a0_1]",
+            "[1:a:1]asetpts=PTS-STARTPTS[a1_1]",
+            "[vx0][a0_x0][a1_x0][v1][a0_1][a1_1]concat=n=2:v=1:a=2[vx1][a0_x1][a1_x1]",
+            "[vx1]fade=t=in:st=0:d=1[v]",
+            "[a0_x1]afade=t=in:st=0:d=1[a0]",
+            "[a1_x1]afade=t=in:st=0:d=1[a1]",
+        ].join(";"));
+    }
+
+    #[test]
+    fn test_smart_encoder_matches_source() {
+        let caps = FFmpegCapabilities { encoders: vec!["libx264".into(), "libx265".into()], ..Default::default() };
+        let streams = |codec: &str| vec![("video".to_string(), codec.to_string()), ("audio".to_string(), "aac".to_string())];
+        let format = |codec: &str, profile: &str, level: i64, pix_fmt: &str| VideoFormat {
+            codec: codec.to_string(), profile: profile.to_string(), level, pix_fmt: pix_fmt.to_string(), width: 1920, height: 1080,
+        };
+
+        let encoder = smart_encoder(&caps, &streams("h264"), &format("h264", "Main", 31, "yuvj420p"), "mp4").unwrap();
+        assert_eq!(encoder.video[6..], ["-pix_fmt", "yuvj420p", "-profile:v", "main", "-level", "3.1"]);
+        let encoder = smart_encoder(&caps, &streams("hevc"), &format("hevc", "Main", 153, "yuv420p"), "mkv").unwrap();
+        assert_eq!(encoder.video[8..], ["-profile:v", "main", "-x265-params", "level-idc=5.1"]);
+
+        // 10 位、4:2:2 或 profile / level 未知时无法匹配
+        for unmatched in [
+            format("h264", "High 10", 40, "yuv420p10le"),
+            format("h264", "High 4:2:2", 40, "yuv422p"),
+            format("h264", "High", -99, "yuv420p"),
+            format("hevc", "Main 10", 120, "yuv420p10le"),
+        ] {
+            assert!(smart_encoder(&caps, &streams(&unmatched.codec), &unmatched, "mp4").is_none(), "{:?}", unmatched);
+        }
+    }
+
+    #[test]
+    fn test_full_encoder_rate_control() {
+        let caps = |encoder: &str| FFmpegCapabilities { encoders: vec![encoder.into()], ..Default::default() };
+        let video = |encoder: &str| full_encoder(&caps(encoder), "mp4").unwrap().video;
+
+        assert_eq!(video("libx264"), ["-c:v", "libx264", "-crf", "18", "-preset", "medium", "-pix_fmt", "yuv420p"]);
+        assert_eq!(video("h264_nvenc")[2..], ["-rc", "vbr", "-cq", "19", "-b:v", "0", "-pix_fmt", "yuv420p"]);
+        assert_eq!(video("h264_videotoolbox")[2..], ["-b:v", "20M", "-pix_fmt", "yuv420p"]);
+        for encoder in ["h264_nvenc", "h264_qsv", "h264_amf", "h264_videotoolbox"] {
+            assert!(!video(encoder).contains(&"-crf".to_string()), "{}", encoder);
+        }
+        assert!(full_encoder(&caps("libx264"), "webm").is_err());
+    }
+
+    fn mock_source(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("instant-cut-join-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("match.mp4");
+        fs::write(&input, vec![0u8; 1024]).unwrap();
+        (dir, input)
+    }
+
+    fn probe(duration: f64) -> CommandOutput {
+        CommandOutput::ok(serde_json::json!({
+            "streams": [
+                { "index": 0, "codec_type": "video", "codec_name": "h264", "r_frame_rate": "25/1", "time_base": "1/12800",
+                  "profile": "High", "level": 40, "pix_fmt": "yuv420p", "width": 1920, "height": 1080 },
+                { "index": 1, "codec_type": "audio", "codec_name": "aac" },
+            ],
+            "format": { "duration": duration.to_string(), "start_time": "0.000000" },
+        }).to_string())
+    }
+
+    fn keyframes(times: &[&str]) -> CommandOutput {
+        let frames: Vec<serde_json::Value> = times.iter().map(|t| serde_json::json!({ "pts_time": t })).collect();
+        CommandOutput::ok(serde_json::json!({ "frames": frames, "format": { "start_time": "0.000000" } }).to_string())
+    }
+
+    fn ranges(list: &[(f64, f64)]) -> Vec<TimeRange> {
+        list.iter().map(|(a, b)| TimeRange { start: TimePoint::seconds(*a), end: TimePoint::seconds(*b) }).collect()
+    }
+
+    #[test]
+    fn test_export_ranges_copy_mode() {
+        let (dir, input) = mock_source("copy");
+        let mock = MockBackend::new();
+        mock.set_default(Tool::FFprobe, probe(60.0));
+        mock.set_output_contents(b"joined");
+
+        let result = export_ranges_with(
+            &mock, input.to_str().unwrap(), &ranges(&[(10.0, 20.0), (30.0, 35.5)]), &JoinOptions::default(),
+            None, None, None
+        ).unwrap();
+        assert_eq!(result.output_path, dir.join("match_1.mp4").to_string_lossy());
+        assert_eq!((result.mode, result.reencoded_duration), (JoinMode::Copy, 0.0));
+
+        let calls = mock.calls_to(Tool::FFmpeg);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0][..5], ["-y", "-f", "concat", "-safe", "0"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_export_ranges_smart_mode() {
+        let (dir, input) = mock_source("smart");
+        let mock = MockBackend::new();
+        // 流、两个范围各两次（帧率、时长）、视频编码参数，之后为两个范围的关键帧和输出探测
+        for _ in 0..6 {
+            mock.respond(Tool::FFprobe, probe(60.0));
+        }
+        mock.respond(Tool::FFprobe, keyframes(&["8.000000", "10.000000", "12.000000", "14.000000"]));
+        mock.respond(Tool::FFprobe, keyframes(&["30.000000", "32.000000"]));
+        mock.set_default(Tool::FFprobe, probe(9.0));
+        mock.set_output_contents(b"joined");
+        mock.set_capabilities(FFmpegCapabilities { encoders: vec!["libx264".into(), "aac".into()], ..Default::default() });
+
+        let options = JoinOptions { transition: Some("wipeleft".to_string()), ..Default::default() };
+        let result = export_ranges_with(
+            &mock, input.to_str().unwrap(), &ranges(&[(10.0, 15.0), (30.0, 35.0)]), &options, None, None, None
+        ).unwrap();
+        assert_eq!(result.mode, JoinMode::Smart);
+        // [10, 14) 流复制，[14, 15) + [30, 32) 转场，[32, 35) 无可用的结尾关键帧
+        assert_eq!(result.reencoded_duration, 3.0 + 3.0);
+        assert!(result.verification.passed, "{:?}", result.verification);
+
+        let calls = mock.calls_to(Tool::FFmpeg);
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[0][1..5], ["-ss", "10", "-i", input.to_str().unwrap()]);
+        assert!(calls[0].windows(2).any(|w| w == ["-c", "copy"]));
+        let graph = calls[1].iter().position(|a| a == "-filter_complex").unwrap();
+        assert!(calls[1][graph + 1].contains("xfade=transition=wipeleft:duration=1:offset=0"));
+        assert!(calls[1].windows(2).any(|w| w == ["-c:v", "libx264"]));
+        assert!(calls[1].windows(6).any(|w| w == ["-pix_fmt", "yuv420p", "-profile:v", "high", "-level", "4.0"]));
+        assert_eq!(calls[2][..3], ["-y", "-f", "concat"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_export_ranges_reencode_without_encoder() {
+        let (dir, input) = mock_source("no-encoder");
+        let mock = MockBackend::new();
+        mock.set_default(Tool::FFprobe, probe(60.0));
+
+        let options = JoinOptions { fade_in: Some(1.0), ..Default::default() };
+        let err = export_ranges_with(
+            &mock, input.to_str().unwrap(), &ranges(&[(0.0, 5.0)]), &options, None, None, None
+        ).unwrap_err();
+        assert!(matches!(err, AppError::ValidationError(_)));
+        assert!(mock.calls_to(Tool::FFmpeg).is_empty());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
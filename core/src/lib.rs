@@ -0,0 +1,30 @@
+//! 瞬剪 Instant Cut 的核心逻辑：探测、剪辑、版本化命名和磁盘检查
+//!
+//! 供 Tauri 图形界面和 `instant-cut` 命令行工具共同使用
+
+pub mod video;
+pub mod error;
+pub mod utils;
+pub mod media;
+pub mod backend;
+pub mod container;
+pub mod output;
+pub mod verify;
+pub mod i18n;
+pub mod settings;
+pub mod diagnostics;
+pub mod binaries;
+pub mod capabilities;
+pub mod timestamp;
+pub mod still;
+pub mod animation;
+pub mod audio;
+pub mod loudness;
+pub mod join;
+pub mod export;
+pub mod transform;
+pub mod cropdetect;
+pub mod speed;
+pub mod subtitle;
+pub mod overlay;
+pub mod correction;
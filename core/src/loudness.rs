@@ -0,0 +1,223 @@
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::audio::select_audio_stream;
+use crate::backend::{MediaBackend, ProcessBackend};
+use crate::error::{AppError, AppResult, ffmpeg_error, validation_error};
+use crate::media::{get_audio_streams_with, resolve_optional_range_with, ResolvedRange};
+use crate::timestamp::TimePoint;
+use crate::utils::{check_command_success, validate_input_path};
+use crate::t;
+
+/// 目标真峰值 (dBTP)
+const TARGET_TRUE_PEAK: f64 = -1.0;
+
+/// 目标响度范围 (LU)
+const TARGET_LRA: f64 = 11.0;
+
+/// 采样率未知时 loudnorm 输出的重采样目标
+const DEFAULT_SAMPLE_RATE: u32 = 48000;
+
+/// 响度标准化的目标预设 (EBU R128 / loudnorm)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoudnessPreset {
+    Streaming,  // -14 LUFS，流媒体平台
+    Podcast,    // -16 LUFS，播客
+    Broadcast,  // -23 LUFS，EBU R128 广播
+}
+
+impl LoudnessPreset {
+    /// 目标综合响度 (LUFS)
+    pub fn target_lufs(&self) -> f64 {
+        match self {
+            LoudnessPreset::Streaming => -14.0,
+            LoudnessPreset::Podcast => -16.0,
+            LoudnessPreset::Broadcast => -23.0,
+        }
+    }
+
+    /// loudnorm 的目标参数
+    fn targets(&self) -> String {
+        format!("I={}:TP={}:LRA={}", self.target_lufs(), TARGET_TRUE_PEAK, TARGET_LRA)
+    }
+}
+
+impl fmt::Display for LoudnessPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LoudnessPreset::Streaming => "streaming",
+            LoudnessPreset::Podcast => "podcast",
+            LoudnessPreset::Broadcast => "broadcast",
+        })
+    }
+}
+
+impl FromStr for LoudnessPreset {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "streaming" => Ok(LoudnessPreset::Streaming),
+            "podcast" => Ok(LoudnessPreset::Podcast),
+            "broadcast" => Ok(LoudnessPreset::Broadcast),
+            _ => Err(validation_error(t!("loudness.preset_unknown", preset = s))),
+        }
+    }
+}
+
+/// 第一遍 loudnorm 的测量结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoudnessReport {
+    pub preset: LoudnessPreset,
+    pub input_i: f64,           // 综合响度 (LUFS)
+    pub input_tp: f64,          // 真峰值 (dBTP)
+    pub input_lra: f64,         // 响度范围 (LU)
+    pub input_thresh: f64,      // 门限 (LUFS)
+    pub target_offset: f64,     // 第二遍使用的增益偏移 (LU)
+}
+
+impl LoudnessReport {
+    /// 第二遍的滤镜：使用测量值做线性标准化，并重采样回源采样率（loudnorm 内部上采样到 192 kHz）
+    pub fn normalize_filter(&self, sample_rate: Option<u32>) -> String {
+        format!(
+            "loudnorm={}:measured_I={}:measured_TP={}:measured_LRA={}:measured_thresh={}:offset={}:linear=true,aresample={}",
+            self.preset.targets(), self.input_i, self.input_tp, self.input_lra, self.input_thresh, self.target_offset,
+            sample_rate.filter(|r| *r > 0).unwrap_or(DEFAULT_SAMPLE_RATE)
+        )
+    }
+}
+
+/// 测量时间范围（或整个文件）内某个音频流的响度
+///
+/// `track` 为音频流在文件中的序号，默认第一个音频流
+pub fn measure_loudness(
+    input_path: &str,
+    start: Option<&TimePoint>,
+    end: Option<&TimePoint>,
+    track: Option<u32>,
+    preset: LoudnessPreset
+) -> AppResult<LoudnessReport> {
+    measure_loudness_with(&ProcessBackend, input_path, start, end, track, preset)
+}
+
+/// 使用指定后端测量响度，参数含义同 [`measure_loudness`]
+pub fn measure_loudness_with(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    start: Option<&TimePoint>,
+    end: Option<&TimePoint>,
+    track: Option<u32>,
+    preset: LoudnessPreset
+) -> AppResult<LoudnessReport> {
+    validate_input_path(input_path)?;
+    let streams = get_audio_streams_with(backend, input_path)?;
+    let stream = select_audio_stream(&streams, track)?;
+    let range = resolve_optional_range_with(backend, input_path, start, end)?;
+    measure_stream_with(backend, input_path, range.as_ref(), stream.index, preset)
+}
+
+/// 运行第一遍 loudnorm，测量指定流在范围内的响度
+pub(crate) fn measure_stream_with(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    range: Option<&ResolvedRange>,
+    stream_index: u32,
+    preset: LoudnessPreset
+) -> AppResult<LoudnessReport> {
+    let mut args: Vec<String> = vec!["-hide_banner".into(), "-nostats".into()];
+    if let Some(range) = range {
+        args.extend([
+            "-ss".into(), range.start.to_ffmpeg_seconds(),
+            "-t".into(), range.duration().to_ffmpeg_seconds(),
+        ]);
+    }
+    args.extend([
+        "-i".into(), input_path.into(),
+        "-map".into(), format!("0:{}", stream_index),
+        "-af".into(), format!("loudnorm={}:print_format=json", preset.targets()),
+        "-f".into(), "null".into(), "-".into(),
+    ]);
+
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    let output = backend.ffmpeg(&args)?;
+    check_command_success(&output, "ffmpeg")?;
+
+    parse_loudnorm_output(&String::from_utf8_lossy(&output.stderr), preset)
+}
+
+/// 解析 loudnorm 打印在 stderr 末尾的 JSON 测量结果
+fn parse_loudnorm_output(stderr: &str, preset: LoudnessPreset) -> AppResult<LoudnessReport> {
+    let failed = || ffmpeg_error(t!("loudness.measure_failed"));
+    let json_start = stderr.rfind('{').ok_or_else(failed)?;
+    let json_end = stderr[json_start..].find('}').ok_or_else(failed)? + json_start;
+    let data: Value = serde_json::from_str(&stderr[json_start..=json_end]).map_err(|_| failed())?;
+
+    // 数值以字符串输出，静音时为 "-inf"
+    let field = |name: &str| -> AppResult<f64> {
+        data[name].as_str()
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .ok_or_else(failed)
+    };
+
+    let report = LoudnessReport {
+        preset,
+        input_i: field("input_i")?,
+        input_tp: field("input_tp")?,
+        input_lra: field("input_lra")?,
+        input_thresh: field("input_thresh")?,
+        target_offset: field("target_offset")?,
+    };
+    if !report.input_i.is_finite() || !report.input_thresh.is_finite() {
+        return Err(validation_error(t!("loudness.silent")));
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOUDNORM_STDERR: &str = r#"Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'in.mp4':
+[Parsed_loudnorm_0 @ 0x5581]
+{
+	"input_i" : "-27.61",
+	"input_tp" : "-4.47",
+	"input_lra" : "18.06",
+	"input_thresh" : "-39.20",
+	"output_i" : "-16.58",
+	"output_tp" : "-1.00",
+	"output_lra" : "14.78",
+	"output_thresh" : "-27.71",
+	"normalization_type" : "dynamic",
+	"target_offset" : "0.58"
+}
+"#;
+
+    #[test]
+    fn test_preset_parse_and_targets() {
+        assert_eq!("Podcast".parse::<LoudnessPreset>().unwrap(), LoudnessPreset::Podcast);
+        assert!("loud".parse::<LoudnessPreset>().is_err());
+        assert_eq!(LoudnessPreset::Streaming.target_lufs(), -14.0);
+        assert_eq!(LoudnessPreset::Broadcast.targets(), "I=-23:TP=-1:LRA=11");
+    }
+
+    #[test]
+    fn test_parse_loudnorm_output() {
+        let report = parse_loudnorm_output(LOUDNORM_STDERR, LoudnessPreset::Podcast).unwrap();
+        assert_eq!(report, LoudnessReport {
+            preset: LoudnessPreset::Podcast,
+            input_i: -27.61, input_tp: -4.47, input_lra: 18.06, input_thresh: -39.2, target_offset: 0.58,
+        });
+        assert_eq!(
+            report.normalize_filter(Some(44100)),
+            "loudnorm=I=-16:TP=-1:LRA=11:measured_I=-27.61:measured_TP=-4.47:measured_LRA=18.06:\
+             measured_thresh=-39.2:offset=0.58:linear=true,aresample=44100"
+        );
+
+        assert!(parse_loudnorm_output("no json here", LoudnessPreset::Podcast).is_err());
+        let silent = LOUDNORM_STDERR.replace("\"-27.61\"", "\"-inf\"");
+        assert!(parse_loudnorm_output(&silent, LoudnessPreset::Podcast).is_err());
+    }
+}
@@ -0,0 +1,1396 @@
+use std::path::{Path, PathBuf};
+use std::fs;
+use serde_json::Value;
+use crate::video::{AudioStream, SubtitleStream, VideoInfo, CutResult, VersionEntry};
+use crate::error::{AppError, AppResult, ffprobe_error,
+                   filesystem_error, path_error, bytes_to_gb};
+use crate::utils::{check_command_success, parse_frame_rate,
+                   validate_input_path, validate_output_dir, validate_time_range, sanitize_filename};
+use crate::container::{normalize_container, check_codec_compatibility};
+use crate::output::{OutputReservation, final_name_from_temp};
+use crate::verify::{StreamCounts, verify_output, MAX_KEYFRAME_OFFSET};
+use crate::t;
+use crate::binaries::{self, FFmpegStatus, Tool};
+use crate::capabilities;
+use crate::backend::{MediaBackend, ProcessBackend};
+use crate::audio::AudioAdjustments;
+use crate::loudness::measure_stream_with;
+use crate::subtitle::{find_sidecar_subtitles, write_trimmed_sidecars};
+use crate::timestamp::{Rational, ResolvedTime, StreamClock, TimePoint, Timecode};
+
+/// 预留输出文件名的最大尝试次数
+const MAX_RESERVE_ATTEMPTS: usize = 10;
+
+/// 剪辑时重新编码音频使用的码率
+const REENCODE_AUDIO_BITRATE: &str = "192k";
+
+/// 检测系统是否安装了 ffmpeg 和 ffprobe，并报告各自的位置以及 ffmpeg 的能力
+pub fn check_ffmpeg_installed() -> AppResult<FFmpegStatus> {
+    // 重新查找，以反映安装或设置的变化
+    binaries::invalidate();
+    let ffmpeg = binaries::locate(Tool::FFmpeg);
+    let ffprobe = binaries::locate(Tool::FFprobe);
+
+    let installed = ffmpeg.is_some() && ffprobe.is_some();
+    let capabilities = match &ffmpeg {
+        Some(_) => Some(capabilities::capabilities()?),
+        None => None,
+    };
+
+    // 设置中的路径无法运行时会回退到其他位置，需要告知用户
+    let mut messages: Vec<String> = [Tool::FFmpeg, Tool::FFprobe].into_iter()
+        .filter_map(|tool| binaries::rejected_setting(tool)
+            .map(|path| t!("ffmpeg.setting_rejected", tool = tool.name(), path = path)))
+        .collect();
+    if !installed {
+        messages.push(t!("ffmpeg.not_installed"));
+    }
+
+    Ok(FFmpegStatus {
+        installed,
+        ffmpeg,
+        ffprobe,
+        capabilities,
+        message: (!messages.is_empty()).then(|| messages.join("\n")),
+    })
+}
+
+/// 使用 ffprobe 获取视频信息（整合版本）
+pub fn get_video_info(path: &str) -> AppResult<VideoInfo> {
+    get_video_info_with(&ProcessBackend, path)
+}
+
+/// 使用指定后端获取视频信息
+pub fn get_video_info_with(backend: &dyn MediaBackend, path: &str) -> AppResult<VideoInfo> {
+    // 验证输入路径
+    let _validated_path = validate_input_path(path)?;
+
+    // 执行 ffprobe 命令
+    let output = backend.ffprobe(&[
+        "-v", "quiet",
+        "-print_format", "json",
+        "-show_format",
+        "-show_streams",
+        path
+    ])?;
+
+    // 检查命令执行结果
+    check_command_success(&output, "ffprobe")?;
+
+    // 解析 JSON 输出
+    let json_str = String::from_utf8(output.stdout)
+        .map_err(|e| ffprobe_error(t!("probe.output_invalid", error = e)))?;
+
+    let data: Value = serde_json::from_str(&json_str)
+        .map_err(|e| ffprobe_error(t!("probe.json_invalid", error = e)))?;
+
+    // 查找视频流
+    let streams = data["streams"].as_array()
+        .ok_or_else(|| ffprobe_error(t!("probe.no_streams")))?;
+
+    let video_stream = streams.iter()
+        .find(|s| s["codec_type"] == "video")
+        .ok_or_else(|| ffprobe_error(t!("probe.no_video_stream")))?;
+
+    // 提取视频信息
+    let width = video_stream["width"].as_u64().unwrap_or(0) as u32;
+    let height = video_stream["height"].as_u64().unwrap_or(0) as u32;
+    let codec = video_stream["codec_name"].as_str().unwrap_or("unknown").to_string();
+
+    // 解析帧率
+    let fps_str = video_stream["r_frame_rate"].as_str().unwrap_or("0/1");
+    let fps = parse_frame_rate(fps_str);
+
+    // 获取时长
+    let duration = data["format"]["duration"]
+        .as_str()
+        .and_then(|s| s.parse::<f64>().ok())
+        .unwrap_or(0.0);
+
+    let format_name = data["format"]["format_name"].as_str().unwrap_or("unknown").to_string();
+
+    Ok(VideoInfo {
+        frame_rate: fps_str.to_string(),
+        time_base: video_stream["time_base"].as_str().unwrap_or("").to_string(),
+        start_timecode: source_timecode(&data).map(|tc| tc.to_string()),
+        rotation: display_rotation(video_stream),
+        ..VideoInfo::new(duration, width, height, fps, codec, format_name)
+    })
+}
+
+/// 获取视频时长（简化版本，仅获取时长）
+pub fn get_video_duration(path: &str) -> AppResult<f64> {
+    get_video_duration_with(&ProcessBackend, path)
+}
+
+/// 使用指定后端获取视频时长
+pub fn get_video_duration_with(backend: &dyn MediaBackend, path: &str) -> AppResult<f64> {
+    // 验证输入路径
+    let _validated_path = validate_input_path(path)?;
+
+    // 执行 ffprobe 命令
+    let output = backend.ffprobe(&[
+        "-v", "quiet",
+        "-print_format", "json",
+        "-show_format",
+        path
+    ])?;
+
+    // 检查命令执行结果
+    check_command_success(&output, "ffprobe")?;
+
+    // 解析 JSON 输出
+    let json_str = String::from_utf8(output.stdout)
+        .map_err(|e| ffprobe_error(t!("probe.output_invalid", error = e)))?;
+
+    let data: Value = serde_json::from_str(&json_str)
+        .map_err(|e| ffprobe_error(t!("probe.json_invalid", error = e)))?;
+
+    // 获取时长
+    let duration = data["format"]["duration"]
+        .as_str()
+        .and_then(|s| s.parse::<f64>().ok())
+        .ok_or_else(|| ffprobe_error(t!("probe.no_duration")))?;
+
+    Ok(duration)
+}
+
+/// 获取所有流的类型和编码名称 (例如 ("video", "h264"))
+pub fn get_stream_codecs(path: &str) -> AppResult<Vec<(String, String)>> {
+    get_stream_codecs_with(&ProcessBackend, path)
+}
+
+/// 使用指定后端获取所有流的类型和编码名称
+pub fn get_stream_codecs_with(backend: &dyn MediaBackend, path: &str) -> AppResult<Vec<(String, String)>> {
+    // 执行 ffprobe 命令
+    let output = backend.ffprobe(&[
+        "-v", "quiet",
+        "-print_format", "json",
+        "-show_streams",
+        path
+    ])?;
+
+    // 检查命令执行结果
+    check_command_success(&output, "ffprobe")?;
+
+    let data: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| ffprobe_error(t!("probe.json_invalid", error = e)))?;
+
+    let streams = data["streams"].as_array()
+        .ok_or_else(|| ffprobe_error(t!("probe.no_streams")))?;
+
+    Ok(streams.iter()
+        .map(|s| (
+            s["codec_type"].as_str().unwrap_or("unknown").to_string(),
+            s["codec_name"].as_str().unwrap_or("unknown").to_string(),
+        ))
+        .collect())
+}
+
+/// 获取所有音频流的信息
+pub fn get_audio_streams(path: &str) -> AppResult<Vec<AudioStream>> {
+    get_audio_streams_with(&ProcessBackend, path)
+}
+
+/// 使用指定后端获取所有音频流的信息
+pub fn get_audio_streams_with(backend: &dyn MediaBackend, path: &str) -> AppResult<Vec<AudioStream>> {
+    // 验证输入路径
+    let _validated_path = validate_input_path(path)?;
+
+    // 执行 ffprobe 命令
+    let output = backend.ffprobe(&[
+        "-v", "quiet",
+        "-print_format", "json",
+        "-show_streams",
+        "-select_streams", "a",
+        path
+    ])?;
+
+    // 检查命令执行结果
+    check_command_success(&output, "ffprobe")?;
+
+    let data: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| ffprobe_error(t!("probe.json_invalid", error = e)))?;
+
+    let streams = data["streams"].as_array()
+        .ok_or_else(|| ffprobe_error(t!("probe.no_streams")))?;
+
+    // ffprobe 把码率和采样率输出为字符串
+    let number = |v: &Value| v.as_str().and_then(|s| s.parse::<u64>().ok());
+    Ok(streams.iter()
+        .filter(|s| s["codec_type"] == "audio")
+        .map(|s| AudioStream {
+            index: s["index"].as_u64().unwrap_or(0) as u32,
+            codec: s["codec_name"].as_str().unwrap_or("unknown").to_string(),
+            channels: s["channels"].as_u64().unwrap_or(0) as u32,
+            sample_rate: number(&s["sample_rate"]).unwrap_or(0) as u32,
+            bit_rate: number(&s["bit_rate"]),
+            language: s["tags"]["language"].as_str().map(|l| l.to_string()),
+        })
+        .collect())
+}
+
+/// 获取所有字幕流的信息
+pub fn get_subtitle_streams(path: &str) -> AppResult<Vec<SubtitleStream>> {
+    get_subtitle_streams_with(&ProcessBackend, path)
+}
+
+/// 使用指定后端获取所有字幕流的信息
+pub fn get_subtitle_streams_with(backend: &dyn MediaBackend, path: &str) -> AppResult<Vec<SubtitleStream>> {
+    // 验证输入路径
+    let _validated_path = validate_input_path(path)?;
+
+    // 执行 ffprobe 命令
+    let output = backend.ffprobe(&[
+        "-v", "quiet",
+        "-print_format", "json",
+        "-show_streams",
+        "-select_streams", "s",
+        path
+    ])?;
+
+    // 检查命令执行结果
+    check_command_success(&output, "ffprobe")?;
+
+    let data: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| ffprobe_error(t!("probe.json_invalid", error = e)))?;
+
+    let streams = data["streams"].as_array()
+        .ok_or_else(|| ffprobe_error(t!("probe.no_streams")))?;
+
+    let tag = |s: &Value, name: &str| s["tags"][name].as_str().map(|v| v.to_string());
+    Ok(streams.iter()
+        .filter(|s| s["codec_type"] == "subtitle")
+        .map(|s| SubtitleStream {
+            index: s["index"].as_u64().unwrap_or(0) as u32,
+            codec: s["codec_name"].as_str().unwrap_or("unknown").to_string(),
+            language: tag(s, "language"),
+            title: tag(s, "title"),
+        })
+        .collect())
+}
+
+/// 获取视频流的时间基和帧率
+pub fn get_stream_clock(path: &str) -> AppResult<StreamClock> {
+    get_stream_clock_with(&ProcessBackend, path)
+}
+
+/// 使用指定后端获取视频流的时间基、帧率和源起始时间码
+pub fn get_stream_clock_with(backend: &dyn MediaBackend, path: &str) -> AppResult<StreamClock> {
+    probe_stream_clock(backend, path).map(|(clock, _)| clock)
+}
+
+/// 获取视频流的时钟和容器的起始时间（秒）
+fn probe_stream_clock(backend: &dyn MediaBackend, path: &str) -> AppResult<(StreamClock, Rational)> {
+    // 执行 ffprobe 命令（时间码可能在 tmcd 数据流中，因此需要所有流）
+    let output = backend.ffprobe(&[
+        "-v", "quiet",
+        "-print_format", "json",
+        "-show_format",
+        "-show_streams",
+        path
+    ])?;
+
+    // 检查命令执行结果
+    check_command_success(&output, "ffprobe")?;
+
+    let data: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| ffprobe_error(t!("probe.json_invalid", error = e)))?;
+
+    let video_stream = data["streams"].as_array()
+        .and_then(|streams| streams.iter().find(|s| s["codec_type"] == "video"))
+        .ok_or_else(|| ffprobe_error(t!("probe.no_video_stream")))?;
+
+    let clock = StreamClock::from_probe(
+        video_stream["time_base"].as_str().unwrap_or(""),
+        video_stream["r_frame_rate"].as_str().unwrap_or(""),
+    );
+    let start_time = data["format"]["start_time"].as_str()
+        .and_then(Rational::parse)
+        .unwrap_or(Rational::ZERO);
+    Ok((clock.with_start_timecode(source_timecode(&data)), start_time))
+}
+
+/// 从 ffprobe 输出中提取源起始时间码
+///
+/// 依次查找：容器元数据、tmcd 轨道、视频流元数据
+fn source_timecode(data: &Value) -> Option<Timecode> {
+    let streams = data["streams"].as_array().map(|s| s.as_slice()).unwrap_or(&[]);
+    let tmcd = streams.iter().filter(|s| s["codec_tag_string"] == "tmcd");
+    let video = streams.iter().filter(|s| s["codec_type"] == "video");
+
+    std::iter::once(&data["format"])
+        .chain(tmcd)
+        .chain(video)
+        .filter_map(|v| v["tags"]["timecode"].as_str())
+        .find_map(Timecode::parse)
+}
+
+/// 视频流显示矩阵的逆时针旋转角度，归一化到 0 / 90 / 180 / 270
+///
+/// 新版 ffprobe 在 side_data_list 中给出逆时针角度，旧版使用顺时针的 `rotate` 标签
+fn display_rotation(stream: &Value) -> i32 {
+    let side_data = stream["side_data_list"].as_array()
+        .and_then(|list| list.iter().find_map(|d| d["rotation"].as_f64()));
+    let tag = stream["tags"]["rotate"].as_str()
+        .and_then(|r| r.trim().parse::<f64>().ok())
+        .map(|r| -r);
+
+    let degrees = side_data.or(tag).unwrap_or(0.0);
+    (((degrees / 90.0).round() as i32 * 90) % 360 + 360) % 360
+}
+
+/// 查找不晚于 `time` 的最近一个视频关键帧（相对于文件开头的秒数）
+///
+/// `start_time` 为容器的起始时间（见 [`ResolvedRange::start_time`]）。
+/// 流复制剪辑从该关键帧开始，输出的第一帧即为此帧；找不到时返回 `None`
+pub fn find_keyframe_before_with(backend: &dyn MediaBackend, path: &str, start_time: Rational, time: Rational) -> AppResult<Option<Rational>> {
+    let from = (time - Rational::from_integer(MAX_KEYFRAME_OFFSET as i64)).max(Rational::ZERO);
+    // 多读 1 秒，确保包含恰好位于 `time` 的关键帧
+    let keyframes = list_keyframes_with(backend, path, start_time, from, time + Rational::from_integer(1))?;
+    Ok(keyframes.into_iter().filter(|t| *t <= time).max())
+}
+
+/// 列出 `from` 到 `to` 附近的视频关键帧（相对于文件开头的秒数，升序）
+///
+/// `-read_intervals` 使用流的绝对时间戳，因此区间需要加上容器的起始时间 `start_time`。
+/// ffprobe 按数据包读取区间，结果可能包含区间外少量的关键帧，调用方需要自行过滤
+pub fn list_keyframes_with(
+    backend: &dyn MediaBackend,
+    path: &str,
+    start_time: Rational,
+    from: Rational,
+    to: Rational
+) -> AppResult<Vec<Rational>> {
+    let interval = format!("{}%{}", (from + start_time).to_ffmpeg_seconds(), (to + start_time).to_ffmpeg_seconds());
+
+    let output = backend.ffprobe(&[
+        "-v", "quiet",
+        "-print_format", "json",
+        "-select_streams", "v:0",
+        "-skip_frame", "nokey",
+        "-read_intervals", &interval,
+        "-show_entries", "frame=pts_time",
+        path
+    ])?;
+
+    check_command_success(&output, "ffprobe")?;
+
+    let data: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| ffprobe_error(t!("probe.json_invalid", error = e)))?;
+
+    // ffmpeg 的 -ss 相对于容器的起始时间
+    let mut keyframes: Vec<Rational> = data["frames"].as_array()
+        .map(|frames| frames.iter()
+            .filter_map(|f| f["pts_time"].as_str().and_then(Rational::parse))
+            .map(|t| t - start_time)
+            .collect())
+        .unwrap_or_default();
+    keyframes.sort();
+    keyframes.dedup();
+
+    Ok(keyframes)
+}
+
+/// 已换算并验证的时间范围
+#[derive(Debug, Clone, Copy)]
+pub struct ResolvedRange {
+    pub clock: StreamClock,     // 视频流的时间基、帧率和起始时间码
+    pub start: Rational,        // 开始时间（秒）
+    pub end: Rational,          // 结束时间（秒）
+    pub total_duration: f64,    // 源文件总时长（秒）
+    pub start_time: Rational,   // 容器起始时间（秒），ffprobe 的读取区间使用绝对时间戳
+}
+
+impl ResolvedRange {
+    /// 范围的精确时长
+    pub fn duration(&self) -> Rational {
+        self.end - self.start
+    }
+}
+
+/// 按视频流的帧率换算开始和结束时间，并验证其位于视频范围内
+pub fn resolve_range_with(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    start: &TimePoint,
+    end: &TimePoint
+) -> AppResult<ResolvedRange> {
+    let (clock, start_time) = probe_stream_clock(backend, input_path)?;
+    let start = start.resolve(&clock)?;
+    let end = end.resolve(&clock)?;
+
+    // 获取视频总时长
+    let total_duration = get_video_duration_with(backend, input_path)?;
+
+    // 验证时间范围
+    validate_time_range(start.to_f64(), end.to_f64(), total_duration)?;
+
+    Ok(ResolvedRange { clock, start, end, total_duration, start_time })
+}
+
+/// 换算可选的时间范围，都未指定时返回 `None` 表示整个文件
+///
+/// 只指定一端时，另一端为文件开头或结尾
+pub fn resolve_optional_range_with(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    start: Option<&TimePoint>,
+    end: Option<&TimePoint>
+) -> AppResult<Option<ResolvedRange>> {
+    if start.is_none() && end.is_none() {
+        return Ok(None);
+    }
+    let start = start.copied().unwrap_or(TimePoint::seconds(0.0));
+    let end = match end {
+        Some(end) => *end,
+        None => TimePoint::seconds(get_video_duration_with(backend, input_path)?),
+    };
+    resolve_range_with(backend, input_path, &start, &end).map(Some)
+}
+
+/// 将时间点换算为秒、帧号和时间码
+pub fn resolve_time(path: &str, time: &TimePoint) -> AppResult<ResolvedTime> {
+    // 验证输入路径
+    let _validated_path = validate_input_path(path)?;
+
+    let clock = get_stream_clock(path)?;
+    Ok(ResolvedTime::new(time.resolve(&clock)?, &clock))
+}
+
+/// 检查磁盘空间是否足够
+pub fn check_disk_space_for_output(
+    output_path: &Path,
+    estimated_output_size: u64
+) -> AppResult<()> {
+    // 获取输出文件的父目录
+    let parent_dir = output_path.parent()
+        .ok_or_else(|| path_error(t!("path.no_output_dir")))?;
+
+    // 获取磁盘可用空间
+    let available_space = get_available_disk_space(parent_dir)
+        .map_err(|e| filesystem_error(t!("disk.query_failed", error = e)))?;
+
+    // 添加安全缓冲区（额外20%空间）
+    let required_space = estimated_output_size + (estimated_output_size / 5);
+
+    if available_space < required_space {
+        return Err(AppError::InsufficientSpace {
+            needed_gb: bytes_to_gb(required_space),
+            available_gb: bytes_to_gb(available_space),
+            path: parent_dir.to_string_lossy().to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// 估算输出文件大小
+pub fn estimate_output_size(
+    input_path: &Path,
+    start_time: f64,
+    end_time: f64,
+    original_duration: f64
+) -> AppResult<u64> {
+    // 获取原始文件大小
+    let input_size = fs::metadata(input_path)
+        .map_err(|e| filesystem_error(t!("file.size_unreadable", error = e)))?
+        .len();
+
+    // 计算剪辑片段的比例
+    let duration_ratio = (end_time - start_time) / original_duration;
+
+    // 估算输出大小（使用流复制，大小应接近比例）
+    let estimated_size = (input_size as f64 * duration_ratio * 1.1) as u64; // 10% 缓冲
+
+    Ok(estimated_size)
+}
+
+/// 解析文件名模式，提取基础名、扩展名和版本号序列
+fn parse_filename_pattern(input_path: &str) -> AppResult<(String, String, Vec<u32>)> {
+    let path = Path::new(input_path);
+    let stem = path.file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| path_error(t!("path.no_file_name")))?;
+    let ext = path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("mp4");
+
+    // 解析版本号模式 (例如: video_1_2.mp4 -> ["video", "1", "2"])
+    let parts: Vec<&str> = stem.split('_').collect();
+
+    // 使用更智能的版本检测逻辑
+    if parts.len() == 1 {
+        // 单个部分，没有版本号
+        let base_name = parts[0].to_string();
+        return Ok((base_name, ext.to_string(), vec![]));
+    }
+
+    // 检查是否所有部分都是数字（如 "1" 或 "1_2"）
+    let all_numeric = parts.iter().all(|part| part.chars().all(|c| c.is_ascii_digit()));
+    if all_numeric {
+        // 所有部分都是数字，没有版本号，将整个作为基础名
+        let base_name = parts.join("_");
+        return Ok((base_name, ext.to_string(), vec![]));
+    }
+
+    // 有混合部分，从后向前检测连续的数字作为版本号
+    let mut base_parts = Vec::new();
+    let mut versions = Vec::new();
+    let mut found_non_numeric = false;
+
+    for part in parts.iter().rev() {
+        if part.chars().all(|c| c.is_ascii_digit()) && !found_non_numeric {
+            let version_num = part.parse::<u32>()
+                .map_err(|_| crate::error::validation_error(t!("naming.invalid_version", version = part)))?;
+            versions.insert(0, version_num);
+        } else {
+            base_parts.insert(0, *part);
+            found_non_numeric = true;
+        }
+    }
+
+    let base_name = base_parts.join("_");
+    Ok((base_name, ext.to_string(), versions))
+}
+
+/// 查找特定前缀的最大版本号
+///
+/// 不区分扩展名：换容器导出和随剪辑写入的外挂字幕 (`video_1.srt`、`video_1.en.srt`) 同样占用版本号，
+/// 保证新版本的视频和外挂字幕都不会与已有文件重名
+fn find_max_version_number(base_name: &str, version_prefix: &str, directory: &Path) -> AppResult<u32> {
+    let mut max_version = 0;
+
+    // 构建预期前缀
+    let expected_prefix = if version_prefix.is_empty() {
+        format!("{}_", base_name)
+    } else {
+        format!("{}_{}_", base_name, version_prefix)
+    };
+
+    if let Ok(entries) = fs::read_dir(directory) {
+        for entry in entries.flatten() {
+            if let Some(file_name) = entry.file_name().to_str() {
+                let file_path = entry.path();
+
+                // 未完成的临时文件同样占用其对应的版本号
+                let final_name = final_name_from_temp(file_name);
+                let file_name = final_name.as_deref().unwrap_or(file_name);
+
+                // 只检查带扩展名的文件（不是目录）
+                if !file_path.is_file() || !file_name.contains('.') {
+                    continue;
+                }
+
+                // 去掉前缀后，第一个 `.` 之前为版本号和可能的备注
+                if let Some(rest) = file_name.strip_prefix(&expected_prefix) {
+                    let middle_part = rest.split('.').next().unwrap_or_default();
+
+                    // 第一个部分应该是版本号
+                    if let Some(first_part) = middle_part.split('_').next() {
+                        if !first_part.is_empty() && first_part.chars().all(|c| c.is_ascii_digit()) {
+                            if let Ok(version) = first_part.parse::<u32>() {
+                                max_version = max_version.max(version);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(max_version)
+}
+
+/// 生成下一个可用的版本文件名
+///
+/// `output_dir` 为空时输出到源文件所在目录，`container` 为空时沿用源文件扩展名
+pub fn generate_next_filename(
+    input_path: &str,
+    notes: Option<&str>,
+    output_dir: Option<&Path>,
+    container: Option<&str>
+) -> AppResult<PathBuf> {
+    let path = Path::new(input_path);
+    let parent = match output_dir {
+        Some(dir) => dir,
+        None => path.parent().ok_or_else(|| path_error(t!("path.no_parent_dir")))?,
+    };
+
+    let (base_name, source_ext, versions) = parse_filename_pattern(input_path)?;
+    let ext = container.map(|c| c.to_string()).unwrap_or(source_ext);
+
+    // 清理备注内容
+    let sanitized_notes = notes
+        .map(sanitize_filename)
+        .filter(|n| !n.is_empty())
+        .map(|n| n.replace('_', "-")); // 替换下划线为短横线
+
+    let next_version = if versions.is_empty() {
+        // 基础文件，查找第一级版本 (video_1.mp4, video_2.mp4...)
+        find_max_version_number(&base_name, "", parent)? + 1
+    } else {
+        // 版本文件，查找下一级版本 (video_1_1.mp4, video_1_2.mp4...)
+        let version_prefix = versions.iter().map(|v| v.to_string()).collect::<Vec<_>>().join("_");
+        find_max_version_number(&base_name, &version_prefix, parent)? + 1
+    };
+
+    // 构建新文件名
+    let new_filename = if versions.is_empty() {
+        // 基础文件命名：base_version_notes.ext 或 base_version.ext
+        match sanitized_notes {
+            Some(notes) => format!("{}_{}_{}.{}", base_name, next_version, notes, ext),
+            None => format!("{}_{}.{}", base_name, next_version, ext),
+        }
+    } else {
+        // 版本文件命名：base_version_prefix_version_notes.ext 或 base_version_prefix_version.ext
+        let version_prefix = versions.iter().map(|v| v.to_string()).collect::<Vec<_>>().join("_");
+        match sanitized_notes {
+            Some(notes) => format!("{}_{}_{}_{}.{}", base_name, version_prefix, next_version, notes, ext),
+            None => format!("{}_{}_{}.{}", base_name, version_prefix, next_version, ext),
+        }
+    };
+
+    Ok(parent.join(new_filename))
+}
+
+/// 生成下一个版本文件名并预留，避免并发任务使用同一文件名
+///
+/// 参数含义同 [`generate_next_filename`]
+pub fn reserve_next_output(
+    input_path: &str,
+    notes: Option<&str>,
+    output_dir: Option<&Path>,
+    ext: Option<&str>
+) -> AppResult<OutputReservation> {
+    for _ in 0..MAX_RESERVE_ATTEMPTS {
+        let candidate = generate_next_filename(input_path, notes, output_dir, ext)?;
+        if let Some(reservation) = OutputReservation::reserve(&candidate)? {
+            return Ok(reservation);
+        }
+    }
+    Err(filesystem_error(t!("output.reserve_failed")))
+}
+
+/// 列出由源文件派生的所有版本文件（包括多级版本），按版本号排序
+///
+/// `output_dir` 为空时在源文件所在目录中查找，`container` 为空时沿用源文件扩展名
+pub fn list_versions(
+    input_path: &str,
+    output_dir: Option<&Path>,
+    container: Option<&str>
+) -> AppResult<Vec<VersionEntry>> {
+    let path = Path::new(input_path);
+    let directory = match output_dir {
+        Some(dir) => dir,
+        None => path.parent().ok_or_else(|| path_error(t!("path.no_parent_dir")))?,
+    };
+
+    let (base_name, source_ext, versions) = parse_filename_pattern(input_path)?;
+    let ext = container.map(|c| c.to_string()).unwrap_or(source_ext);
+    let prefix = format!("{}_", base_name);
+    let suffix = format!(".{}", ext);
+
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(directory)?.flatten() {
+        let file_path = entry.path();
+        let Some(file_name) = entry.file_name().to_str().map(|s| s.to_string()) else { continue };
+        if !file_path.is_file() || !file_name.starts_with(&prefix) || !file_name.ends_with(&suffix) {
+            continue;
+        }
+        if file_name.len() < prefix.len() + suffix.len() {
+            continue;
+        }
+
+        // 中间部分: 版本号序列，后跟可选的备注 (例如 "1_2_notes")
+        let middle = &file_name[prefix.len()..file_name.len() - suffix.len()];
+        let parts: Vec<&str> = middle.split('_').collect();
+        let numeric = parts.iter()
+            .take_while(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+            .count();
+        let Ok(entry_versions) = parts[..numeric].iter()
+            .map(|p| p.parse::<u32>())
+            .collect::<Result<Vec<u32>, _>>() else { continue };
+
+        // 只保留源文件的后代版本
+        if entry_versions.len() <= versions.len() || !entry_versions.starts_with(&versions) {
+            continue;
+        }
+
+        let notes = parts[numeric..].join("_");
+        entries.push(VersionEntry {
+            path: file_path.to_string_lossy().to_string(),
+            versions: entry_versions,
+            notes: if notes.is_empty() { None } else { Some(notes) },
+        });
+    }
+
+    entries.sort_by(|a, b| a.versions.cmp(&b.versions));
+    Ok(entries)
+}
+
+/// 剪辑视频（整合版本）
+///
+/// `start` / `end` 可以是秒、帧号或时间码，按视频流的帧率精确换算；
+/// `output_dir` 指定输出目录（默认与源文件相同），`container` 指定输出容器（默认沿用源文件格式），
+/// `audio` 指定音频处理（静音、增益、淡入淡出、下混、移除音轨、响度标准化，视频仍然流复制），
+/// `verify_decode` 为真时在校验阶段额外进行一次完整解码
+#[allow(clippy::too_many_arguments)]
+pub fn cut_video(
+    input_path: &str,
+    start: &TimePoint,
+    end: &TimePoint,
+    notes: Option<&str>,
+    output_dir: Option<&str>,
+    container: Option<&str>,
+    audio: &AudioAdjustments,
+    verify_decode: bool
+) -> AppResult<CutResult> {
+    cut_video_with(&ProcessBackend, input_path, start, end, notes, output_dir, container, audio, verify_decode)
+}
+
+/// 使用指定后端剪辑视频，参数含义同 [`cut_video`]
+#[allow(clippy::too_many_arguments)]
+pub fn cut_video_with(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    start: &TimePoint,
+    end: &TimePoint,
+    notes: Option<&str>,
+    output_dir: Option<&str>,
+    container: Option<&str>,
+    audio: &AudioAdjustments,
+    verify_decode: bool
+) -> AppResult<CutResult> {
+    // 验证输入路径
+    let validated_path = validate_input_path(input_path)?;
+
+    // 验证输出目录
+    let output_dir = output_dir
+        .map(validate_output_dir)
+        .transpose()?;
+
+    // 规范化输出容器并检查编码兼容性
+    let container = container
+        .map(normalize_container)
+        .transpose()?;
+
+    let source_streams = get_stream_codecs_with(backend, input_path)?;
+    let keep_subtitles = match &container {
+        Some(ext) => check_codec_compatibility(ext, &source_streams)?,
+        None => true,
+    };
+
+    // 换算并验证时间范围
+    let range = resolve_range_with(backend, input_path, start, end)?;
+    let ResolvedRange { clock, start, end, total_duration, start_time: container_start } = range;
+    let (start_time, end_time) = (start.to_f64(), end.to_f64());
+
+    // 计算剪辑持续时间（精确值，避免浮点误差）
+    let duration = end - start;
+
+    // 验证音频处理参数，保留的音频流按序号排列
+    let audio_streams: Vec<AudioStream> = if audio.is_empty() {
+        Vec::new()
+    } else {
+        let streams = get_audio_streams_with(backend, input_path)?;
+        audio.validate(duration.to_f64(), &streams)?;
+        streams.into_iter().filter(|s| !audio.drop_tracks.contains(&s.index)).collect()
+    };
+
+    // 生成输出文件路径并预留版本号，避免并发剪辑使用同一文件名
+    let reservation = reserve_next_output(input_path, notes, output_dir.as_deref(), container.as_deref())?;
+
+    // 估算输出文件大小
+    let estimated_size = estimate_output_size(&validated_path, start_time, end_time, total_duration)?;
+
+    // 检查磁盘空间
+    check_disk_space_for_output(reservation.final_path(), estimated_size)?;
+
+    // 流复制的输出从起点之前的关键帧开始，时间码和外挂字幕都以该帧为准
+    let sidecars = find_sidecar_subtitles(&validated_path)?;
+    let first_frame = if clock.start_timecode.is_some() || !sidecars.is_empty() {
+        find_keyframe_before_with(backend, input_path, container_start, start)?.unwrap_or(start)
+    } else {
+        start
+    };
+
+    // 源文件带有时间码时，输出从实际起点对应的时间码开始
+    let timecode = match clock.start_timecode {
+        Some(_) => Some(clock.timecode_at_frame(clock.nearest_frame(first_frame)?)?.to_string()),
+        None => None,
+    };
+    let timecode_metadata = timecode.as_ref().map(|tc| format!("timecode={}", tc));
+
+    // 响度标准化：测量唯一保留的音频流
+    let loudness = match (audio.loudness, audio_streams.first()) {
+        (Some(preset), Some(stream)) => Some(measure_stream_with(backend, input_path, Some(&range), stream.index, preset)?),
+        _ => None,
+    };
+    let loudness_filter = loudness.as_ref()
+        .map(|report| report.normalize_filter(audio_streams.first().map(|s| s.sample_rate)));
+    let audio_filter = audio.filter_chain(duration, loudness_filter.as_deref());
+    let output_ext = reservation.final_path().extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+
+    // 执行 ffmpeg 剪辑
+    let start_str = start.to_ffmpeg_seconds();
+    let duration_str = duration.to_ffmpeg_seconds();
+    let mut args = vec![
+        "-ss", &start_str,
+        "-i", input_path,
+        "-t", &duration_str,
+    ];
+    let dropped_maps: Vec<String> = audio.drop_tracks.iter().map(|i| format!("-0:{}", i)).collect();
+    if !audio.is_empty() {
+        // 处理音频时显式选择所有视频、音频（和字幕）流，再排除移除的音频流，
+        // 否则 ffmpeg 默认只选择一个音频流
+        args.extend(["-map", "0:v?", "-map", "0:a?"]);
+        if keep_subtitles {
+            args.extend(["-map", "0:s?"]);
+        }
+        for map in &dropped_maps {
+            args.extend(["-map", map]);
+        }
+    }
+    args.extend(["-c", "copy", "-avoid_negative_ts", "1"]);
+    let channels = audio.downmix.map(|d| d.channels().to_string());
+    if audio.needs_reencode() {
+        // 只重新编码音频，视频仍然流复制
+        if let Some(filter) = &audio_filter {
+            args.extend(["-af", filter]);
+        }
+        args.extend(["-c:a", reencode_audio_encoder(&output_ext), "-b:a", REENCODE_AUDIO_BITRATE]);
+        if let Some(channels) = &channels {
+            args.extend(["-ac", channels]);
+        }
+    }
+    if !keep_subtitles {
+        // 目标容器不支持原字幕编码，丢弃字幕流
+        args.push("-sn");
+    }
+    if let Some(metadata) = &timecode_metadata {
+        // 覆盖从源文件复制的时间码（mov / mp4 会据此写入 tmcd 轨道）
+        args.extend(["-metadata", metadata, "-metadata:s:v:0", metadata]);
+    }
+    args.push("-y");  // 覆盖已预留的临时文件
+    args.push(reservation.temp_path().to_str().ok_or_else(|| path_error(t!("path.not_utf8")))?);
+
+    let output = backend.ffmpeg(&args)?;
+
+    // 检查 ffmpeg 执行结果
+    check_command_success(&output, "ffmpeg")?;
+
+    // 检查临时文件并重命名为最终文件名
+    let output_path = reservation.commit()?;
+    let output_str = output_path.to_string_lossy().to_string();
+
+    // 源文件旁的外挂字幕裁剪并平移后写到输出旁，失败的只记录在结果中
+    let (subtitles, skipped_subtitles) = write_trimmed_sidecars(&sidecars, &validated_path, &output_path, first_frame, end);
+
+    // 探测输出文件，校验时长和流
+    let expected_streams = if audio.is_empty() {
+        StreamCounts::from_streams(&source_streams).expected_output(keep_subtitles)
+    } else {
+        // 显式选择流时输出包含所有未移除的流
+        let kept: Vec<(String, String)> = source_streams.iter().enumerate()
+            .filter(|(i, (codec_type, _))| {
+                !audio.drop_tracks.contains(&(*i as u32)) && (keep_subtitles || codec_type != "subtitle")
+            })
+            .map(|(_, stream)| stream.clone())
+            .collect();
+        StreamCounts::from_streams(&kept)
+    };
+    let verification = verify_output(backend, &output_str, duration.to_f64(), expected_streams, false, verify_decode)?;
+
+    let message = if verification.passed {
+        t!("cut.success", path = output_path.display())
+    } else {
+        t!("cut.verification_failed", path = output_path.display())
+    };
+
+    Ok(CutResult {
+        output_path: output_str,
+        message,
+        verification,
+        timecode,
+        loudness,
+        audio: (!audio.is_empty()).then(|| audio.clone()),
+        subtitles,
+        skipped_subtitles,
+    })
+}
+
+/// 剪辑时重新编码音频使用的编码器：WebM 只接受 Vorbis / Opus，其余容器使用 AAC
+fn reencode_audio_encoder(ext: &str) -> &'static str {
+    match ext {
+        "webm" => "libopus",
+        _ => "aac",
+    }
+}
+
+/// 获取磁盘可用空间（使用 sysinfo 跨平台实现）
+fn get_available_disk_space(path: &Path) -> Result<u64, std::io::Error> {
+    use sysinfo::Disks;
+
+    // 获取路径的元数据（验证路径有效性）
+    let _metadata = fs::metadata(path)?;
+
+    // 获取路径的绝对路径
+    let canonical_path = path.canonicalize()
+        .map_err(|e| std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            t!("path.canonicalize_failed", error = e)
+        ))?;
+
+    let path_str = canonical_path.to_string_lossy().to_string();
+
+    // 只在 Windows 上处理扩展路径格式 (\\?\C:\path -> C:\path)
+    #[cfg(windows)]
+    let path_str = match path_str.strip_prefix("\\\\?\\") {
+        Some(stripped) => stripped.to_string(),
+        None => path_str,
+    };
+
+    // 初始化磁盘列表
+    let disks = Disks::new_with_refreshed_list();
+
+    // 查找包含目标路径的磁盘
+    for disk in &disks {
+        let mount_point = disk.mount_point().to_string_lossy();
+        if path_str.starts_with(&*mount_point) {
+            return Ok(disk.available_space());
+        }
+    }
+
+    // 如果没有找到精确匹配的磁盘，尝试找到最接近的父目录
+    let mut best_match: Option<(usize, &sysinfo::Disk)> = None;
+
+    for disk in &disks {
+        let mount_point = disk.mount_point().to_string_lossy();
+        if mount_point.len() > best_match.as_ref().map_or(0, |(len, _)| *len)
+            && path_str.starts_with(&*mount_point) {
+            best_match = Some((mount_point.len(), disk));
+        }
+    }
+
+    if let Some((_, disk)) = best_match {
+        return Ok(disk.available_space());
+    }
+
+    // 如果仍然没有找到，使用第一个可用磁盘作为兜底
+    if let Some(first_disk) = disks.iter().next() {
+        return Ok(first_disk.available_space());
+    }
+
+    // 最后的兜底方案：返回保守的估计值
+    Ok(5 * 1024 * 1024 * 1024) // 5GB
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{CommandOutput, MockBackend};
+    use crate::audio::Downmix;
+    use crate::diagnostics::DiagnosticKind;
+    use crate::loudness::LoudnessPreset;
+    use crate::output::temp_path_for;
+
+    #[test]
+    fn test_parse_filename_pattern() {
+        // 测试纯数字文件名
+        let result = parse_filename_pattern("1.mp4").unwrap();
+        assert_eq!(result, ("1".to_string(), "mp4".to_string(), vec![]));
+
+        // 测试纯数字复合文件名
+        let result = parse_filename_pattern("1_2.mp4").unwrap();
+        assert_eq!(result, ("1_2".to_string(), "mp4".to_string(), vec![]));
+
+        // 测试普通文件名
+        let result = parse_filename_pattern("video.mp4").unwrap();
+        assert_eq!(result, ("video".to_string(), "mp4".to_string(), vec![]));
+
+        // 测试版本文件名
+        let result = parse_filename_pattern("video_1.mp4").unwrap();
+        assert_eq!(result, ("video".to_string(), "mp4".to_string(), vec![1]));
+
+        // 测试嵌套版本文件名
+        let result = parse_filename_pattern("video_1_2.mp4").unwrap();
+        assert_eq!(result, ("video".to_string(), "mp4".to_string(), vec![1, 2]));
+    }
+
+    #[test]
+    fn test_list_versions() {
+        let dir = std::env::temp_dir().join(format!("instant-cut-versions-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["clip.mp4", "clip_1.mp4", "clip_2_intro.mp4", "clip_1_1.mp4",
+                     "clip_3.mkv", "other_1.mp4", ".clip_4.instant-cut-tmp.mp4"] {
+            fs::write(dir.join(name), b"x").unwrap();
+        }
+
+        let source = dir.join("clip.mp4");
+        let entries = list_versions(source.to_str().unwrap(), None, None).unwrap();
+        let versions: Vec<Vec<u32>> = entries.iter().map(|e| e.versions.clone()).collect();
+        assert_eq!(versions, vec![vec![1], vec![1, 1], vec![2]]);
+        assert_eq!(entries[2].notes.as_deref(), Some("intro"));
+
+        // 版本文件只列出其下一级及更深的版本
+        let source = dir.join("clip_1.mp4");
+        let entries = list_versions(source.to_str().unwrap(), None, None).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].versions, vec![1, 1]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_next_filename_counts_all_extensions() {
+        let dir = std::env::temp_dir().join(format!("instant-cut-next-name-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["video.mp4", "video_1.mp4", "video_1_1_intro.mov"] {
+            fs::write(dir.join(name), b"x").unwrap();
+        }
+        let source = dir.join("video.mp4");
+        let source = source.to_str().unwrap();
+        let next = |container: Option<&str>| {
+            generate_next_filename(source, None, None, container).unwrap().file_name().unwrap().to_str().unwrap().to_string()
+        };
+
+        // 换容器导出不会重新从 1 开始
+        assert_eq!(next(Some("mkv")), "video_2.mkv");
+
+        // 外挂字幕占用版本号，新版本的字幕不会与其重名
+        fs::write(dir.join("video_2.en.srt"), b"x").unwrap();
+        assert_eq!(next(None), "video_3.mp4");
+
+        let nested = dir.join("video_1.mp4");
+        let nested = generate_next_filename(nested.to_str().unwrap(), None, None, Some("mkv")).unwrap();
+        assert_eq!(nested.file_name().unwrap(), "video_1_2.mkv");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// 包含格式和流信息的 ffprobe 输出
+    fn probe_json(duration: f64, streams: &[(&str, &str)]) -> CommandOutput {
+        let streams: Vec<Value> = streams.iter().enumerate()
+            .map(|(index, (codec_type, codec_name))| serde_json::json!({
+                "index": index,
+                "codec_type": codec_type,
+                "codec_name": codec_name,
+                "width": 1920,
+                "height": 1080,
+                "r_frame_rate": "30000/1001",
+                "time_base": "1/30000",
+            }))
+            .collect();
+        let json = serde_json::json!({
+            "streams": streams,
+            "format": { "duration": duration.to_string(), "format_name": "mov,mp4,m4a,3gp,3g2,mj2" },
+        });
+        CommandOutput::ok(json.to_string())
+    }
+
+    fn mock_source(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("instant-cut-mock-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("clip.mkv");
+        fs::write(&input, vec![0u8; 1024]).unwrap();
+        (dir, input)
+    }
+
+    #[test]
+    fn test_get_video_info_with_mock() {
+        let (dir, input) = mock_source("info");
+        let mock = MockBackend::new();
+        mock.respond(Tool::FFprobe, probe_json(12.5, &[("audio", "aac"), ("video", "h264")]));
+
+        let info = get_video_info_with(&mock, input.to_str().unwrap()).unwrap();
+        assert_eq!(info.duration, 12.5);
+        assert_eq!((info.width, info.height), (1920, 1080));
+        assert_eq!(info.codec, "h264");
+        assert!((info.fps - 29.97).abs() < 0.01);
+        assert_eq!(info.frame_rate, "30000/1001");
+        assert_eq!(info.time_base, "1/30000");
+
+        let args = &mock.calls_to(Tool::FFprobe)[0];
+        assert!(args.contains(&"-show_streams".to_string()));
+        assert_eq!(args.last().unwrap(), input.to_str().unwrap());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_display_rotation() {
+        let side_data = serde_json::json!({ "side_data_list": [{ "side_data_type": "Display Matrix", "rotation": -90 }] });
+        assert_eq!(display_rotation(&side_data), 270);
+        assert_eq!(display_rotation(&serde_json::json!({ "tags": { "rotate": "90" } })), 270);
+        assert_eq!(display_rotation(&serde_json::json!({ "tags": { "rotate": "-180" } })), 180);
+        assert_eq!(display_rotation(&serde_json::json!({})), 0);
+
+        let info = VideoInfo { rotation: 90, ..VideoInfo::new(1.0, 1920, 1080, 30.0, "h264".into(), "mov".into()) };
+        assert_eq!(info.display_size(), (1080, 1920));
+    }
+
+    #[test]
+    fn test_cut_video_with_mock_arguments() {
+        let (dir, input) = mock_source("cut");
+        let mock = MockBackend::new();
+        // 前三次探测源文件（流、帧率、时长），之后探测剪辑输出
+        let source = probe_json(20.0, &[("video", "h264"), ("audio", "aac"), ("subtitle", "subrip")]);
+        for _ in 0..3 {
+            mock.respond(Tool::FFprobe, source.clone());
+        }
+        mock.set_default(Tool::FFprobe, probe_json(5.2, &[("video", "h264"), ("audio", "aac")]));
+        mock.set_output_contents(b"output");
+
+        // mkv 中的 subrip 字幕不能复制到 mp4，应当丢弃；
+        // 29.97 fps 下第 150 帧从 5.005 秒开始，150 帧正好 5.005 秒
+        let (start, end) = ("150f".parse().unwrap(), "300f".parse().unwrap());
+        let result = cut_video_with(
+            &mock, input.to_str().unwrap(), &start, &end, Some("intro"), None, Some("mp4"), &AudioAdjustments::default(), false
+        ).unwrap();
+
+        let expected_path = dir.join("clip_1_intro.mp4");
+        assert_eq!(result.output_path, expected_path.to_string_lossy());
+        assert!(expected_path.exists());
+
+        let ffmpeg_calls = mock.calls_to(Tool::FFmpeg);
+        assert_eq!(ffmpeg_calls.len(), 1);
+        let args = &ffmpeg_calls[0];
+        let temp = temp_path_for(&expected_path).unwrap();
+        assert_eq!(args, &vec![
+            "-ss", "5.005", "-i", input.to_str().unwrap(), "-t", "5.005", "-c", "copy",
+            "-avoid_negative_ts", "1", "-sn", "-y", temp.to_str().unwrap(),
+        ].iter().map(|a| a.to_string()).collect::<Vec<_>>());
+
+        // 校验阶段探测输出文件
+        assert!(result.verification.passed);
+        assert_eq!(result.verification.expected_streams, StreamCounts { video: 1, audio: 1, subtitle: 0 });
+        assert_eq!(mock.calls_to(Tool::FFprobe).last().unwrap().last().unwrap(), expected_path.to_str().unwrap());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_cut_video_with_mock_source_timecode() {
+        let (dir, input) = mock_source("timecode");
+        let mock = MockBackend::new();
+
+        // 起始时间码在 tmcd 轨道中
+        let source = serde_json::json!({
+            "streams": [
+                { "codec_type": "video", "codec_name": "h264", "r_frame_rate": "30000/1001", "time_base": "1/30000" },
+                { "codec_type": "data", "codec_tag_string": "tmcd", "tags": { "timecode": "01:00:00;00" } },
+            ],
+            "format": { "duration": "20.0", "start_time": "1.400000" },
+        }).to_string();
+        for _ in 0..3 {
+            mock.respond(Tool::FFprobe, CommandOutput::ok(source.clone()));
+        }
+        // 容器从 1.4 秒开始，起点之前最近的关键帧在 4.004 秒（第 120 帧）
+        mock.respond(Tool::FFprobe, CommandOutput::ok(serde_json::json!({
+            "frames": [{ "pts_time": "1.400000" }, { "pts_time": "5.404000" }, { "pts_time": "9.408000" }],
+        }).to_string()));
+        mock.set_default(Tool::FFprobe, probe_json(6.0, &[("video", "h264")]));
+        mock.set_output_contents(b"output");
+
+        let (start, end) = ("01:00:05;00".parse().unwrap(), "01:00:10;00".parse().unwrap());
+        let result = cut_video_with(&mock, input.to_str().unwrap(), &start, &end, None, None, None, &AudioAdjustments::default(), false).unwrap();
+        assert_eq!(result.timecode.as_deref(), Some("01:00:04;00"));
+
+        // 读取区间使用绝对时间戳
+        let probe = &mock.calls_to(Tool::FFprobe)[3];
+        let interval = probe.iter().position(|a| a == "-read_intervals").unwrap();
+        assert_eq!(probe[interval + 1], "1.4%7.405");
+
+        let args = &mock.calls_to(Tool::FFmpeg)[0];
+        assert_eq!(&args[..2], &["-ss".to_string(), "5.005".to_string()]);
+        let metadata = args.iter().position(|a| a == "-metadata").unwrap();
+        assert_eq!(args[metadata + 1], "timecode=01:00:04;00");
+        assert_eq!(args[metadata + 3], "timecode=01:00:04;00");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_cut_video_with_mock_loudness() {
+        let (dir, input) = mock_source("loudness");
+        let mock = MockBackend::new();
+        // 流、帧率、时长、音频流各探测一次，之后探测剪辑输出
+        let source = probe_json(20.0, &[("video", "h264"), ("audio", "aac")]);
+        for _ in 0..4 {
+            mock.respond(Tool::FFprobe, source.clone());
+        }
+        mock.set_default(Tool::FFprobe, probe_json(5.0, &[("video", "h264"), ("audio", "aac")]));
+        mock.respond(Tool::FFmpeg, CommandOutput {
+            code: Some(0),
+            stdout: Vec::new(),
+            stderr: br#"{ "input_i" : "-20.5", "input_tp" : "-3.0", "input_lra" : "6.0",
+                          "input_thresh" : "-31.0", "target_offset" : "-0.1" }"#.to_vec(),
+        });
+        mock.set_output_contents(b"output");
+
+        let result = cut_video_with(
+            &mock, input.to_str().unwrap(), &TimePoint::seconds(5.0), &TimePoint::seconds(10.0), None, None,
+            None, &AudioAdjustments { loudness: Some(LoudnessPreset::Streaming), ..Default::default() }, false
+        ).unwrap();
+        assert_eq!(result.loudness.map(|r| r.input_i), Some(-20.5));
+        assert!(result.verification.passed);
+
+        // 第一遍只测量所选范围内的音频流，第二遍显式选择所有流、视频流复制、音频按测量值标准化
+        let calls = mock.calls_to(Tool::FFmpeg);
+        assert_eq!(calls.len(), 2);
+        assert!(calls[0].windows(4).any(|w| w == ["-t", "5", "-i", input.to_str().unwrap()]));
+        assert!(calls[0].windows(2).any(|w| w == ["-map", "0:1"]));
+        let args = &calls[1];
+        let filter = args.iter().position(|a| a == "-af").unwrap();
+        assert_eq!(args[..6], ["-ss", "5", "-i", input.to_str().unwrap(), "-t", "5"]);
+        assert_eq!(args[6..12], ["-map", "0:v?", "-map", "0:a?", "-map", "0:s?"]);
+        assert!(args[filter + 1].starts_with("loudnorm=I=-14:TP=-1:LRA=11:measured_I=-20.5:"));
+        assert_eq!(args[filter + 2..filter + 6], ["-c:a", "aac", "-b:a", "192k"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_cut_video_with_mock_audio_adjustments() {
+        let (dir, input) = mock_source("adjust");
+        let mock = MockBackend::new();
+        let source = probe_json(20.0, &[("video", "h264"), ("audio", "aac"), ("audio", "ac3")]);
+        for _ in 0..4 {
+            mock.respond(Tool::FFprobe, source.clone());
+        }
+        mock.set_default(Tool::FFprobe, probe_json(4.0, &[("video", "h264"), ("audio", "aac")]));
+        mock.set_output_contents(b"output");
+
+        let audio = AudioAdjustments {
+            gain_db: Some(-3.5),
+            fade_in: Some(0.5),
+            fade_out: Some(1.0),
+            downmix: Some(Downmix::Mono),
+            drop_tracks: vec![2],
+            ..Default::default()
+        };
+        let result = cut_video_with(
+            &mock, input.to_str().unwrap(), &TimePoint::seconds(2.0), &TimePoint::seconds(6.0), None, None, None,
+            &audio, false
+        ).unwrap();
+        assert_eq!(result.audio.as_ref(), Some(&audio));
+        assert_eq!(result.verification.expected_streams, StreamCounts { video: 1, audio: 1, subtitle: 0 });
+
+        let args = &mock.calls_to(Tool::FFmpeg)[0];
+        assert_eq!(args[6..], [
+            "-map", "0:v?", "-map", "0:a?", "-map", "0:s?", "-map", "-0:2",
+            "-c", "copy", "-avoid_negative_ts", "1",
+            "-af", "volume=-3.5dB,afade=t=in:st=0:d=0.5,afade=t=out:st=3:d=1",
+            "-c:a", "aac", "-b:a", "192k", "-ac", "1",
+            "-y", temp_path_for(&dir.join("clip_1.mkv")).unwrap().to_str().unwrap(),
+        ]);
+
+        // 淡入淡出超过剪辑范围时在执行前拒绝
+        let too_long = AudioAdjustments { fade_in: Some(3.0), fade_out: Some(3.0), ..Default::default() };
+        let err = cut_video_with(
+            &mock, input.to_str().unwrap(), &TimePoint::seconds(2.0), &TimePoint::seconds(6.0), None, None, None,
+            &too_long, false
+        ).unwrap_err();
+        assert!(matches!(err, AppError::ValidationError(_)));
+        assert_eq!(mock.calls_to(Tool::FFmpeg).len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_cut_video_with_mock_ffmpeg_failure() {
+        let (dir, input) = mock_source("fail");
+        let mock = MockBackend::new();
+        mock.set_default(Tool::FFprobe, probe_json(20.0, &[("video", "h264")]));
+        mock.set_output_contents(b"output");
+        mock.respond(Tool::FFmpeg, CommandOutput::failed(1, "clip_1.mkv: Permission denied\n"));
+
+        let err = cut_video_with(
+            &mock, input.to_str().unwrap(), &TimePoint::seconds(0.0), &TimePoint::seconds(5.0), None, None, None, &AudioAdjustments::default(), false
+        ).unwrap_err();
+        assert!(matches!(err, AppError::FFmpegFailure { kind: DiagnosticKind::PermissionDenied, .. }));
+
+        // 失败后不应留下临时文件或输出文件
+        let names: Vec<String> = fs::read_dir(&dir).unwrap().flatten()
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["clip.mkv".to_string()]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_cut_video_with_mock_rejects_invalid_range() {
+        let (dir, input) = mock_source("range");
+        let mock = MockBackend::new();
+        mock.set_default(Tool::FFprobe, probe_json(8.0, &[("video", "h264")]));
+
+        let err = cut_video_with(
+            &mock, input.to_str().unwrap(), &TimePoint::seconds(2.0), &TimePoint::seconds(9.0), None, None, None, &AudioAdjustments::default(), false
+        ).unwrap_err();
+        assert!(matches!(err, AppError::ValidationError(_)));
+        assert!(mock.calls_to(Tool::FFmpeg).is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_probe_failure_with_mock() {
+        let (dir, input) = mock_source("probe");
+        let mock = MockBackend::new();
+        mock.respond(Tool::FFprobe, CommandOutput::failed(1, "Invalid data found when processing input"));
+
+        let err = get_video_duration_with(&mock, input.to_str().unwrap()).unwrap_err();
+        assert!(matches!(err, AppError::FFprobeError { stderr_tail: Some(_), .. }));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_bytes_to_gb() {
+        assert_eq!(bytes_to_gb(1024 * 1024 * 1024), 1.0);
+        assert_eq!(bytes_to_gb(2 * 1024 * 1024 * 1024), 2.0);
+    }
+
+    #[test]
+    fn test_get_available_disk_space() {
+        use std::env;
+        use sysinfo::Disks;
+
+        // 测试当前工作目录的磁盘空间检查
+        let current_dir = env::current_dir().expect("获取当前目录失败");
+        println!("当前目录: {}", current_dir.display());
+
+        // 显示所有可用的磁盘信息
+        let disks = Disks::new_with_refreshed_list();
+        println!("\n所有可用磁盘:");
+        for (i, disk) in disks.iter().enumerate() {
+            println!("  [{}] 磁盘: {:?}", i, disk.name());
+            println!("      挂载点: {}", disk.mount_point().display());
+            println!("      文件系统: {:?}", disk.file_system());
+            println!("      可用空间: {:.2} GB", disk.available_space() as f64 / (1024.0 * 1024.0 * 1024.0));
+            println!("      总空间: {:.2} GB", disk.total_space() as f64 / (1024.0 * 1024.0 * 1024.0));
+            println!();
+        }
+
+        match get_available_disk_space(&current_dir) {
+            Ok(available_space) => {
+                // 验证返回的磁盘空间是合理的（应该大于0）
+                assert!(available_space > 0, "磁盘空间应该大于0");
+                // 验证空间不会过大（防止返回错误的值）
+                assert!(available_space < 100 * 1024 * 1024 * 1024 * 1024, "磁盘空间不应该超过100TB");
+                println!("当前磁盘可用空间: {:.2} GB", available_space as f64 / (1024.0 * 1024.0 * 1024.0));
+            }
+            Err(e) => {
+                // 在某些环境中（如测试容器），这个测试可能会失败
+                println!("警告：无法获取磁盘空间: {}", e);
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,319 @@
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, OnceLock};
+use crate::error::{AppError, filesystem_error, path_error};
+use crate::t;
+
+/// 临时文件名中的标记，用于识别未完成的输出
+const TEMP_MARKER: &str = ".instant-cut-tmp.";
+
+/// 临时文件日志的文件名
+const JOURNAL_FILE: &str = "pending_outputs.txt";
+
+/// 临时文件日志路径，在启动时通过 `init_temp_journal` 设置
+static JOURNAL_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// 保护日志文件的读写
+static JOURNAL_LOCK: Mutex<()> = Mutex::new(());
+
+/// 中间文件目录的序号，避免并发导出互相覆盖
+static WORK_DIR_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// 已预留的输出文件
+///
+/// 创建时在目标目录中独占创建临时文件，从而预留版本号；
+/// 调用 `commit` 后重命名为最终文件名。未提交即被丢弃时会删除临时文件。
+#[derive(Debug)]
+pub struct OutputReservation {
+    temp_path: PathBuf,
+    final_path: PathBuf,
+    committed: bool,
+}
+
+impl OutputReservation {
+    /// 预留输出文件，若最终文件或临时文件已存在则返回 `Ok(None)`
+    pub fn reserve(final_path: &Path) -> Result<Option<Self>, AppError> {
+        if final_path.exists() {
+            return Ok(None);
+        }
+
+        let temp_path = temp_path_for(final_path)?;
+        match OpenOptions::new().write(true).create_new(true).open(&temp_path) {
+            Ok(_) => {}
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => return Ok(None),
+            Err(e) => return Err(filesystem_error(t!("output.temp_create_failed", error = e))),
+        }
+
+        journal_add(&temp_path);
+
+        Ok(Some(Self {
+            temp_path,
+            final_path: final_path.to_path_buf(),
+            committed: false,
+        }))
+    }
+
+    /// 临时文件路径（ffmpeg 的实际写入目标）
+    pub fn temp_path(&self) -> &Path {
+        &self.temp_path
+    }
+
+    /// 最终文件路径
+    pub fn final_path(&self) -> &Path {
+        &self.final_path
+    }
+
+    /// 检查临时文件并将其重命名为最终文件名
+    pub fn commit(mut self) -> Result<PathBuf, AppError> {
+        let size = fs::metadata(&self.temp_path)
+            .map_err(|e| filesystem_error(t!("output.not_found", error = e)))?
+            .len();
+        if size == 0 {
+            return Err(filesystem_error(t!("output.empty")));
+        }
+
+        // 不覆盖已存在的文件：硬链接在目标已存在时失败，检查和提交是同一个原子操作
+        let exists = || filesystem_error(t!("output.exists", path = self.final_path.display()));
+        match fs::hard_link(&self.temp_path, &self.final_path) {
+            Ok(()) => {
+                let _ = fs::remove_file(&self.temp_path);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => return Err(exists()),
+            // 不支持硬链接的文件系统（FAT、部分网络盘）：先独占创建最终文件占位，再用临时文件替换自己的占位
+            Err(_) => {
+                match OpenOptions::new().write(true).create_new(true).open(&self.final_path) {
+                    Ok(_) => {}
+                    Err(e) if e.kind() == ErrorKind::AlreadyExists => return Err(exists()),
+                    Err(e) => return Err(filesystem_error(t!("output.rename_failed", error = e))),
+                }
+                if let Err(e) = fs::rename(&self.temp_path, &self.final_path) {
+                    let _ = fs::remove_file(&self.final_path);
+                    return Err(filesystem_error(t!("output.rename_failed", error = e)));
+                }
+            }
+        }
+
+        self.committed = true;
+        journal_remove(&self.temp_path);
+        Ok(self.final_path.clone())
+    }
+}
+
+impl Drop for OutputReservation {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.temp_path);
+            journal_remove(&self.temp_path);
+        }
+    }
+}
+
+/// 存放中间文件的临时目录，离开作用域时删除
+#[derive(Debug)]
+pub struct WorkDir(PathBuf);
+
+impl WorkDir {
+    /// 在系统临时目录中创建，`purpose` 用于区分目录名 (例如 "join")
+    pub fn new(purpose: &str) -> Result<Self, AppError> {
+        let id = WORK_DIR_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("instant-cut-{}-{}-{}", purpose, std::process::id(), id));
+        fs::create_dir_all(&dir)?;
+        Ok(Self(dir))
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+
+    /// 写入 concat 列表文件
+    pub fn write_list(&self, contents: &str) -> Result<PathBuf, AppError> {
+        let path = self.path("list.txt");
+        fs::write(&path, contents)?;
+        Ok(path)
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// 根据最终文件路径生成临时文件路径 (video_1.mp4 -> .video_1.instant-cut-tmp.mp4)
+pub fn temp_path_for(final_path: &Path) -> Result<PathBuf, AppError> {
+    let parent = final_path.parent()
+        .ok_or_else(|| path_error(t!("path.no_output_dir")))?;
+    let stem = final_path.file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| path_error(t!("path.no_file_name")))?;
+
+    // 保留扩展名，便于 ffmpeg 推断输出格式
+    let temp_name = match final_path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!(".{}{}{}", stem, TEMP_MARKER, ext),
+        None => format!(".{}{}part", stem, TEMP_MARKER),
+    };
+
+    Ok(parent.join(temp_name))
+}
+
+/// 从临时文件名还原最终文件名，不是临时文件时返回 `None`
+pub fn final_name_from_temp(file_name: &str) -> Option<String> {
+    let rest = file_name.strip_prefix('.')?;
+    let pos = rest.find(TEMP_MARKER)?;
+    let stem = &rest[..pos];
+    let ext = &rest[pos + TEMP_MARKER.len()..];
+    if stem.is_empty() || ext.is_empty() {
+        return None;
+    }
+    Some(format!("{}.{}", stem, ext))
+}
+
+/// 初始化临时文件日志，并清理上次运行中断后遗留的临时文件
+///
+/// 日志的每一行记录创建临时文件的进程号，只清理进程已退出的条目，
+/// 共用同一数据目录的其他进程（应用或命令行）正在写入的文件不受影响。返回清理的文件数量
+pub fn init_temp_journal(data_dir: &Path) -> Result<usize, AppError> {
+    fs::create_dir_all(data_dir)?;
+    let journal = data_dir.join(JOURNAL_FILE);
+    let _ = JOURNAL_PATH.set(journal.clone());
+
+    let _guard = JOURNAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let content = match fs::read_to_string(&journal) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut removed = 0;
+    let mut remaining = String::new();
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        // 格式不符的条目直接丢弃
+        let Some((owner, path)) = parse_journal_entry(line) else { continue };
+        if process_is_running(owner) {
+            remaining.push_str(line);
+            remaining.push('\n');
+            continue;
+        }
+        // 只删除符合临时文件命名的文件，避免误删
+        let path = Path::new(path);
+        let is_temp = path.file_name()
+            .and_then(|n| n.to_str())
+            .and_then(final_name_from_temp)
+            .is_some();
+        if is_temp && fs::remove_file(path).is_ok() {
+            removed += 1;
+        }
+    }
+
+    fs::write(&journal, remaining)?;
+    Ok(removed)
+}
+
+/// 日志条目 `进程号\t路径`
+fn parse_journal_entry(line: &str) -> Option<(u32, &str)> {
+    let (pid, path) = line.split_once('\t')?;
+    Some((pid.parse().ok()?, path))
+}
+
+fn journal_entry(temp_path: &Path) -> String {
+    format!("{}\t{}", std::process::id(), temp_path.display())
+}
+
+/// 进程是否仍在运行（当前进程的条目在本次运行中产生，不会出现在启动时的日志中）
+fn process_is_running(pid: u32) -> bool {
+    use sysinfo::{Pid, ProcessRefreshKind, System};
+
+    pid != std::process::id()
+        && System::new().refresh_process_specifics(Pid::from_u32(pid), ProcessRefreshKind::new())
+}
+
+fn journal_add(temp_path: &Path) {
+    let Some(journal) = JOURNAL_PATH.get() else { return };
+    let _guard = JOURNAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(journal) {
+        let _ = writeln!(file, "{}", journal_entry(temp_path));
+    }
+}
+
+fn journal_remove(temp_path: &Path) {
+    let Some(journal) = JOURNAL_PATH.get() else { return };
+    let _guard = JOURNAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Ok(content) = fs::read_to_string(journal) {
+        let entry = journal_entry(temp_path);
+        let remaining: String = content.lines()
+            .filter(|l| *l != entry)
+            .map(|l| format!("{}\n", l))
+            .collect();
+        let _ = fs::write(journal, remaining);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temp_name_roundtrip() {
+        let temp = temp_path_for(Path::new("/videos/clip_1_2.mp4")).unwrap();
+        assert_eq!(temp, Path::new("/videos/.clip_1_2.instant-cut-tmp.mp4"));
+
+        let name = temp.file_name().unwrap().to_str().unwrap();
+        assert_eq!(final_name_from_temp(name), Some("clip_1_2.mp4".to_string()));
+        assert_eq!(final_name_from_temp("clip_1_2.mp4"), None);
+        assert_eq!(final_name_from_temp(".hidden.mp4"), None);
+    }
+
+    #[test]
+    fn test_reservation_is_exclusive() {
+        let dir = std::env::temp_dir().join(format!("instant-cut-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let final_path = dir.join("clip_1.mp4");
+
+        let first = OutputReservation::reserve(&final_path).unwrap();
+        assert!(first.is_some());
+        // 第二次预留同一文件名应失败
+        assert!(OutputReservation::reserve(&final_path).unwrap().is_none());
+
+        // 丢弃未提交的预留会删除临时文件
+        let temp = first.as_ref().unwrap().temp_path().to_path_buf();
+        drop(first);
+        assert!(!temp.exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_commit_never_overwrites() {
+        let dir = std::env::temp_dir().join(format!("instant-cut-commit-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let final_path = dir.join("clip_1.mp4");
+
+        let reservation = OutputReservation::reserve(&final_path).unwrap().unwrap();
+        fs::write(reservation.temp_path(), b"new").unwrap();
+        // 预留之后由其他程序创建了同名文件
+        fs::write(&final_path, b"other").unwrap();
+        let err = reservation.commit().unwrap_err();
+        assert!(err.to_string().contains("clip_1.mp4"), "{}", err);
+        assert_eq!(fs::read(&final_path).unwrap(), b"other");
+
+        fs::remove_file(&final_path).unwrap();
+        let reservation = OutputReservation::reserve(&final_path).unwrap().unwrap();
+        let temp = reservation.temp_path().to_path_buf();
+        fs::write(&temp, b"new").unwrap();
+        assert_eq!(reservation.commit().unwrap(), final_path);
+        assert_eq!(fs::read(&final_path).unwrap(), b"new");
+        assert!(!temp.exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_journal_entries() {
+        assert_eq!(parse_journal_entry("123\t/v/.a.instant-cut-tmp.mp4"), Some((123, "/v/.a.instant-cut-tmp.mp4")));
+        assert_eq!(parse_journal_entry("/v/.a.instant-cut-tmp.mp4"), None);
+        assert_eq!(parse_journal_entry("abc\t/v/.a.instant-cut-tmp.mp4"), None);
+        assert!(!process_is_running(std::process::id()));
+    }
+}
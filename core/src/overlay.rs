@@ -0,0 +1,421 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::backend::{MediaBackend, ProcessBackend};
+use crate::error::{AppError, AppResult, validation_error};
+use crate::export::{audio_streams, reencode_export, AudioPlan, Encode, ExportRequest, VideoFilter};
+use crate::media::{get_stream_codecs_with, get_video_info_with, resolve_range_with};
+use crate::output::WorkDir;
+use crate::settings;
+use crate::subtitle::escape_filter_value;
+use crate::timestamp::{Rational, StreamClock, TimePoint};
+use crate::utils::validate_input_path;
+use crate::video::OverlayResult;
+use crate::t;
+
+/// 文字模板中的时间码占位符，渲染为逐帧递增的时间码
+const TIMECODE_PLACEHOLDER: &str = "{timecode}";
+
+/// 叠加位置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Position {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+    Center,
+}
+
+impl FromStr for Position {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "top-left" => Ok(Position::TopLeft),
+            "top-right" => Ok(Position::TopRight),
+            "bottom-left" => Ok(Position::BottomLeft),
+            "bottom-right" => Ok(Position::BottomRight),
+            "center" => Ok(Position::Center),
+            _ => Err(validation_error(t!("overlay.position_unknown", position = s))),
+        }
+    }
+}
+
+impl Position {
+    /// overlay / drawtext 的 x、y 表达式，参数为画面和叠加内容宽、高在滤镜中的变量名
+    fn expressions(&self, margin: u32, (main_w, main_h): (&str, &str), (item_w, item_h): (&str, &str)) -> (String, String) {
+        let left = margin.to_string();
+        let right = format!("{}-{}-{}", main_w, item_w, margin);
+        let top = margin.to_string();
+        let bottom = format!("{}-{}-{}", main_h, item_h, margin);
+        match self {
+            Position::TopLeft => (left, top),
+            Position::TopRight => (right, top),
+            Position::BottomLeft => (left, bottom),
+            Position::BottomRight => (right, bottom),
+            Position::Center => (format!("({}-{})/2", main_w, item_w), format!("({}-{})/2", main_h, item_h)),
+        }
+    }
+}
+
+fn default_margin() -> u32 {
+    24
+}
+
+fn default_opacity() -> f64 {
+    1.0
+}
+
+fn default_font_size() -> u32 {
+    32
+}
+
+fn default_font_color() -> String {
+    "white".to_string()
+}
+
+/// 图片水印（PNG 等，透明通道会保留）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageWatermark {
+    pub path: String,                   // 图片路径
+    #[serde(default)]
+    pub position: Position,
+    #[serde(default = "default_margin")]
+    pub margin: u32,                    // 与画面边缘的距离（像素）
+    #[serde(default)]
+    pub scale: Option<f64>,             // 水印宽度占画面宽度的比例 (0-1]，为空时保持原尺寸
+    #[serde(default = "default_opacity")]
+    pub opacity: f64,                   // 不透明度 [0-1]
+}
+
+/// 文字叠加，内容支持 `{filename}`、`{name}`、`{notes}` 和 `{timecode}`（只能位于末尾）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextOverlay {
+    pub text: String,                   // 文字模板
+    #[serde(default)]
+    pub position: Position,
+    #[serde(default = "default_margin")]
+    pub margin: u32,
+    #[serde(default = "default_font_size")]
+    pub font_size: u32,
+    #[serde(default = "default_font_color")]
+    pub font_color: String,             // 例如 white、#ffcc00、white@0.8
+    #[serde(default)]
+    pub font_file: Option<String>,      // 字体文件，为空时由 fontconfig 选择
+    #[serde(default)]
+    pub background: Option<String>,     // 文字背景框的颜色，为空时不加背景
+}
+
+/// 导出时的叠加内容，水印在下、文字在上
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlayOptions {
+    pub watermark: Option<ImageWatermark>,
+    pub text: Option<TextOverlay>,
+}
+
+/// 保存在设置中的叠加预设
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OverlayPreset {
+    pub name: String,
+    #[serde(flatten)]
+    pub options: OverlayOptions,
+}
+
+/// 按名称查找设置中保存的预设
+pub fn find_preset(name: &str) -> AppResult<OverlayOptions> {
+    settings::current().overlay_presets
+        .into_iter()
+        .find(|p| p.name == name)
+        .map(|p| p.options)
+        .ok_or_else(|| validation_error(t!("overlay.preset_not_found", name = name)))
+}
+
+/// 展开文字模板，返回文字和是否以时间码结尾
+fn expand_template(template: &str, input_path: &str, notes: Option<&str>) -> AppResult<(String, bool)> {
+    let path = Path::new(input_path);
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let stem = path.file_stem().and_then(|n| n.to_str()).unwrap_or("");
+
+    // drawtext 的时间码总是显示在文字之后
+    let (template, timecode) = match template.find(TIMECODE_PLACEHOLDER) {
+        Some(pos) if pos + TIMECODE_PLACEHOLDER.len() == template.len() => (&template[..pos], true),
+        Some(_) => return Err(validation_error(t!("overlay.timecode_not_last"))),
+        None => (template, false),
+    };
+    let text = template
+        .replace("{filename}", file_name)
+        .replace("{name}", stem)
+        .replace("{notes}", notes.unwrap_or(""));
+    Ok((text, timecode))
+}
+
+/// 校验选项并生成 -filter_complex 滤镜图，输入 0 为视频，输入 1 为水印图片（如有）
+///
+/// `text_file` 为展开后的文字所在文件，`timecode` 为时间码起点和帧率
+fn filter_graph(
+    options: &OverlayOptions,
+    video_width: u32,
+    text_file: Option<&Path>,
+    timecode: Option<(String, String)>
+) -> AppResult<String> {
+    let text = options.text.as_ref().zip(text_file);
+    if options.watermark.is_none() && text.is_none() {
+        return Err(validation_error(t!("overlay.empty")));
+    }
+    let mut chains = Vec::new();
+
+    if let Some(watermark) = &options.watermark {
+        if !(0.0..=1.0).contains(&watermark.opacity) {
+            return Err(validation_error(t!("overlay.invalid_opacity")));
+        }
+        let mut filters = vec!["format=rgba".to_string()];
+        if let Some(scale) = watermark.scale {
+            if !(scale > 0.0 && scale <= 1.0) {
+                return Err(validation_error(t!("overlay.invalid_scale")));
+            }
+            // 宽度取偶数，高度按比例
+            let width = ((video_width as f64 * scale / 2.0).round() as u32).max(1) * 2;
+            filters.push(format!("scale={}:-2", width));
+        }
+        if watermark.opacity < 1.0 {
+            filters.push(format!("colorchannelmixer=aa={}", watermark.opacity));
+        }
+        let (x, y) = watermark.position.expressions(watermark.margin, ("main_w", "main_h"), ("overlay_w", "overlay_h"));
+        chains.push(format!("[1:v]{}[wm]", filters.join(",")));
+        // 最后一步输出到 [vout]
+        let output = if text.is_some() { "marked" } else { "vout" };
+        chains.push(format!("[0:v][wm]overlay=x={}:y={}[{}]", x, y, output));
+    }
+
+    if let Some((text, text_file)) = text {
+        if text.font_size == 0 {
+            return Err(validation_error(t!("overlay.invalid_font_size")));
+        }
+        let (x, y) = text.position.expressions(text.margin, ("w", "h"), ("text_w", "text_h"));
+        // 文字从文件读取并关闭 % 展开，模板内容无需转义
+        let mut args = vec![
+            format!("textfile={}", escape_filter_value(&text_file.to_string_lossy())),
+            "expansion=none".to_string(),
+            format!("fontsize={}", text.font_size),
+            format!("fontcolor={}", escape_filter_value(&text.font_color)),
+            format!("x={}", x),
+            format!("y={}", y),
+        ];
+        if let Some(font_file) = &text.font_file {
+            args.push(format!("fontfile={}", escape_filter_value(font_file)));
+        }
+        if let Some(background) = &text.background {
+            args.push("box=1".to_string());
+            args.push(format!("boxcolor={}", escape_filter_value(background)));
+            args.push(format!("boxborderw={}", (text.font_size / 4).max(1)));
+        }
+        if let Some((start, rate)) = timecode {
+            args.push(format!("timecode={}", escape_filter_value(&start)));
+            args.push(format!("rate={}", rate));
+        }
+        let input = if options.watermark.is_some() { "marked" } else { "0:v" };
+        chains.push(format!("[{}]drawtext={}[vout]", input, args.join(":")));
+    }
+    Ok(chains.join(";"))
+}
+
+/// 输出起点的时间码和帧率：源带有时间码时从源时间码计，否则从 00:00:00:00 计
+fn start_timecode(clock: &StreamClock, start: Rational) -> AppResult<(String, String)> {
+    let rate = clock.frame_rate
+        .ok_or_else(|| validation_error(t!("time.frame_rate_unknown")))?;
+    let timecode = clock.timecode_at_frame(clock.nearest_frame(start)?)?;
+    Ok((timecode.to_string(), rate.to_string()))
+}
+
+/// 导出时间范围并叠加水印和文字，输出到下一个版本文件
+///
+/// 视频重新编码，音频能放入输出容器时流复制
+#[allow(clippy::too_many_arguments)]
+pub fn export_overlay(
+    input_path: &str,
+    start: &TimePoint,
+    end: &TimePoint,
+    options: &OverlayOptions,
+    notes: Option<&str>,
+    output_dir: Option<&str>,
+    container: Option<&str>
+) -> AppResult<OverlayResult> {
+    export_overlay_with(&ProcessBackend, input_path, start, end, options, notes, output_dir, container)
+}
+
+/// 使用指定后端导出带叠加内容的视频，参数含义同 [`export_overlay`]
+#[allow(clippy::too_many_arguments)]
+pub fn export_overlay_with(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    start: &TimePoint,
+    end: &TimePoint,
+    options: &OverlayOptions,
+    notes: Option<&str>,
+    output_dir: Option<&str>,
+    container: Option<&str>
+) -> AppResult<OverlayResult> {
+    let request = ExportRequest::new(input_path, notes, output_dir, container)?;
+    let watermark_path = options.watermark.as_ref()
+        .map(|w| validate_input_path(&w.path))
+        .transpose()?;
+
+    let caps = backend.capabilities()?;
+    if options.text.is_some() && !caps.features.drawtext {
+        return Err(validation_error(t!("overlay.drawtext_unavailable")));
+    }
+
+    let info = get_video_info_with(backend, input_path)?;
+    let audio = audio_streams(get_stream_codecs_with(backend, input_path)?);
+    let range = resolve_range_with(backend, input_path, start, end)?;
+
+    // 文字写入临时文件，由 drawtext 读取
+    let work_dir = WorkDir::new("overlay")?;
+    let (text_file, timecode) = match &options.text {
+        Some(text) => {
+            let (contents, with_timecode) = expand_template(&text.text, input_path, notes)?;
+            let path = work_dir.path("text.txt");
+            fs::write(&path, contents)?;
+            let timecode = with_timecode
+                .then(|| start_timecode(&range.clock, range.start))
+                .transpose()?;
+            (Some(path), timecode)
+        }
+        None => (None, None),
+    };
+    let graph = filter_graph(options, info.display_size().0, text_file.as_deref(), timecode)?;
+
+    let span = Some((range.start, range.end));
+    let target = request.reserve(request.estimate_size(span, range.total_duration)?)?;
+    let encode = Encode {
+        input_path,
+        span,
+        extra_inputs: watermark_path.iter().map(|p| p.to_string_lossy().to_string()).collect(),
+        video: VideoFilter::Graph(graph),
+        audio: AudioPlan::Keep,
+    };
+    let exported = reencode_export(backend, &caps, target, &encode, &audio, range.duration().to_f64())?;
+
+    let message = exported.message("overlay.success", "overlay.verification_failed");
+    Ok(OverlayResult { output_path: exported.output_path, message, verification: exported.verification })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{CommandOutput, MockBackend};
+    use crate::binaries::Tool;
+    use crate::capabilities::{FeatureSupport, FFmpegCapabilities};
+
+    fn watermark() -> ImageWatermark {
+        ImageWatermark { path: "logo.png".into(), position: Position::TopRight, margin: 16, scale: Some(0.1), opacity: 0.5 }
+    }
+
+    fn text(template: &str) -> TextOverlay {
+        TextOverlay {
+            text: template.into(), position: Position::BottomLeft, margin: 24, font_size: 32,
+            font_color: "white".into(), font_file: None, background: Some("black@0.5".into()),
+        }
+    }
+
+    #[test]
+    fn test_templates_and_presets() {
+        let (expanded, timecode) = expand_template("{name} / {filename} {notes} TC ", "/v/clip.mp4", Some("review")).unwrap();
+        assert_eq!((expanded.as_str(), timecode), ("clip / clip.mp4 review TC ", false));
+        assert!(expand_template("TC {timecode}", "clip.mp4", None).unwrap().1);
+        assert!(expand_template("{timecode} later", "clip.mp4", None).is_err());
+
+        // 预设以扁平结构保存，缺省字段使用默认值
+        let preset: OverlayPreset = serde_json::from_value(serde_json::json!({
+            "name": "team",
+            "watermark": { "path": "logo.png" },
+        })).unwrap();
+        let watermark = preset.options.watermark.unwrap();
+        assert_eq!((watermark.position, watermark.margin, watermark.opacity), (Position::BottomRight, 24, 1.0));
+        assert_eq!("Top_Right".parse::<Position>().unwrap(), Position::TopRight);
+    }
+
+    #[test]
+    fn test_filter_graph() {
+        let options = OverlayOptions { watermark: Some(watermark()), text: Some(text("x")) };
+        let graph = filter_graph(&options, 1920, Some(Path::new("/tmp/text.txt")), Some(("10:00:00:00".into(), "25/1".into()))).unwrap();
+        assert_eq!(graph, "[1:v]format=rgba,scale=192:-2,colorchannelmixer=aa=0.5[wm];\
+[0:v][wm]overlay=x=main_w-overlay_w-16:y=16[marked];\
+[marked]drawtext=textfile=/tmp/text.txt:expansion=none:fontsize=32:fontcolor=white:x=24:y=h-text_h-24:\
+box=1:boxcolor=black@0.5:boxborderw=8:timecode=10\\\\:00\\\\:00\\\\:00:rate=25/1[vout]");
+
+        let only_logo = OverlayOptions { watermark: Some(ImageWatermark { scale: None, opacity: 1.0, ..watermark() }), text: None };
+        assert_eq!(filter_graph(&only_logo, 1920, None, None).unwrap(),
+            "[1:v]format=rgba[wm];[0:v][wm]overlay=x=main_w-overlay_w-16:y=16[vout]");
+
+        let invalid = OverlayOptions { watermark: Some(ImageWatermark { opacity: 1.5, ..watermark() }), text: None };
+        assert!(filter_graph(&invalid, 1920, None, None).is_err());
+        assert!(filter_graph(&OverlayOptions::default(), 1920, None, None).is_err());
+    }
+
+    #[test]
+    fn test_export_overlay_with_mock() {
+        let dir = std::env::temp_dir().join(format!("instant-cut-overlay-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("clip.mp4");
+        fs::write(&input, vec![0u8; 1024]).unwrap();
+        let logo = dir.join("logo.png");
+        fs::write(&logo, b"png").unwrap();
+
+        let probe = CommandOutput::ok(serde_json::json!({
+            "streams": [
+                { "index": 0, "codec_type": "video", "codec_name": "h264", "width": 1280, "height": 720,
+                  "r_frame_rate": "25/1", "time_base": "1/12800" },
+                { "index": 1, "codec_type": "audio", "codec_name": "aac" },
+            ],
+            "format": { "duration": "60.0", "start_time": "0.000000" },
+        }).to_string());
+        let mock = MockBackend::new();
+        for _ in 0..4 {
+            mock.respond(Tool::FFprobe, probe.clone());
+        }
+        mock.set_default(Tool::FFprobe, CommandOutput::ok(serde_json::json!({
+            "streams": [
+                { "index": 0, "codec_type": "video", "codec_name": "h264" },
+                { "index": 1, "codec_type": "audio", "codec_name": "aac" },
+            ],
+            "format": { "duration": "5.0" },
+        }).to_string()));
+        mock.set_output_contents(b"overlaid");
+        mock.set_capabilities(FFmpegCapabilities {
+            encoders: vec!["libx264".into()],
+            ..Default::default()
+        });
+
+        let options = OverlayOptions {
+            watermark: Some(ImageWatermark { path: logo.to_string_lossy().to_string(), ..watermark() }),
+            text: Some(text("{name}")),
+        };
+        let (start, end) = (TimePoint::seconds(10.0), TimePoint::seconds(15.0));
+
+        // 没有 drawtext 时不能叠加文字
+        let err = export_overlay_with(&mock, input.to_str().unwrap(), &start, &end, &options, None, None, None);
+        assert!(matches!(err, Err(AppError::ValidationError(_))));
+
+        mock.set_capabilities(FFmpegCapabilities {
+            encoders: vec!["libx264".into()],
+            features: FeatureSupport { drawtext: true, ..Default::default() },
+            ..Default::default()
+        });
+        let result = export_overlay_with(&mock, input.to_str().unwrap(), &start, &end, &options, None, None, None).unwrap();
+        assert!(result.verification.passed, "{:?}", result.verification);
+
+        let args = &mock.calls_to(Tool::FFmpeg)[0];
+        assert_eq!(args.iter().filter(|a| *a == "-i").count(), 2);
+        let graph = &args[args.iter().position(|a| a == "-filter_complex").unwrap() + 1];
+        assert!(graph.contains("scale=128:-2"), "{}", graph);
+        assert!(args.windows(2).any(|w| w == ["-c:a", "copy"]));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,126 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use crate::error::AppResult;
+use crate::i18n::{self, Locale};
+use crate::binaries;
+use crate::overlay::OverlayPreset;
+use crate::t;
+
+/// 设置文件名
+const SETTINGS_FILE: &str = "settings.json";
+
+/// 设置文件路径，在启动时通过 `init_settings` 设置
+static SETTINGS_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// 当前生效的设置
+static SETTINGS: RwLock<Option<Settings>> = RwLock::new(None);
+
+/// 用户设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub locale: Option<String>,         // 界面语言 (例如 "en"、"zh-CN")，为空时跟随系统
+    pub ffmpeg_path: Option<String>,    // ffmpeg 可执行文件路径，为空时自动查找
+    pub ffprobe_path: Option<String>,   // ffprobe 可执行文件路径，为空时自动查找
+    #[serde(deserialize_with = "lenient_presets")]
+    pub overlay_presets: Vec<OverlayPreset>,    // 导出时可选用的水印 / 文字叠加预设
+}
+
+/// 逐个解析叠加预设，跳过无效的预设，不让一个预设导致整个设置文件失效
+fn lenient_presets<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<OverlayPreset>, D::Error> {
+    let entries = match Value::deserialize(deserializer)? {
+        Value::Array(entries) => entries,
+        Value::Null => Vec::new(),
+        other => vec![other],
+    };
+    Ok(entries.into_iter()
+        .enumerate()
+        .filter_map(|(i, entry)| match serde_json::from_value(entry) {
+            Ok(preset) => Some(preset),
+            Err(e) => {
+                eprintln!("{}", t!("settings.preset_skipped", index = i + 1, error = e));
+                None
+            }
+        })
+        .collect())
+}
+
+impl Settings {
+    /// 实际使用的语言
+    pub fn effective_locale(&self) -> Locale {
+        self.locale.as_deref()
+            .and_then(Locale::parse)
+            .unwrap_or_else(Locale::system)
+    }
+}
+
+/// 从数据目录加载设置并应用，文件不存在或损坏时使用默认设置
+pub fn init_settings(data_dir: &Path) -> Settings {
+    let path = data_dir.join(SETTINGS_FILE);
+    let _ = SETTINGS_PATH.set(path.clone());
+
+    let settings: Settings = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content)
+            .map_err(|e| eprintln!("{}", t!("settings.invalid", path = path.display(), error = e)))
+            .ok())
+        .unwrap_or_default();
+
+    apply(settings.clone());
+    settings
+}
+
+/// 获取当前设置
+pub fn current() -> Settings {
+    SETTINGS.read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
+/// 保存并应用新设置
+pub fn update(settings: Settings) -> AppResult<()> {
+    if let Some(path) = SETTINGS_PATH.get() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&settings)?)?;
+    }
+
+    apply(settings);
+    Ok(())
+}
+
+fn apply(settings: Settings) {
+    i18n::set_locale(settings.effective_locale());
+    *SETTINGS.write().unwrap_or_else(|e| e.into_inner()) = Some(settings);
+    // 可执行文件路径可能已变更
+    binaries::invalidate();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_presets_are_skipped() {
+        let settings: Settings = serde_json::from_str(r#"{
+            "locale": "en",
+            "overlay_presets": [
+                { "name": "logo", "watermark": { "path": "/logo.png", "position": "top-right" } },
+                { "name": "broken", "watermark": { "position": "middle" } },
+                { "watermark": { "path": "/logo.png" } },
+                { "name": "credit", "text": { "text": "{name}" } }
+            ]
+        }"#).unwrap();
+        assert_eq!(settings.locale.as_deref(), Some("en"));
+        let names: Vec<&str> = settings.overlay_presets.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["logo", "credit"]);
+
+        let settings: Settings = serde_json::from_str(r#"{ "overlay_presets": null }"#).unwrap();
+        assert!(settings.overlay_presets.is_empty());
+    }
+}
@@ -0,0 +1,415 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::backend::{MediaBackend, ProcessBackend};
+use crate::error::{AppResult, path_error, validation_error};
+use crate::export::{audio_streams, reencode_export, AudioPlan, Encode, ExportRequest, VideoFilter};
+use crate::join::{concat_quote, full_encoder, run_ffmpeg, Encoder};
+use crate::media::{get_stream_codecs_with, get_video_info_with, resolve_range_with};
+use crate::output::WorkDir;
+use crate::timestamp::{Rational, TimePoint};
+use crate::verify::StreamCounts;
+use crate::video::SpeedResult;
+use crate::t;
+
+/// 允许的速度范围
+const MIN_SPEED: f64 = 0.1;
+const MAX_SPEED: f64 = 100.0;
+
+/// 单个 atempo 滤镜的速度范围，超出时串联多个
+const ATEMPO_MIN: f64 = 0.5;
+const ATEMPO_MAX: f64 = 2.0;
+
+/// 倒放时每段缓存的解码帧上限（字节），reverse 需要把整段读入内存
+const REVERSE_MEMORY_BUDGET: u64 = 512 * 1024 * 1024;
+
+/// 到达 reverse 的帧每像素最多占用的字节数：滤镜链可能输出 RGBA，10 位源也多于 4:2:0 的 1.5 字节
+const REVERSE_BYTES_PER_PIXEL: u64 = 4;
+
+/// 帧率未知时按 25 fps 计算分段长度
+const FALLBACK_FPS: i64 = 25;
+
+/// 变速和倒放选项
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeedOptions {
+    pub speed: Option<f64>,     // 播放速度倍数，默认 1；大于 1 为快放（延时），小于 1 为慢放
+    pub reverse: bool,          // 倒放
+    pub drop_audio: bool,       // 去掉音频，否则用 atempo 保持音调变速
+}
+
+impl SpeedOptions {
+    /// 校验选项，返回速度倍数
+    fn validated_speed(&self) -> AppResult<f64> {
+        let speed = self.speed.unwrap_or(1.0);
+        if !speed.is_finite() || !(MIN_SPEED..=MAX_SPEED).contains(&speed) {
+            return Err(validation_error(t!("speed.invalid", min = MIN_SPEED, max = MAX_SPEED)));
+        }
+        if speed == 1.0 && !self.reverse {
+            return Err(validation_error(t!("speed.no_change")));
+        }
+        Ok(speed)
+    }
+}
+
+/// 变速后的输出时长
+pub fn output_duration(duration: f64, speed: f64) -> f64 {
+    duration / speed
+}
+
+/// 去掉多余的小数位 (例如 1.500000 -> 1.5)
+fn format_factor(value: f64) -> String {
+    let formatted = format!("{:.6}", value);
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// 把速度拆分为 atempo 能接受的 0.5-2 倍的因子，依次串联
+fn atempo_chain(speed: f64) -> Vec<String> {
+    let mut filters = Vec::new();
+    let mut remaining = speed;
+    while remaining > ATEMPO_MAX {
+        filters.push(format!("atempo={}", format_factor(ATEMPO_MAX)));
+        remaining /= ATEMPO_MAX;
+    }
+    while remaining < ATEMPO_MIN {
+        filters.push(format!("atempo={}", format_factor(ATEMPO_MIN)));
+        remaining /= ATEMPO_MIN;
+    }
+    if (remaining - 1.0).abs() > 1e-6 {
+        filters.push(format!("atempo={}", format_factor(remaining)));
+    }
+    filters
+}
+
+/// 视频和音频滤镜链；音频在分段时倒放，不在音频滤镜链中
+fn filter_chains(speed: f64, reverse: bool) -> (String, String) {
+    let mut video = Vec::new();
+    let mut audio = Vec::new();
+    if reverse {
+        video.push("reverse".to_string());
+    }
+    video.push(format!("setpts=(PTS-STARTPTS)/{}", format_factor(speed)));
+    audio.push("asetpts=PTS-STARTPTS".to_string());
+    audio.extend(atempo_chain(speed));
+    (video.join(","), audio.join(","))
+}
+
+/// 倒放时每段的帧数：按解码后的帧大小和内存预算计算
+fn reverse_chunk_frames(width: u32, height: u32) -> i64 {
+    let frame_bytes = width.max(1) as u64 * height.max(1) as u64 * REVERSE_BYTES_PER_PIXEL;
+    (REVERSE_MEMORY_BUDGET / frame_bytes).max(1) as i64
+}
+
+/// 把范围按帧对齐地分为若干段，按源中的先后顺序返回
+fn split_range(start: Rational, end: Rational, chunk: Rational) -> Vec<(Rational, Rational)> {
+    let mut chunks = Vec::new();
+    let mut position = start;
+    while position < end {
+        let next = (position + chunk).min(end);
+        chunks.push((position, next));
+        position = next;
+    }
+    chunks
+}
+
+/// 对时间范围变速和 / 或倒放，输出到下一个版本文件
+///
+/// 视频用 setpts 变速，音频用串联的 atempo 变速（或去掉音频），总是重新编码。
+/// 倒放时按内存预算分段：每段各自倒放后，按相反的顺序拼接。
+/// 音频随视频分段倒放为无损 PCM，拼接后统一编码，段间不会留下编码器延迟造成的空隙
+#[allow(clippy::too_many_arguments)]
+pub fn export_speed(
+    input_path: &str,
+    start: &TimePoint,
+    end: &TimePoint,
+    options: &SpeedOptions,
+    notes: Option<&str>,
+    output_dir: Option<&str>,
+    container: Option<&str>
+) -> AppResult<SpeedResult> {
+    export_speed_with(&ProcessBackend, input_path, start, end, options, notes, output_dir, container)
+}
+
+/// 使用指定后端变速和倒放，参数含义同 [`export_speed`]
+#[allow(clippy::too_many_arguments)]
+pub fn export_speed_with(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    start: &TimePoint,
+    end: &TimePoint,
+    options: &SpeedOptions,
+    notes: Option<&str>,
+    output_dir: Option<&str>,
+    container: Option<&str>
+) -> AppResult<SpeedResult> {
+    // 验证输入路径和输出目录
+    let request = ExportRequest::new(input_path, notes, output_dir, container)?;
+    let speed = options.validated_speed()?;
+
+    let info = get_video_info_with(backend, input_path)?;
+    let audio = audio_streams(get_stream_codecs_with(backend, input_path)?);
+    let has_audio = !audio.is_empty() && !options.drop_audio;
+    let range = resolve_range_with(backend, input_path, start, end)?;
+    let span = Some((range.start, range.end));
+    let expected_duration = output_duration(range.duration().to_f64(), speed);
+
+    // 预留输出文件；按输出时长估算大小，倒放时中间文件需要同样的空间
+    let source_size = request.estimate_size(span, range.total_duration)?;
+    let mut estimated_size = (source_size as f64 / speed) as u64;
+    if options.reverse {
+        estimated_size *= 2;
+    }
+    let target = request.reserve(estimated_size)?;
+    let caps = backend.capabilities()?;
+    let (video_filter, audio_filter) = filter_chains(speed, options.reverse);
+
+    let (exported, chunks) = if options.reverse {
+        // 每段帧数对齐到源帧率，避免段间重复或丢帧
+        let encoder = full_encoder(&caps, &target.ext)?;
+        let fps = range.clock.frame_rate.unwrap_or(Rational::from_integer(FALLBACK_FPS));
+        let (width, height) = info.display_size();
+        let chunk = Rational::from_integer(reverse_chunk_frames(width, height)) / fps;
+        let chunks = split_range(range.start, range.end, chunk);
+
+        let work = WorkDir::new("reverse")?;
+        let mut video_list = String::new();
+        let mut audio_list = String::new();
+        for (i, (chunk_start, chunk_end)) in chunks.iter().enumerate().rev() {
+            // Matroska 能容纳所有输出编码，拼接后再流复制到目标容器
+            let path = work.path(&format!("chunk_{}.mkv", i));
+            let path_str = path.to_str().ok_or_else(|| path_error(t!("path.not_utf8")))?;
+            let encode = Encode {
+                input_path,
+                span: Some((*chunk_start, *chunk_end)),
+                extra_inputs: Vec::new(),
+                video: VideoFilter::Chain(video_filter.clone()),
+                audio: AudioPlan::Drop,
+            };
+            let mut args = encode.args(&encoder, false, &["-f", "matroska"], path_str);
+            let _ = writeln!(video_list, "file {}", concat_quote(&path));
+
+            // 同一次解码的第二个输出：所有音频流倒放为 PCM
+            if has_audio {
+                let audio_path = work.path(&format!("audio_{}.mkv", i));
+                let audio_str = audio_path.to_str().ok_or_else(|| path_error(t!("path.not_utf8")))?;
+                args.extend(reversed_audio_args(audio_str));
+                let _ = writeln!(audio_list, "file {}", concat_quote(&audio_path));
+            }
+            run_ffmpeg(backend, args)?;
+        }
+
+        let video_list = work.write_list(&video_list)?;
+        let audio_list_path = work.path("audio_list.txt");
+        if has_audio {
+            fs::write(&audio_list_path, &audio_list)?;
+        }
+        let mux_audio = has_audio.then_some((audio_list_path.as_path(), audio_filter.as_str()));
+        run_ffmpeg(backend, mux_args(&video_list, mux_audio, &encoder, target.output()?))?;
+
+        // 倒放和正放保留同样的音频流
+        let audio_count = if has_audio { audio.len() as u32 } else { 0 };
+        let expected_streams = StreamCounts { video: 1, audio: audio_count, subtitle: 0 };
+        (target.finish(backend, expected_duration, expected_streams, true)?, chunks.len() as u32)
+    } else {
+        let encode = Encode {
+            input_path,
+            span,
+            extra_inputs: Vec::new(),
+            video: VideoFilter::Chain(video_filter),
+            audio: if has_audio { AudioPlan::Filter(audio_filter) } else { AudioPlan::Drop },
+        };
+        (reencode_export(backend, &caps, target, &encode, &audio, expected_duration)?, 1)
+    };
+
+    let message = exported.message("speed.success", "speed.verification_failed");
+    Ok(SpeedResult {
+        output_path: exported.output_path,
+        message,
+        verification: exported.verification,
+        speed,
+        reversed: options.reverse,
+        expected_duration,
+        chunks,
+    })
+}
+
+/// 倒放一段的所有音频流，作为同一条命令的第二个输出写入无损的 PCM 中间文件
+fn reversed_audio_args(output: &str) -> Vec<String> {
+    [
+        "-map", "0:a",
+        "-af", "areverse",
+        "-c:a", "pcm_f32le",
+        "-vn", "-sn",
+        "-f", "matroska", output,
+    ].iter().map(|a| a.to_string()).collect()
+}
+
+/// 拼接倒放后的视频段并流复制；音频段拼接后经 `audio` 中的滤镜统一编码
+fn mux_args(list: &Path, audio: Option<(&Path, &str)>, encoder: &Encoder, output: &str) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        "-y".into(),
+        "-f".into(), "concat".into(),
+        "-safe".into(), "0".into(),
+        "-i".into(), list.to_string_lossy().to_string(),
+    ];
+    if let Some((audio_list, _)) = audio {
+        args.extend([
+            "-f".into(), "concat".into(),
+            "-safe".into(), "0".into(),
+            "-i".into(), audio_list.to_string_lossy().to_string(),
+        ]);
+    }
+    args.extend(["-map".into(), "0:v:0".into(), "-c:v".into(), "copy".into()]);
+    if let Some((_, filter)) = audio {
+        args.extend(["-map".into(), "1:a".into(), "-af".into(), filter.into()]);
+        args.extend(encoder.audio.iter().cloned());
+    }
+    args.extend([
+        "-avoid_negative_ts".into(), "1".into(),
+        output.into(),
+    ]);
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use crate::backend::{CommandOutput, MockBackend};
+    use crate::binaries::Tool;
+    use crate::capabilities::FFmpegCapabilities;
+
+    #[test]
+    fn test_atempo_chain() {
+        assert_eq!(atempo_chain(1.5), vec!["atempo=1.5"]);
+        assert_eq!(atempo_chain(8.0), vec!["atempo=2", "atempo=2", "atempo=2"]);
+        assert_eq!(atempo_chain(0.3), vec!["atempo=0.5", "atempo=0.6"]);
+        assert_eq!(atempo_chain(6.0), vec!["atempo=2", "atempo=2", "atempo=1.5"]);
+        assert!(atempo_chain(1.0).is_empty());
+
+        assert_eq!(filter_chains(0.5, true), (
+            "reverse,setpts=(PTS-STARTPTS)/0.5".to_string(),
+            "asetpts=PTS-STARTPTS,atempo=0.5".to_string(),
+        ));
+    }
+
+    #[test]
+    fn test_options_and_chunks() {
+        assert!(SpeedOptions::default().validated_speed().is_err());
+        assert!(SpeedOptions { speed: Some(0.05), ..Default::default() }.validated_speed().is_err());
+        assert_eq!(SpeedOptions { reverse: true, ..Default::default() }.validated_speed().unwrap(), 1.0);
+        assert_eq!(output_duration(10.0, 4.0), 2.5);
+
+        // 按每像素 4 字节，1080p 每帧约 8 MB，512 MB 约 64 帧
+        assert_eq!(reverse_chunk_frames(1920, 1080), 64);
+
+        let s = Rational::from_integer;
+        assert_eq!(split_range(s(10), s(25), s(6)), vec![(s(10), s(16)), (s(16), s(22)), (s(22), s(25))]);
+    }
+
+    fn mock_source(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("instant-cut-speed-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("clip.mp4");
+        fs::write(&input, vec![0u8; 1024]).unwrap();
+        (dir, input)
+    }
+
+    fn probe(duration: f64, width: u32) -> CommandOutput {
+        CommandOutput::ok(serde_json::json!({
+            "streams": [
+                { "index": 0, "codec_type": "video", "codec_name": "h264", "width": width, "height": width / 16 * 9,
+                  "r_frame_rate": "25/1", "time_base": "1/12800" },
+                { "index": 1, "codec_type": "audio", "codec_name": "aac" },
+            ],
+            "format": { "duration": duration.to_string(), "start_time": "0.000000" },
+        }).to_string())
+    }
+
+    fn mock(source: CommandOutput, output: CommandOutput) -> MockBackend {
+        let mock = MockBackend::new();
+        // 信息、流、帧率、时长，之后探测输出
+        for _ in 0..4 {
+            mock.respond(Tool::FFprobe, source.clone());
+        }
+        mock.set_default(Tool::FFprobe, output);
+        mock.set_output_contents(b"output");
+        mock.set_capabilities(FFmpegCapabilities { encoders: vec!["libx264".into()], ..Default::default() });
+        mock
+    }
+
+    #[test]
+    fn test_export_speed_timelapse() {
+        let (dir, input) = mock_source("timelapse");
+        let mock = mock(probe(60.0, 1920), probe(2.5, 1920));
+
+        let options = SpeedOptions { speed: Some(4.0), ..Default::default() };
+        let result = export_speed_with(
+            &mock, input.to_str().unwrap(), &TimePoint::seconds(10.0), &TimePoint::seconds(20.0), &options, None, None, None
+        ).unwrap();
+        assert_eq!((result.expected_duration, result.chunks), (2.5, 1));
+        assert!(result.verification.passed, "{:?}", result.verification);
+
+        let args = &mock.calls_to(Tool::FFmpeg)[0];
+        assert!(args.windows(2).any(|w| w == ["-vf", "setpts=(PTS-STARTPTS)/4"]));
+        assert!(args.windows(2).any(|w| w == ["-af", "asetpts=PTS-STARTPTS,atempo=2,atempo=2"]));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_export_speed_reverse_in_chunks() {
+        let (dir, input) = mock_source("reverse");
+        // 3840x2160 每帧按 4 字节约 33 MB，每段 16 帧（0.64 秒）
+        let mock = mock(probe(60.0, 3840), probe(4.0, 3840));
+
+        let options = SpeedOptions { reverse: true, drop_audio: true, ..Default::default() };
+        let result = export_speed_with(
+            &mock, input.to_str().unwrap(), &TimePoint::seconds(0.0), &TimePoint::seconds(4.0), &options, None, None, None
+        ).unwrap();
+        assert_eq!(result.chunks, 7);
+        assert_eq!(result.verification.expected_streams, StreamCounts { video: 1, audio: 0, subtitle: 0 });
+
+        let calls = mock.calls_to(Tool::FFmpeg);
+        assert_eq!(calls.len(), 8);
+        // 最后一段最先编码，各段之后拼接
+        assert_eq!(calls[0][1..5], ["-ss", "3.84", "-t", "0.16"]);
+        assert!(calls[0].contains(&"-an".to_string()));
+        assert!(calls[6].windows(2).any(|w| w == ["-vf", "reverse,setpts=(PTS-STARTPTS)/1"]));
+        assert_eq!(calls[7][..3], ["-y", "-f", "concat"]);
+        assert!(!calls[7].contains(&"1:a".to_string()));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_export_speed_reverse_audio_in_chunks() {
+        let (dir, input) = mock_source("reverse-audio");
+        let mock = mock(probe(60.0, 3840), probe(4.0, 3840));
+
+        let options = SpeedOptions { reverse: true, ..Default::default() };
+        let result = export_speed_with(
+            &mock, input.to_str().unwrap(), &TimePoint::seconds(0.0), &TimePoint::seconds(4.0), &options, None, None, None
+        ).unwrap();
+        assert_eq!(result.verification.expected_streams, StreamCounts { video: 1, audio: 1, subtitle: 0 });
+
+        // 每段在同一次解码中分别输出倒放的视频和 PCM 音频，最后各自拼接并合并
+        let calls = mock.calls_to(Tool::FFmpeg);
+        assert_eq!(calls.len(), 8);
+        for args in &calls[..7] {
+            assert!(args.contains(&"-an".to_string()));
+            assert!(args.windows(6).any(|w| w == ["-map", "0:a", "-af", "areverse", "-c:a", "pcm_f32le"]));
+        }
+        assert_eq!(calls[0][1..5], ["-ss", "3.84", "-t", "0.16"]);
+        let mux = &calls[7];
+        assert_eq!(mux.iter().filter(|a| *a == "concat").count(), 2);
+        assert!(mux.windows(8).any(|w| w == [
+            "-map", "0:v:0", "-c:v", "copy", "-map", "1:a", "-af", "asetpts=PTS-STARTPTS",
+        ]));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
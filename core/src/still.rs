@@ -0,0 +1,294 @@
+use serde::{Deserialize, Serialize};
+use crate::backend::{MediaBackend, ProcessBackend};
+use crate::error::{AppResult, path_error, validation_error};
+use crate::media::{check_disk_space_for_output, get_stream_clock_with, get_video_info_with, reserve_next_output};
+use crate::timestamp::{Rational, TimePoint};
+use crate::utils::{check_command_success, validate_input_path, validate_output_dir, validate_time_point};
+use crate::video::StillResult;
+use crate::t;
+
+/// 支持的静帧格式
+pub const STILL_FORMATS: &[&str] = &["png", "jpg", "webp"];
+
+/// 未指定格式时使用 PNG
+const DEFAULT_FORMAT: &str = "png";
+
+/// 估算输出大小时每像素的字节数（未压缩 RGB，作为上限）
+const BYTES_PER_PIXEL_ESTIMATE: u64 = 3;
+
+/// 静帧导出选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StillOptions {
+    pub format: Option<String>,     // png（默认）/ jpg / webp
+    pub width: Option<u32>,         // 输出宽度，只指定一边时按比例缩放
+    pub height: Option<u32>,        // 输出高度
+}
+
+/// 规范化静帧格式 (例如 ".JPEG" -> "jpg")，并确认其受支持
+pub fn normalize_still_format(format: &str) -> AppResult<String> {
+    let normalized = format.trim().trim_start_matches('.').to_ascii_lowercase();
+    let normalized = if normalized == "jpeg" { "jpg".to_string() } else { normalized };
+    if !STILL_FORMATS.contains(&normalized.as_str()) {
+        return Err(validation_error(t!("still.format_unsupported", format = format)));
+    }
+    Ok(normalized)
+}
+
+/// 导出静帧：在每个时间点各导出一帧，多个时间点只解码一次
+pub fn export_stills(
+    input_path: &str,
+    times: &[TimePoint],
+    options: &StillOptions,
+    notes: Option<&str>,
+    output_dir: Option<&str>
+) -> AppResult<StillResult> {
+    export_stills_with(&ProcessBackend, input_path, times, options, notes, output_dir)
+}
+
+/// 使用指定后端导出静帧，参数含义同 [`export_stills`]
+pub fn export_stills_with(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    times: &[TimePoint],
+    options: &StillOptions,
+    notes: Option<&str>,
+    output_dir: Option<&str>
+) -> AppResult<StillResult> {
+    // 验证输入路径和输出目录
+    validate_input_path(input_path)?;
+    let output_dir = output_dir
+        .map(validate_output_dir)
+        .transpose()?;
+
+    if times.is_empty() {
+        return Err(validation_error(t!("still.no_times")));
+    }
+    if options.width == Some(0) || options.height == Some(0) {
+        return Err(validation_error(t!("still.invalid_size")));
+    }
+    let format = normalize_still_format(options.format.as_deref().unwrap_or(DEFAULT_FORMAT))?;
+
+    // 换算并验证时间点
+    let info = get_video_info_with(backend, input_path)?;
+    let clock = get_stream_clock_with(backend, input_path)?;
+    let seconds = times.iter()
+        .map(|t| t.resolve(&clock))
+        .collect::<AppResult<Vec<Rational>>>()?;
+    for time in &seconds {
+        validate_time_point(time.to_f64(), info.duration)?;
+    }
+
+    // ffprobe 输出的帧时间经过四舍五入，提前半帧定位，确保选中从该时间开始的帧
+    let half_frame = clock.frame_rate
+        .and_then(|rate| rate.recip())
+        .map(|frame| frame / Rational::from_integer(2))
+        .unwrap_or(Rational::ZERO);
+    let seeks: Vec<Rational> = seconds.iter()
+        .map(|t| (*t - half_frame).max(Rational::ZERO))
+        .collect();
+
+    // 按时间点顺序预留版本化的输出文件
+    let reservations = seconds.iter()
+        .map(|_| reserve_next_output(input_path, notes, output_dir.as_deref(), Some(&format)))
+        .collect::<AppResult<Vec<_>>>()?;
+
+    // 检查磁盘空间
+    let (width, height) = target_size(info.width, info.height, options.width, options.height);
+    let estimated_size = width as u64 * height as u64 * BYTES_PER_PIXEL_ESTIMATE * seconds.len() as u64;
+    check_disk_space_for_output(reservations[0].final_path(), estimated_size)?;
+
+    // 执行 ffmpeg 导出
+    let outputs = reservations.iter()
+        .map(|r| r.temp_path().to_str().ok_or_else(|| path_error(t!("path.not_utf8"))))
+        .collect::<AppResult<Vec<&str>>>()?;
+    let args = still_args(input_path, &seeks, scale_filter(options).as_deref(), &format, &outputs);
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    let output = backend.ffmpeg(&args)?;
+
+    // 检查 ffmpeg 执行结果
+    check_command_success(&output, "ffmpeg")?;
+
+    // 检查临时文件并重命名为最终文件名
+    let output_paths = reservations.into_iter()
+        .map(|r| r.commit().map(|p| p.to_string_lossy().to_string()))
+        .collect::<AppResult<Vec<String>>>()?;
+
+    Ok(StillResult {
+        message: t!("still.success", count = output_paths.len(), path = output_paths[0]),
+        output_paths,
+    })
+}
+
+/// 只指定一边时按源比例计算另一边
+pub(crate) fn target_size(source_width: u32, source_height: u32, width: Option<u32>, height: Option<u32>) -> (u32, u32) {
+    let scale = |value: u32, from: u32, to: u32| (value as u64 * to as u64 / from.max(1) as u64) as u32;
+    match (width, height) {
+        (Some(w), Some(h)) => (w, h),
+        (Some(w), None) => (w, scale(source_height, source_width, w)),
+        (None, Some(h)) => (scale(source_width, source_height, h), h),
+        (None, None) => (source_width, source_height),
+    }
+}
+
+/// 缩放滤镜，未指定尺寸时为 `None`
+fn scale_filter(options: &StillOptions) -> Option<String> {
+    if options.width.is_none() && options.height.is_none() {
+        return None;
+    }
+    let side = |v: Option<u32>| v.map(|v| v.to_string()).unwrap_or_else(|| "-1".to_string());
+    Some(format!("scale={}:{}", side(options.width), side(options.height)))
+}
+
+/// 各格式的编码质量参数
+fn quality_args(format: &str) -> &'static [&'static str] {
+    match format {
+        "jpg" => &["-q:v", "2"],
+        "webp" => &["-quality", "90"],
+        _ => &[],
+    }
+}
+
+/// 构建 ffmpeg 参数
+///
+/// 单个时间点直接定位解码；多个时间点从最早的时间点开始解码一次，
+/// 用 split + select 为每个时间点输出第一帧
+fn still_args(input_path: &str, seeks: &[Rational], scale: Option<&str>, format: &str, outputs: &[&str]) -> Vec<String> {
+    let base = seeks.iter().min().copied().unwrap_or(Rational::ZERO);
+    let mut args: Vec<String> = vec![
+        "-y".into(),
+        "-ss".into(), base.to_ffmpeg_seconds(),
+        "-i".into(), input_path.into(),
+    ];
+
+    if seeks.len() == 1 {
+        args.extend(["-map".into(), "0:v:0".into()]);
+        if let Some(scale) = scale {
+            args.extend(["-vf".into(), scale.into()]);
+        }
+    } else {
+        let labels: String = (0..seeks.len()).map(|i| format!("[v{}]", i)).collect();
+        let mut graph = format!("[0:v:0]split={}{}", seeks.len(), labels);
+        for (i, seek) in seeks.iter().enumerate() {
+            let scale = scale.map(|s| format!(",{}", s)).unwrap_or_default();
+            graph.push_str(&format!(";[v{}]select=gte(t\\,{}){}[o{}]", i, (*seek - base).to_ffmpeg_seconds(), scale, i));
+        }
+        args.extend(["-filter_complex".into(), graph]);
+    }
+
+    for (i, output) in outputs.iter().enumerate() {
+        if seeks.len() > 1 {
+            args.extend(["-map".into(), format!("[o{}]", i)]);
+        }
+        args.extend(["-frames:v".into(), "1".into()]);
+        args.extend(quality_args(format).iter().map(|a| a.to_string()));
+        // 单文件输出，不使用 image2 的序号模式
+        args.extend(["-update".into(), "1".into(), output.to_string()]);
+    }
+
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use crate::backend::{CommandOutput, MockBackend};
+    use crate::binaries::Tool;
+
+    fn r(num: i64, den: i64) -> Rational {
+        Rational::new(num, den).unwrap()
+    }
+
+    #[test]
+    fn test_normalize_still_format() {
+        assert_eq!(normalize_still_format(".JPEG").unwrap(), "jpg");
+        assert_eq!(normalize_still_format("webp").unwrap(), "webp");
+        assert!(normalize_still_format("bmp").is_err());
+    }
+
+    #[test]
+    fn test_target_size() {
+        assert_eq!(target_size(1920, 1080, None, None), (1920, 1080));
+        assert_eq!(target_size(1920, 1080, Some(640), None), (640, 360));
+        assert_eq!(target_size(1920, 1080, None, Some(540)), (960, 540));
+        assert_eq!(target_size(1920, 1080, Some(100), Some(100)), (100, 100));
+    }
+
+    #[test]
+    fn test_single_still_args() {
+        let args = still_args("in.mp4", &[r(5, 2)], Some("scale=640:-1"), "jpg", &["out.jpg"]);
+        assert_eq!(args, [
+            "-y", "-ss", "2.5", "-i", "in.mp4", "-map", "0:v:0", "-vf", "scale=640:-1",
+            "-frames:v", "1", "-q:v", "2", "-update", "1", "out.jpg",
+        ]);
+    }
+
+    #[test]
+    fn test_batch_still_args() {
+        let args = still_args("in.mp4", &[r(4, 1), r(1, 1)], None, "png", &["a.png", "b.png"]);
+        assert_eq!(args, [
+            "-y", "-ss", "1", "-i", "in.mp4",
+            "-filter_complex", "[0:v:0]split=2[v0][v1];[v0]select=gte(t\\,3)[o0];[v1]select=gte(t\\,0)[o1]",
+            "-map", "[o0]", "-frames:v", "1", "-update", "1", "a.png",
+            "-map", "[o1]", "-frames:v", "1", "-update", "1", "b.png",
+        ]);
+    }
+
+    fn mock_source(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("instant-cut-still-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("clip.mp4");
+        fs::write(&input, b"video").unwrap();
+        (dir, input)
+    }
+
+    fn mock_backend() -> MockBackend {
+        let mock = MockBackend::new();
+        mock.set_default(Tool::FFprobe, CommandOutput::ok(serde_json::json!({
+            "streams": [{ "codec_type": "video", "codec_name": "h264", "width": 1920, "height": 1080,
+                          "r_frame_rate": "25/1", "time_base": "1/12800" }],
+            "format": { "duration": "10.0", "format_name": "mov,mp4,m4a,3gp,3g2,mj2" },
+        }).to_string()));
+        mock.set_output_contents(b"image");
+        mock
+    }
+
+    #[test]
+    fn test_export_stills_with_mock() {
+        let (dir, input) = mock_source("batch");
+        let mock = mock_backend();
+        let times = ["50f".parse().unwrap(), TimePoint::seconds(1.0)];
+
+        let result = export_stills_with(&mock, input.to_str().unwrap(), &times, &StillOptions::default(), None, None).unwrap();
+        assert_eq!(result.output_paths, vec![
+            dir.join("clip_1.png").to_string_lossy().to_string(),
+            dir.join("clip_2.png").to_string_lossy().to_string(),
+        ]);
+        assert!(dir.join("clip_2.png").exists());
+
+        // 从最早的时间点（提前半帧）开始解码一次
+        let args = &mock.calls_to(Tool::FFmpeg)[0];
+        assert_eq!(args[2], "0.98");
+        assert!(args[6].contains("select=gte(t\\,1)[o0]"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_export_stills_rejects_out_of_range() {
+        let (dir, input) = mock_source("range");
+        let mock = mock_backend();
+
+        let err = export_stills_with(
+            &mock, input.to_str().unwrap(), &[TimePoint::seconds(10.0)], &StillOptions::default(), None, None
+        ).unwrap_err();
+        assert!(matches!(err, crate::error::AppError::ValidationError(_)));
+        assert!(mock.calls_to(Tool::FFmpeg).is_empty());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,567 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::backend::{MediaBackend, ProcessBackend};
+use crate::error::{AppError, AppResult, path_error, validation_error};
+use crate::export::{audio_streams, reencode_export, AudioPlan, Encode, ExportRequest, VideoFilter};
+use crate::media::{
+    get_stream_codecs_with, get_subtitle_streams_with, reserve_next_output, resolve_optional_range_with,
+    resolve_range_with,
+};
+use crate::timestamp::{Rational, TimePoint};
+use crate::utils::{check_command_success, validate_input_path, validate_output_dir};
+use crate::video::{BurnResult, SkippedSubtitle, SubtitleResult, SubtitleStream};
+use crate::t;
+
+/// 无法转换为文本字幕的图形字幕编码
+const BITMAP_CODECS: &[&str] = &["hdmv_pgs_subtitle", "dvd_subtitle", "dvb_subtitle", "xsub"];
+
+/// 不限时间范围时使用的结束时间（秒）
+const MAX_SUBTITLE_SECONDS: i64 = 1_000_000_000;
+
+/// 文本字幕格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleFormat {
+    Srt,
+    Ass,
+}
+
+impl SubtitleFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "srt",
+            SubtitleFormat::Ass => "ass",
+        }
+    }
+
+    /// 按扩展名识别，不区分大小写
+    fn from_path(path: &Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+impl FromStr for SubtitleFormat {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().trim_start_matches('.').to_ascii_lowercase().as_str() {
+            "srt" => Ok(SubtitleFormat::Srt),
+            "ass" | "ssa" => Ok(SubtitleFormat::Ass),
+            _ => Err(validation_error(t!("subtitle.format_unsupported", format = s))),
+        }
+    }
+}
+
+/// 要烧录的字幕：内嵌字幕流的序号，或外部字幕文件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleSource {
+    Track(u32),
+    File(String),
+}
+
+/// 毫秒时间
+type Millis = i64;
+
+fn to_millis(seconds: Rational) -> Millis {
+    (seconds.to_f64() * 1000.0).round() as Millis
+}
+
+/// SRT 时间 `HH:MM:SS,mmm`
+fn parse_srt_time(s: &str) -> Option<Millis> {
+    let (hms, ms) = s.trim().split_once([',', '.'])?;
+    let parts: Vec<Millis> = hms.split(':').map(|p| p.trim().parse().ok()).collect::<Option<_>>()?;
+    let [h, m, sec] = parts[..] else { return None };
+    Some(((h * 60 + m) * 60 + sec) * 1000 + ms.trim().parse::<Millis>().ok()?)
+}
+
+fn format_srt_time(ms: Millis) -> String {
+    format!("{:02}:{:02}:{:02},{:03}", ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000)
+}
+
+/// ASS 时间 `H:MM:SS.cc`（百分之一秒）
+fn parse_ass_time(s: &str) -> Option<Millis> {
+    let (hms, cs) = s.trim().split_once('.')?;
+    let parts: Vec<Millis> = hms.split(':').map(|p| p.trim().parse().ok()).collect::<Option<_>>()?;
+    let [h, m, sec] = parts[..] else { return None };
+    Some(((h * 60 + m) * 60 + sec) * 1000 + cs.trim().parse::<Millis>().ok()? * 10)
+}
+
+fn format_ass_time(ms: Millis) -> String {
+    let cs = (ms + 5) / 10;
+    format!("{}:{:02}:{:02}.{:02}", cs / 360_000, cs / 6000 % 60, cs / 100 % 60, cs % 100)
+}
+
+/// 把字幕裁剪到 `[from, to)` 并平移到从 0 开始，与范围部分重叠的字幕截断到范围内
+fn clip(start: Millis, end: Millis, from: Millis, to: Millis) -> Option<(Millis, Millis)> {
+    let (start, end) = (start.max(from), end.min(to));
+    (start < end).then_some((start - from, end - from))
+}
+
+/// 裁剪并平移 SRT 字幕，重新编号，返回新内容和字幕条数
+fn trim_srt(contents: &str, from: Millis, to: Millis) -> (String, usize) {
+    let normalized = contents.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let mut output = String::new();
+    let mut count = 0;
+    for block in normalized.split("\n\n") {
+        let mut lines = block.lines().skip_while(|l| l.trim().is_empty()).peekable();
+        // 序号行可以省略
+        if lines.peek().is_some_and(|l| !l.contains("-->")) {
+            lines.next();
+        }
+        let Some(timing) = lines.next() else { continue };
+        let Some((start, rest)) = timing.split_once("-->") else { continue };
+        // 结束时间之后可能带有位置信息
+        let mut rest = rest.trim().splitn(2, char::is_whitespace);
+        let (Some(start), Some(end)) = (parse_srt_time(start), rest.next().and_then(parse_srt_time)) else { continue };
+        let Some((start, end)) = clip(start, end, from, to) else { continue };
+
+        count += 1;
+        output.push_str(&format!("{}\n{} --> {}", count, format_srt_time(start), format_srt_time(end)));
+        if let Some(position) = rest.next() {
+            output.push(' ');
+            output.push_str(position.trim());
+        }
+        output.push('\n');
+        for line in lines {
+            output.push_str(line);
+            output.push('\n');
+        }
+        output.push('\n');
+    }
+    (output, count)
+}
+
+/// 裁剪并平移 ASS 字幕中的 Dialogue 行，其余内容原样保留，返回新内容和字幕条数
+fn trim_ass(contents: &str, from: Millis, to: Millis) -> (String, usize) {
+    let normalized = contents.replace("\r\n", "\n");
+    let mut output = String::new();
+    let mut count = 0;
+    // 默认 Format: Layer, Start, End, ...
+    let (mut start_field, mut end_field, mut field_count) = (1, 2, 10);
+    let mut in_events = false;
+
+    for line in normalized.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_events = trimmed.eq_ignore_ascii_case("[events]");
+        } else if in_events && trimmed.starts_with("Format:") {
+            let fields: Vec<String> = trimmed["Format:".len()..].split(',').map(|f| f.trim().to_ascii_lowercase()).collect();
+            start_field = fields.iter().position(|f| f == "start").unwrap_or(1);
+            end_field = fields.iter().position(|f| f == "end").unwrap_or(2);
+            field_count = fields.len();
+        } else if in_events && trimmed.starts_with("Dialogue:") {
+            // 最后一个字段（文本）中可能包含逗号
+            let body = trimmed["Dialogue:".len()..].trim_start();
+            let mut fields: Vec<String> = body.splitn(field_count, ',').map(|f| f.to_string()).collect();
+            let times = fields.get(start_field).and_then(|s| parse_ass_time(s))
+                .zip(fields.get(end_field).and_then(|e| parse_ass_time(e)));
+            let Some((start, end)) = times.and_then(|(s, e)| clip(s, e, from, to)) else { continue };
+
+            fields[start_field] = format_ass_time(start);
+            fields[end_field] = format_ass_time(end);
+            output.push_str(&format!("Dialogue: {}\n", fields.join(",")));
+            count += 1;
+            continue;
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    (output, count)
+}
+
+/// 裁剪并平移字幕内容，返回新内容和字幕条数
+pub fn trim_subtitles(contents: &str, format: SubtitleFormat, from: Rational, to: Rational) -> (String, usize) {
+    let (from, to) = (to_millis(from), to_millis(to));
+    match format {
+        SubtitleFormat::Srt => trim_srt(contents, from, to),
+        SubtitleFormat::Ass => trim_ass(contents, from, to),
+    }
+}
+
+/// 查找源文件旁的外挂字幕：与源同名（`video.srt`）或带语言标签（`video.en.ass`）的 SRT / ASS 文件
+pub fn find_sidecar_subtitles(input_path: &Path) -> AppResult<Vec<PathBuf>> {
+    let (Some(dir), Some(stem)) = (input_path.parent(), input_path.file_stem().and_then(|s| s.to_str())) else {
+        return Ok(Vec::new());
+    };
+    let prefix = format!("{}.", stem);
+    let mut sidecars: Vec<PathBuf> = fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && SubtitleFormat::from_path(path).is_some())
+        .filter(|path| path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with(&prefix)))
+        .collect();
+    sidecars.sort();
+    Ok(sidecars)
+}
+
+/// 为剪辑输出写入裁剪后的外挂字幕（`video_1.en.srt`），`from` / `to` 为输出在源中对应的范围
+///
+/// 在输出已提交后调用，不会使剪辑失败：读写出错、不是 UTF-8 的字幕和已存在的目标文件都跳过，
+/// 不做有损转换也不覆盖，返回写入和跳过的文件
+pub(crate) fn write_trimmed_sidecars(
+    sidecars: &[PathBuf],
+    input_path: &Path,
+    output_path: &Path,
+    from: Rational,
+    to: Rational
+) -> (Vec<String>, Vec<SkippedSubtitle>) {
+    let input_stem = input_path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    let output_stem = output_path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+
+    let mut written = Vec::new();
+    let mut skipped = Vec::new();
+    for sidecar in sidecars {
+        let Some(format) = SubtitleFormat::from_path(sidecar) else { continue };
+        let name = sidecar.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        // 保留语言标签和扩展名
+        let suffix = &name[input_stem.len()..];
+        let target = output_path.with_file_name(format!("{}{}", output_stem, suffix));
+        let mut skip = |reason: String| skipped.push(SkippedSubtitle {
+            path: sidecar.to_string_lossy().to_string(),
+            reason,
+        });
+
+        let bytes = match fs::read(sidecar) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                skip(t!("subtitle.read_failed", path = sidecar.display(), error = e));
+                continue;
+            }
+        };
+        let Ok(text) = String::from_utf8(bytes) else {
+            skip(t!("subtitle.not_utf8", path = sidecar.display()));
+            continue;
+        };
+        let (contents, _) = trim_subtitles(&text, format, from, to);
+
+        // 只创建新文件，不覆盖输出旁已有的字幕
+        let mut file = match OpenOptions::new().write(true).create_new(true).open(&target) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                skip(t!("subtitle.sidecar_exists", path = target.display()));
+                continue;
+            }
+            Err(e) => {
+                skip(t!("subtitle.write_failed", path = target.display(), error = e));
+                continue;
+            }
+        };
+        if let Err(e) = file.write_all(contents.as_bytes()) {
+            let _ = fs::remove_file(&target);
+            skip(t!("subtitle.write_failed", path = target.display(), error = e));
+            continue;
+        }
+        written.push(target.to_string_lossy().to_string());
+    }
+    (written, skipped)
+}
+
+/// 按流序号选择字幕流，未指定时使用第一个字幕流
+fn select_subtitle_stream(streams: &[SubtitleStream], track: Option<u32>) -> AppResult<&SubtitleStream> {
+    match track {
+        Some(index) => streams.iter().find(|s| s.index == index)
+            .ok_or_else(|| validation_error(t!("subtitle.track_not_found", track = index))),
+        None => streams.first().ok_or_else(|| validation_error(t!("subtitle.no_stream"))),
+    }
+}
+
+/// 把内嵌字幕流提取为 SRT / ASS，可只提取时间范围内的部分（时间从范围开头算起）
+#[allow(clippy::too_many_arguments)]
+pub fn extract_subtitles(
+    input_path: &str,
+    start: Option<&TimePoint>,
+    end: Option<&TimePoint>,
+    track: Option<u32>,
+    format: SubtitleFormat,
+    notes: Option<&str>,
+    output_dir: Option<&str>
+) -> AppResult<SubtitleResult> {
+    extract_subtitles_with(&ProcessBackend, input_path, start, end, track, format, notes, output_dir)
+}
+
+/// 使用指定后端提取字幕，参数含义同 [`extract_subtitles`]
+#[allow(clippy::too_many_arguments)]
+pub fn extract_subtitles_with(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    start: Option<&TimePoint>,
+    end: Option<&TimePoint>,
+    track: Option<u32>,
+    format: SubtitleFormat,
+    notes: Option<&str>,
+    output_dir: Option<&str>
+) -> AppResult<SubtitleResult> {
+    validate_input_path(input_path)?;
+    let output_dir = output_dir
+        .map(validate_output_dir)
+        .transpose()?;
+
+    let streams = get_subtitle_streams_with(backend, input_path)?;
+    let stream = select_subtitle_stream(&streams, track)?;
+    if BITMAP_CODECS.contains(&stream.codec.as_str()) {
+        return Err(validation_error(t!("subtitle.bitmap", codec = stream.codec)));
+    }
+    let range = resolve_optional_range_with(backend, input_path, start, end)?;
+
+    let reservation = reserve_next_output(input_path, notes, output_dir.as_deref(), Some(format.extension()))?;
+    let output = reservation.temp_path().to_str()
+        .ok_or_else(|| path_error(t!("path.not_utf8")))?;
+
+    // 提取整条字幕流后在本地裁剪，避免输入定位对字幕时间的影响
+    let encoder = match format {
+        SubtitleFormat::Srt => "srt",
+        SubtitleFormat::Ass => "ass",
+    };
+    let map = format!("0:{}", stream.index);
+    let args = ["-y", "-i", input_path, "-map", &map, "-c:s", encoder, "-f", encoder, output];
+    let result = backend.ffmpeg(&args)?;
+    check_command_success(&result, "ffmpeg")?;
+
+    // 没有时间范围时也重写一遍，统一编号和换行
+    let contents = fs::read_to_string(reservation.temp_path())?;
+    let (contents, cues) = match &range {
+        Some(range) => trim_subtitles(&contents, format, range.start, range.end),
+        None => trim_subtitles(&contents, format, Rational::ZERO, Rational::from_integer(MAX_SUBTITLE_SECONDS)),
+    };
+    fs::write(reservation.temp_path(), contents)?;
+
+    let output_path = reservation.commit()?.to_string_lossy().to_string();
+    let message = t!("subtitle.extract_success", count = cues, path = output_path);
+    Ok(SubtitleResult { output_path, message, cues })
+}
+
+/// 转义滤镜选项值（例如文件名）：先按选项值转义，再按滤镜图转义
+pub(crate) fn escape_filter_value(path: &str) -> String {
+    let escape = |s: &str, special: &[char]| {
+        s.chars().fold(String::new(), |mut out, c| {
+            if special.contains(&c) {
+                out.push('\\');
+            }
+            out.push(c);
+            out
+        })
+    };
+    let value = escape(path, &['\\', '\'', ':']);
+    escape(&value, &['\\', '\'', '[', ']', ',', ';'])
+}
+
+/// 把字幕烧录到时间范围内的画面中，输出到下一个版本文件
+///
+/// 视频重新编码，音频能放入输出容器时流复制，输出不含字幕流
+#[allow(clippy::too_many_arguments)]
+pub fn burn_subtitles(
+    input_path: &str,
+    start: &TimePoint,
+    end: &TimePoint,
+    source: &SubtitleSource,
+    notes: Option<&str>,
+    output_dir: Option<&str>,
+    container: Option<&str>
+) -> AppResult<BurnResult> {
+    burn_subtitles_with(&ProcessBackend, input_path, start, end, source, notes, output_dir, container)
+}
+
+/// 使用指定后端烧录字幕，参数含义同 [`burn_subtitles`]
+#[allow(clippy::too_many_arguments)]
+pub fn burn_subtitles_with(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    start: &TimePoint,
+    end: &TimePoint,
+    source: &SubtitleSource,
+    notes: Option<&str>,
+    output_dir: Option<&str>,
+    container: Option<&str>
+) -> AppResult<BurnResult> {
+    let request = ExportRequest::new(input_path, notes, output_dir, container)?;
+
+    let caps = backend.capabilities()?;
+    if !caps.features.subtitles {
+        return Err(validation_error(t!("subtitle.burn_unavailable")));
+    }
+
+    // subtitles 滤镜自行读取字幕：内嵌字幕用 si 选择第几个字幕流
+    let subtitle_filter = match source {
+        SubtitleSource::Track(index) => {
+            let streams = get_subtitle_streams_with(backend, input_path)?;
+            let position = streams.iter().position(|s| s.index == *index)
+                .ok_or_else(|| validation_error(t!("subtitle.track_not_found", track = index)))?;
+            format!("subtitles=filename={}:si={}", escape_filter_value(input_path), position)
+        }
+        SubtitleSource::File(path) => {
+            let path = validate_input_path(path)?;
+            SubtitleFormat::from_path(&path)
+                .ok_or_else(|| validation_error(t!("subtitle.format_unsupported", format = path.display())))?;
+            format!("subtitles=filename={}", escape_filter_value(&path.to_string_lossy()))
+        }
+    };
+
+    let audio = audio_streams(get_stream_codecs_with(backend, input_path)?);
+    let range = resolve_range_with(backend, input_path, start, end)?;
+    let span = Some((range.start, range.end));
+    let target = request.reserve(request.estimate_size(span, range.total_duration)?)?;
+
+    // 输入定位后时间戳从 0 开始，烧录前先恢复源时间，使字幕与画面对齐；
+    // subtitles 滤镜按数据包的原始时间戳读取内嵌字幕，还需要加上容器的起始时间
+    let source_time = match source {
+        SubtitleSource::Track(_) => range.start + range.start_time,
+        SubtitleSource::File(_) => range.start,
+    };
+    let encode = Encode {
+        input_path,
+        span,
+        extra_inputs: Vec::new(),
+        video: VideoFilter::Chain(format!(
+            "setpts=PTS+{}/TB,{},setpts=PTS-STARTPTS", source_time.to_ffmpeg_seconds(), subtitle_filter
+        )),
+        audio: AudioPlan::Keep,
+    };
+    let exported = reencode_export(backend, &caps, target, &encode, &audio, range.duration().to_f64())?;
+
+    let message = exported.message("subtitle.burn_success", "subtitle.burn_verification_failed");
+    Ok(BurnResult { output_path: exported.output_path, message, verification: exported.verification })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{CommandOutput, MockBackend};
+    use crate::binaries::Tool;
+    use crate::capabilities::{FeatureSupport, FFmpegCapabilities};
+
+    const SRT: &str = "\u{feff}1\r\n00:00:01,000 --> 00:00:03,500\r\nBefore the cut\r\n\r\n\
+2\r\n00:00:09,000 --> 00:00:11,000 X1:10 X2:20\r\nAcross the start\r\nsecond line\r\n\r\n\
+3\r\n00:00:12,250 --> 00:00:13,000\r\nInside\r\n\r\n\
+4\r\n00:00:19,500 --> 00:00:21,000\r\nAcross the end\r\n";
+
+    const ASS: &str = "[Script Info]\nTitle: test\n\n[Events]\n\
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n\
+Dialogue: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,Too early\n\
+Dialogue: 0,0:00:09.50,0:00:12.00,Default,,0,0,0,,Hello, world\n\
+Comment: 0,0:00:10.00,0:00:11.00,Default,,0,0,0,,note\n";
+
+    fn s(seconds: i64) -> Rational {
+        Rational::from_integer(seconds)
+    }
+
+    #[test]
+    fn test_trim_srt() {
+        let (trimmed, count) = trim_subtitles(SRT, SubtitleFormat::Srt, s(10), s(20));
+        assert_eq!(count, 3);
+        assert_eq!(trimmed, "1\n00:00:00,000 --> 00:00:01,000 X1:10 X2:20\nAcross the start\nsecond line\n\n\
+2\n00:00:02,250 --> 00:00:03,000\nInside\n\n\
+3\n00:00:09,500 --> 00:00:10,000\nAcross the end\n\n");
+    }
+
+    #[test]
+    fn test_trim_ass() {
+        let (trimmed, count) = trim_subtitles(ASS, SubtitleFormat::Ass, s(9), s(20));
+        assert_eq!(count, 1);
+        assert!(trimmed.starts_with("[Script Info]\nTitle: test\n"));
+        assert!(trimmed.contains("Dialogue: 0,0:00:00.50,0:00:03.00,Default,,0,0,0,,Hello, world\n"));
+        assert!(!trimmed.contains("Too early"));
+        // 注释行原样保留
+        assert!(trimmed.contains("Comment: 0,0:00:10.00"));
+        assert_eq!(format_ass_time(3_723_456), "1:02:03.46");
+    }
+
+    #[test]
+    fn test_sidecars_and_escaping() {
+        let dir = std::env::temp_dir().join(format!("instant-cut-subtitle-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("match.mp4");
+        for name in ["match.mp4", "match.srt", "match.en.ASS", "match_1.srt", "other.srt", "match.txt"] {
+            fs::write(dir.join(name), SRT).unwrap();
+        }
+
+        let sidecars = find_sidecar_subtitles(&input).unwrap();
+        assert_eq!(sidecars, vec![dir.join("match.en.ASS"), dir.join("match.srt")]);
+
+        let (written, skipped) = write_trimmed_sidecars(&sidecars[1..], &input, &dir.join("match_2.mp4"), s(10), s(20));
+        assert_eq!(written, vec![dir.join("match_2.srt").to_string_lossy().to_string()]);
+        assert!(skipped.is_empty());
+        assert!(fs::read_to_string(dir.join("match_2.srt")).unwrap().starts_with("1\n00:00:00,000 --> 00:00:01,000"));
+
+        // 已存在的目标不覆盖，不是 UTF-8 的字幕不做有损转换
+        fs::write(dir.join("match.en.ASS"), b"\xff\xfeD\x00i\x00").unwrap();
+        let (written, skipped) = write_trimmed_sidecars(&sidecars, &input, &dir.join("match_2.mp4"), s(10), s(20));
+        assert!(written.is_empty());
+        assert_eq!(skipped.iter().map(|s| s.path.clone()).collect::<Vec<_>>(), vec![
+            dir.join("match.en.ASS").to_string_lossy().to_string(),
+            dir.join("match.srt").to_string_lossy().to_string(),
+        ]);
+        assert!(!dir.join("match_2.en.ASS").exists());
+
+        // 写入失败时跳过并记录原因，不使剪辑失败
+        let (written, skipped) = write_trimmed_sidecars(&sidecars[1..], &input, &dir.join("missing").join("match_3.mp4"), s(10), s(20));
+        assert!(written.is_empty());
+        assert_eq!(skipped.len(), 1);
+        assert!(fs::read_to_string(dir.join("match_2.srt")).unwrap().starts_with("1\n00:00:00,000 --> 00:00:01,000"));
+
+        assert_eq!(escape_filter_value("C:\\subs\\it's.srt"), "C\\\\:\\\\\\\\subs\\\\\\\\it\\\\\\'s.srt");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_burn_subtitles_with_mock() {
+        let dir = std::env::temp_dir().join(format!("instant-cut-burn-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("clip.mkv");
+        fs::write(&input, vec![0u8; 1024]).unwrap();
+
+        let probe = CommandOutput::ok(serde_json::json!({
+            "streams": [
+                { "index": 0, "codec_type": "video", "codec_name": "h264", "r_frame_rate": "25/1", "time_base": "1/1000" },
+                { "index": 1, "codec_type": "audio", "codec_name": "aac" },
+                { "index": 2, "codec_type": "subtitle", "codec_name": "hdmv_pgs_subtitle" },
+                { "index": 3, "codec_type": "subtitle", "codec_name": "subrip", "tags": { "language": "eng" } },
+            ],
+            "format": { "duration": "60.0", "start_time": "1.400000" },
+        }).to_string());
+        let mock = MockBackend::new();
+        for _ in 0..4 {
+            mock.respond(Tool::FFprobe, probe.clone());
+        }
+        mock.set_default(Tool::FFprobe, CommandOutput::ok(serde_json::json!({
+            "streams": [
+                { "index": 0, "codec_type": "video", "codec_name": "h264" },
+                { "index": 1, "codec_type": "audio", "codec_name": "aac" },
+            ],
+            "format": { "duration": "5.0" },
+        }).to_string()));
+        mock.set_output_contents(b"burned");
+        mock.set_capabilities(FFmpegCapabilities {
+            encoders: vec!["libx264".into()],
+            features: FeatureSupport { subtitles: true, ..Default::default() },
+            ..Default::default()
+        });
+
+        let result = burn_subtitles_with(
+            &mock, input.to_str().unwrap(), &TimePoint::seconds(10.0), &TimePoint::seconds(15.0),
+            &SubtitleSource::Track(3), None, None, None
+        ).unwrap();
+        assert!(result.verification.passed, "{:?}", result.verification);
+
+        let args = &mock.calls_to(Tool::FFmpeg)[0];
+        let filter = &args[args.iter().position(|a| a == "-vf").unwrap() + 1];
+        // 内嵌字幕按原始时间戳读取，加上容器的起始时间
+        assert!(filter.starts_with("setpts=PTS+11.4/TB,subtitles=filename="), "{}", filter);
+        assert!(filter.ends_with(":si=1,setpts=PTS-STARTPTS"), "{}", filter);
+        assert!(args.windows(2).any(|w| w == ["-c:a", "copy"]));
+
+        // 图形字幕不能提取为文本
+        let err = extract_subtitles_with(
+            &mock, input.to_str().unwrap(), None, None, Some(2), SubtitleFormat::Srt, None, None
+        );
+        assert!(matches!(err, Err(AppError::ValidationError(_))));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,635 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+use std::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::error::{AppError, AppResult, validation_error};
+use crate::t;
+
+/// 小数秒最多保留的位数（纳秒）
+const MAX_FRACTION_DIGITS: usize = 9;
+
+/// ffmpeg 时间参数的精度（微秒，即 AV_TIME_BASE）
+const FFMPEG_TIME_SCALE: i64 = 1_000_000;
+
+/// ffprobe 未给出时间基时使用的默认值
+const DEFAULT_TIME_BASE: Rational = Rational { num: 1, den: FFMPEG_TIME_SCALE };
+
+/// 有理数，分母恒为正且已约分
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i64,
+    den: i64,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { num: 0, den: 1 };
+
+    /// 创建有理数，分母为 0 时返回 `None`
+    pub fn new(num: i64, den: i64) -> Option<Self> {
+        if den == 0 {
+            return None;
+        }
+        Some(Self::reduced(num as i128, den as i128))
+    }
+
+    pub fn from_integer(n: i64) -> Self {
+        Self { num: n, den: 1 }
+    }
+
+    /// 从浮点秒数转换，按 ffmpeg 的精度（微秒）取整以消除浮点误差
+    pub fn from_seconds_f64(seconds: f64) -> Self {
+        Self::reduced((seconds * FFMPEG_TIME_SCALE as f64).round() as i128, FFMPEG_TIME_SCALE as i128)
+    }
+
+    /// 解析 "30000/1001"、"25" 或 "12.345" 形式的数值
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if let Some((num, den)) = s.split_once('/') {
+            return Self::new(num.trim().parse().ok()?, den.trim().parse().ok()?);
+        }
+
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+        if (int_part.is_empty() && frac_part.is_empty())
+            || frac_part.len() > MAX_FRACTION_DIGITS
+            || !int_part.chars().chain(frac_part.chars()).all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+
+        let int_value: i64 = if int_part.is_empty() { 0 } else { int_part.parse().ok()? };
+        let scale = 10i64.pow(frac_part.len() as u32);
+        let frac_value: i64 = if frac_part.is_empty() { 0 } else { frac_part.parse().ok()? };
+        let num = int_value.checked_mul(scale)?.checked_add(frac_value)?;
+        Self::new(if negative { -num } else { num }, scale)
+    }
+
+    pub fn num(&self) -> i64 {
+        self.num
+    }
+
+    pub fn den(&self) -> i64 {
+        self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    /// 向下取整
+    pub fn floor(&self) -> i64 {
+        self.num.div_euclid(self.den)
+    }
+
+    /// 四舍五入（.5 远离零）
+    pub fn round(&self) -> i64 {
+        let doubled = 2 * self.num as i128 + if self.num >= 0 { self.den as i128 } else { -(self.den as i128) };
+        (doubled / (2 * self.den as i128)) as i64
+    }
+
+    /// 倒数，值为 0 时返回 `None`
+    pub fn recip(&self) -> Option<Self> {
+        Self::new(self.den, self.num)
+    }
+
+    /// 格式化为 ffmpeg 的时间参数（秒，最多 6 位小数）
+    pub fn to_ffmpeg_seconds(&self) -> String {
+        let micros = (*self * Self::from_integer(FFMPEG_TIME_SCALE)).round();
+        let sign = if micros < 0 { "-" } else { "" };
+        let micros = micros.unsigned_abs();
+        let whole = micros / FFMPEG_TIME_SCALE as u64;
+        let frac = micros % FFMPEG_TIME_SCALE as u64;
+        if frac == 0 {
+            format!("{}{}", sign, whole)
+        } else {
+            let frac = format!("{:06}", frac);
+            format!("{}{}.{}", sign, whole, frac.trim_end_matches('0'))
+        }
+    }
+
+    fn reduced(num: i128, den: i128) -> Self {
+        let sign = if den < 0 { -1 } else { 1 };
+        let divisor = gcd(num.abs(), den.abs()).max(1);
+        Self {
+            num: (sign * num / divisor) as i64,
+            den: (sign * den / divisor) as i64,
+        }
+    }
+}
+
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl Add for Rational {
+    type Output = Rational;
+    fn add(self, rhs: Rational) -> Rational {
+        Rational::reduced(
+            self.num as i128 * rhs.den as i128 + rhs.num as i128 * self.den as i128,
+            self.den as i128 * rhs.den as i128,
+        )
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+    fn sub(self, rhs: Rational) -> Rational {
+        self + Rational { num: -rhs.num, den: rhs.den }
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+    fn mul(self, rhs: Rational) -> Rational {
+        Rational::reduced(self.num as i128 * rhs.num as i128, self.den as i128 * rhs.den as i128)
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+
+    /// 除数为 0 时 panic，调用方应先用 [`Rational::recip`] 或 `is_zero` 检查
+    fn div(self, rhs: Rational) -> Rational {
+        assert!(!rhs.is_zero(), "division by zero");
+        Rational::reduced(self.num as i128 * rhs.den as i128, self.den as i128 * rhs.num as i128)
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.num as i128 * other.den as i128).cmp(&(other.num as i128 * self.den as i128))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+/// 视频流的时间基和帧率，用于在秒、帧号、时间戳和时间码之间精确换算
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamClock {
+    pub time_base: Rational,                // 时间戳单位（秒）
+    pub frame_rate: Option<Rational>,       // 帧率，未知时为 `None`
+    pub start_timecode: Option<Timecode>,   // 源文件第一帧的时间码（tmcd 轨道或元数据）
+}
+
+impl StreamClock {
+    /// 由 ffprobe 的 `time_base` 和 `r_frame_rate` 字符串构造
+    pub fn from_probe(time_base: &str, r_frame_rate: &str) -> Self {
+        let time_base = Rational::parse(time_base)
+            .filter(|r| r.num > 0)
+            .unwrap_or(DEFAULT_TIME_BASE);
+        let frame_rate = Rational::parse(r_frame_rate).filter(|r| r.num > 0);
+        Self { time_base, frame_rate, start_timecode: None }
+    }
+
+    /// 设置源文件的起始时间码，与帧率不符的时间码被忽略
+    pub fn with_start_timecode(mut self, timecode: Option<Timecode>) -> Self {
+        self.start_timecode = timecode.filter(|tc| tc.to_frame(&self).is_ok());
+        self
+    }
+
+    /// 最接近给定时间的帧号（用于换算 ffprobe 输出的、已四舍五入的时间）
+    pub fn nearest_frame(&self, seconds: Rational) -> AppResult<u64> {
+        let rate = self.require_frame_rate()?;
+        Ok((seconds * rate).round().max(0) as u64)
+    }
+
+    /// 给定帧对应的时间码：有源时间码时从其开始计数，否则从 00:00:00:00 开始
+    pub fn timecode_at_frame(&self, frame: u64) -> AppResult<Timecode> {
+        match self.start_timecode {
+            Some(origin) => Timecode::from_frame(origin.to_frame(self)? + frame, self, origin.drop_frame),
+            None => Timecode::from_frame(frame, self, self.supports_drop_frame()),
+        }
+    }
+
+    /// 时间码对应的帧号（相对于源文件第一帧）
+    pub fn frame_of_timecode(&self, timecode: &Timecode) -> AppResult<u64> {
+        let frame = timecode.to_frame(self)?;
+        match self.start_timecode {
+            Some(origin) => frame.checked_sub(origin.to_frame(self)?).ok_or_else(|| {
+                validation_error(t!("time.before_source_start", value = timecode, start = origin))
+            }),
+            None => Ok(frame),
+        }
+    }
+
+    fn require_frame_rate(&self) -> AppResult<Rational> {
+        self.frame_rate.ok_or_else(|| validation_error(t!("time.frame_rate_unknown")))
+    }
+
+    /// 第 `frame` 帧的开始时间（秒）
+    pub fn frame_to_seconds(&self, frame: u64) -> AppResult<Rational> {
+        let rate = self.require_frame_rate()?;
+        Ok(Rational::from_integer(frame as i64) / rate)
+    }
+
+    /// 包含给定时间的帧号
+    pub fn seconds_to_frame(&self, seconds: Rational) -> AppResult<u64> {
+        let rate = self.require_frame_rate()?;
+        Ok((seconds * rate).floor().max(0) as u64)
+    }
+
+    /// 秒转换为以时间基为单位的时间戳
+    pub fn seconds_to_pts(&self, seconds: Rational) -> i64 {
+        (seconds / self.time_base).round()
+    }
+
+    /// 以时间基为单位的时间戳转换为秒
+    pub fn pts_to_seconds(&self, pts: i64) -> Rational {
+        Rational::from_integer(pts) * self.time_base
+    }
+
+    /// 时间码使用的整数帧率 (例如 29.97 -> 30)
+    pub fn nominal_fps(&self) -> AppResult<u32> {
+        Ok(self.require_frame_rate()?.round().max(1) as u32)
+    }
+
+    /// 是否为可使用丢帧时间码的 NTSC 帧率 (29.97 / 59.94)
+    pub fn supports_drop_frame(&self) -> bool {
+        matches!(self.frame_rate, Some(r) if r.den == 1001 && (r.num == 30000 || r.num == 60000))
+    }
+
+    /// 每分钟丢弃的帧号数量 (29.97 为 2，59.94 为 4)
+    fn dropped_per_minute(&self) -> AppResult<u64> {
+        Ok(self.nominal_fps()? as u64 / 15)
+    }
+}
+
+/// SMPTE 时间码 (HH:MM:SS:FF，丢帧时为 HH:MM:SS;FF)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timecode {
+    pub hours: u32,
+    pub minutes: u32,
+    pub seconds: u32,
+    pub frames: u32,
+    pub drop_frame: bool,
+}
+
+impl Timecode {
+    /// 解析时间码，帧号前使用 `;` 表示丢帧
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let drop_frame = s.contains(';');
+        let parts: Vec<&str> = s.split([':', ';']).collect();
+        if parts.len() != 4 || parts.iter().any(|p| p.is_empty() || !p.chars().all(|c| c.is_ascii_digit())) {
+            return None;
+        }
+        let values: Vec<u32> = parts.iter().map(|p| p.parse().ok()).collect::<Option<_>>()?;
+        Some(Self {
+            hours: values[0],
+            minutes: values[1],
+            seconds: values[2],
+            frames: values[3],
+            drop_frame,
+        })
+    }
+
+    /// 转换为从 0 开始的帧号
+    pub fn to_frame(&self, clock: &StreamClock) -> AppResult<u64> {
+        let fps = clock.nominal_fps()?;
+        let invalid = || validation_error(t!("time.invalid_timecode", value = self));
+        if self.minutes >= 60 || self.seconds >= 60 || self.frames >= fps {
+            return Err(invalid());
+        }
+
+        let total_minutes = 60 * self.hours as u64 + self.minutes as u64;
+        let mut frame = ((total_minutes * 60 + self.seconds as u64) * fps as u64) + self.frames as u64;
+
+        if self.drop_frame {
+            if !clock.supports_drop_frame() {
+                return Err(validation_error(t!("time.drop_frame_unsupported", fps = clock.require_frame_rate()?)));
+            }
+            // 除每第十分钟外，每分钟开头的帧号被跳过
+            let dropped = clock.dropped_per_minute()?;
+            if self.seconds == 0 && !self.minutes.is_multiple_of(10) && (self.frames as u64) < dropped {
+                return Err(invalid());
+            }
+            frame -= dropped * (total_minutes - total_minutes / 10);
+        }
+
+        Ok(frame)
+    }
+
+    /// 由帧号生成时间码
+    pub fn from_frame(frame: u64, clock: &StreamClock, drop_frame: bool) -> AppResult<Self> {
+        let fps = clock.nominal_fps()? as u64;
+        let mut frame = frame;
+
+        let drop_frame = drop_frame && clock.supports_drop_frame();
+        if drop_frame {
+            let dropped = clock.dropped_per_minute()?;
+            let per_ten_minutes = fps * 600 - dropped * 9;
+            let per_minute = fps * 60 - dropped;
+            let tens = frame / per_ten_minutes;
+            let rest = frame % per_ten_minutes;
+            frame += dropped * 9 * tens;
+            if rest > dropped {
+                frame += dropped * ((rest - dropped) / per_minute);
+            }
+        }
+
+        Ok(Self {
+            hours: (frame / (fps * 3600)) as u32,
+            minutes: (frame / (fps * 60) % 60) as u32,
+            seconds: (frame / fps % 60) as u32,
+            frames: (frame % fps) as u32,
+            drop_frame,
+        })
+    }
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.drop_frame { ';' } else { ':' };
+        write!(f, "{:02}:{:02}:{:02}{}{:02}", self.hours, self.minutes, self.seconds, separator, self.frames)
+    }
+}
+
+/// 用户指定的时间点：秒、帧号或 SMPTE 时间码（源文件带有起始时间码时按源时间码解释）
+///
+/// 字符串形式: `12.5` / `12.5s`、`[HH:]MM:SS[.sss]`、`1234f`（帧号）、`HH:MM:SS:FF` / `HH:MM:SS;FF`（时间码）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimePoint {
+    Seconds(Rational),
+    Frame(u64),
+    Timecode(Timecode),
+}
+
+impl TimePoint {
+    /// 由浮点秒数构造
+    pub fn seconds(seconds: f64) -> Self {
+        TimePoint::Seconds(Rational::from_seconds_f64(seconds))
+    }
+
+    /// 换算为精确的秒数
+    pub fn resolve(&self, clock: &StreamClock) -> AppResult<Rational> {
+        match self {
+            TimePoint::Seconds(seconds) => Ok(*seconds),
+            TimePoint::Frame(frame) => clock.frame_to_seconds(*frame),
+            TimePoint::Timecode(timecode) => clock.frame_to_seconds(clock.frame_of_timecode(timecode)?),
+        }
+    }
+}
+
+impl FromStr for TimePoint {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim();
+        let invalid = || validation_error(t!("time.invalid", value = s));
+
+        if let Some(frame) = value.strip_suffix('f') {
+            return frame.trim().parse().map(TimePoint::Frame).map_err(|_| invalid());
+        }
+
+        if value.contains(';') || value.matches(':').count() == 3 {
+            return Timecode::parse(value).map(TimePoint::Timecode).ok_or_else(invalid);
+        }
+
+        if value.contains(':') {
+            // [HH:]MM:SS[.sss]
+            let parts: Vec<&str> = value.split(':').collect();
+            if parts.len() > 3 {
+                return Err(invalid());
+            }
+            let mut total = Rational::ZERO;
+            for (i, part) in parts.iter().enumerate() {
+                let is_last = i == parts.len() - 1;
+                let component = if is_last {
+                    Rational::parse(part)
+                } else {
+                    part.parse::<u32>().ok().map(|v| Rational::from_integer(v as i64))
+                };
+                let component = component.filter(|c| *c >= Rational::ZERO).ok_or_else(invalid)?;
+                if i > 0 && component >= Rational::from_integer(60) {
+                    return Err(invalid());
+                }
+                total = total * Rational::from_integer(60) + component;
+            }
+            return Ok(TimePoint::Seconds(total));
+        }
+
+        let seconds = value.strip_suffix('s').unwrap_or(value);
+        Rational::parse(seconds).map(TimePoint::Seconds).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for TimePoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimePoint::Seconds(seconds) => write!(f, "{}", seconds.to_ffmpeg_seconds()),
+            TimePoint::Frame(frame) => write!(f, "{}f", frame),
+            TimePoint::Timecode(timecode) => write!(f, "{}", timecode),
+        }
+    }
+}
+
+impl Serialize for TimePoint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TimePoint {
+    /// 接受数字（秒）或上述字符串形式
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Seconds(f64),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Seconds(seconds) => Ok(TimePoint::seconds(seconds)),
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// 换算后的时间点，同时给出秒、帧号和时间码
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedTime {
+    pub seconds: f64,               // 秒
+    pub frame: Option<u64>,         // 帧号，帧率未知时为空
+    pub timecode: Option<String>,   // 时间码（有源时间码时为源时间码），帧率未知时为空
+}
+
+impl ResolvedTime {
+    pub fn new(seconds: Rational, clock: &StreamClock) -> Self {
+        let frame = clock.seconds_to_frame(seconds).ok();
+        let timecode = frame
+            .and_then(|f| clock.timecode_at_frame(f).ok())
+            .map(|tc| tc.to_string());
+        Self { seconds: seconds.to_f64(), frame, timecode }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ntsc() -> StreamClock {
+        StreamClock::from_probe("1/30000", "30000/1001")
+    }
+
+    fn r(num: i64, den: i64) -> Rational {
+        Rational::new(num, den).unwrap()
+    }
+
+    #[test]
+    fn test_rational_arithmetic() {
+        assert_eq!(r(2, 4), r(1, 2));
+        assert_eq!(r(1, -2), r(-1, 2));
+        assert_eq!(r(1, 10) + r(2, 10), r(3, 10));
+        assert_eq!(r(1, 2) - r(1, 3), r(1, 6));
+        assert_eq!(r(1001, 30000) * Rational::from_integer(30), r(1001, 1000));
+        assert!(r(1, 3) < r(1, 2));
+        assert_eq!(r(7, 2).floor(), 3);
+        assert_eq!(r(7, 2).round(), 4);
+        assert_eq!(r(-7, 2).floor(), -4);
+        assert!(Rational::new(1, 0).is_none());
+    }
+
+    #[test]
+    fn test_rational_parse_and_format() {
+        assert_eq!(Rational::parse("30000/1001"), Some(r(30000, 1001)));
+        assert_eq!(Rational::parse("12.345"), Some(r(12345, 1000)));
+        assert_eq!(Rational::parse(".5"), Some(r(1, 2)));
+        assert_eq!(Rational::parse("0/0"), None);
+        assert_eq!(Rational::parse("abc"), None);
+
+        // 0.1 + 0.2 的浮点误差被消除
+        assert_eq!(Rational::from_seconds_f64(0.1 + 0.2), r(3, 10));
+        assert_eq!(r(3, 10).to_ffmpeg_seconds(), "0.3");
+        assert_eq!(Rational::from_integer(5).to_ffmpeg_seconds(), "5");
+        assert_eq!(r(1001, 30000).to_ffmpeg_seconds(), "0.033367");
+    }
+
+    #[test]
+    fn test_frame_addressing() {
+        let clock = ntsc();
+        let seconds = clock.frame_to_seconds(1234).unwrap();
+        assert_eq!(seconds, r(1234 * 1001, 30000));
+        assert_eq!(clock.seconds_to_frame(seconds).unwrap(), 1234);
+        assert_eq!(clock.seconds_to_pts(seconds), 1234 * 1001);
+        assert_eq!(clock.pts_to_seconds(1234 * 1001), seconds);
+
+        let unknown = StreamClock::from_probe("1/1000", "0/0");
+        assert!(unknown.frame_to_seconds(10).is_err());
+    }
+
+    #[test]
+    fn test_drop_frame_timecode() {
+        let clock = ntsc();
+        // 00:01:00;02 是丢帧后的第 1800 帧
+        let tc = Timecode::parse("00:01:00;02").unwrap();
+        assert!(tc.drop_frame);
+        assert_eq!(tc.to_frame(&clock).unwrap(), 1800);
+        assert_eq!(Timecode::from_frame(1800, &clock, true).unwrap(), tc);
+
+        // 第十分钟不丢帧
+        let tc = Timecode::parse("00:10:00;00").unwrap();
+        assert_eq!(tc.to_frame(&clock).unwrap(), 17982);
+        assert_eq!(Timecode::from_frame(17982, &clock, true).unwrap().to_string(), "00:10:00;00");
+
+        // 一小时正好 107892 帧
+        assert_eq!(Timecode::parse("01:00:00;00").unwrap().to_frame(&clock).unwrap(), 107892);
+
+        // 被跳过的帧号不存在
+        assert!(Timecode::parse("00:01:00;00").unwrap().to_frame(&clock).is_err());
+
+        // 往返换算
+        for frame in [0, 1, 1799, 1800, 17981, 17982, 17983, 107891, 123456] {
+            let tc = Timecode::from_frame(frame, &clock, true).unwrap();
+            assert_eq!(tc.to_frame(&clock).unwrap(), frame, "{}", tc);
+        }
+    }
+
+    #[test]
+    fn test_non_drop_timecode() {
+        let clock = StreamClock::from_probe("1/12800", "25/1");
+        let tc = Timecode::parse("01:02:03:04").unwrap();
+        assert!(!tc.drop_frame);
+        assert_eq!(tc.to_frame(&clock).unwrap(), (3723 * 25) + 4);
+        assert_eq!(Timecode::from_frame(93079, &clock, false).unwrap(), tc);
+        assert!(Timecode::parse("00:00:00:25").unwrap().to_frame(&clock).is_err());
+
+        // 25 fps 不支持丢帧时间码
+        assert!(Timecode::parse("00:00:01;00").unwrap().to_frame(&clock).is_err());
+    }
+
+    #[test]
+    fn test_time_point_parse() {
+        let clock = ntsc();
+        let resolve = |s: &str| s.parse::<TimePoint>().unwrap().resolve(&clock).unwrap();
+
+        assert_eq!(resolve("12.5"), r(25, 2));
+        assert_eq!(resolve("12.5s"), r(25, 2));
+        assert_eq!(resolve("01:30"), Rational::from_integer(90));
+        assert_eq!(resolve("1:00:01.25"), r(14405, 4));
+        assert_eq!(resolve("1234f"), r(1234 * 1001, 30000));
+        assert_eq!(resolve("00:01:00;02"), r(1800 * 1001, 30000));
+        assert_eq!(resolve("00:00:01:00"), r(30 * 1001, 30000));
+
+        assert!("abc".parse::<TimePoint>().is_err());
+        assert!("1:75".parse::<TimePoint>().is_err());
+        assert!("00:00:01:xx".parse::<TimePoint>().is_err());
+    }
+
+    #[test]
+    fn test_source_timecode_origin() {
+        let clock = ntsc().with_start_timecode(Timecode::parse("01:00:00;00"));
+        let resolve = |s: &str| s.parse::<TimePoint>().unwrap().resolve(&clock);
+
+        // 源时间码 01:00:05;00 是第 150 帧
+        assert_eq!(resolve("01:00:05;00").unwrap(), r(150 * 1001, 30000));
+        // 早于源起始时间码
+        assert!(resolve("00:59:59;00").is_err());
+        // 秒和帧号不受源时间码影响
+        assert_eq!(resolve("150f").unwrap(), r(150 * 1001, 30000));
+
+        assert_eq!(clock.timecode_at_frame(150).unwrap().to_string(), "01:00:05;00");
+        // 跨过丢帧的分钟边界
+        assert_eq!(clock.timecode_at_frame(1800).unwrap().to_string(), "01:01:00;02");
+        assert_eq!(ResolvedTime::new(r(5, 1), &clock).timecode.as_deref(), Some("01:00:04;29"));
+
+        // 与帧率不符的时间码被忽略
+        let clock = StreamClock::from_probe("1/12800", "25/1").with_start_timecode(Timecode::parse("10:00:00:29"));
+        assert_eq!(clock.start_timecode, None);
+        assert_eq!(clock.nearest_frame(r(3999999, 1000000)).unwrap(), 100);
+    }
+
+    #[test]
+    fn test_time_point_serde() {
+        let from_number: TimePoint = serde_json::from_str("2.5").unwrap();
+        assert_eq!(from_number, TimePoint::Seconds(r(5, 2)));
+        let from_text: TimePoint = serde_json::from_str("\"120f\"").unwrap();
+        assert_eq!(from_text, TimePoint::Frame(120));
+        assert_eq!(serde_json::to_string(&from_text).unwrap(), "\"120f\"");
+        assert!(serde_json::from_str::<TimePoint>("\"nope\"").is_err());
+    }
+}
@@ -0,0 +1,506 @@
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::backend::{MediaBackend, ProcessBackend};
+use crate::capabilities::FFmpegCapabilities;
+use crate::error::{AppError, AppResult, validation_error};
+use crate::export::{audio_streams, reencode_export, AudioPlan, Encode, Exported, ExportRequest, VideoFilter};
+use crate::join::run_ffmpeg;
+use crate::media::{get_stream_codecs_with, get_video_info_with, resolve_optional_range_with};
+use crate::timestamp::TimePoint;
+use crate::verify::StreamCounts;
+use crate::video::TransformResult;
+use crate::t;
+
+/// 输出边长上限
+const MAX_DIMENSION: u32 = 16384;
+
+/// 支持写入显示矩阵的容器
+const DISPLAY_MATRIX_CONTAINERS: &[&str] = &["mp4", "mov", "m4v"];
+
+/// `-display_rotation` 等输入选项从 ffmpeg 6.0 开始提供
+const DISPLAY_ROTATION_MIN_VERSION: (u32, u32) = (6, 0);
+
+/// 裁剪区域，坐标为显示画面（已按源的显示矩阵旋转）中的像素
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CropRect {
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub x: Option<u32>,     // 左上角横坐标，为空时水平居中
+    #[serde(default)]
+    pub y: Option<u32>,     // 左上角纵坐标，为空时垂直居中
+}
+
+impl FromStr for CropRect {
+    type Err = AppError;
+
+    /// 解析 `宽:高[:X:Y]`，与 ffmpeg crop 滤镜的参数顺序一致
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || validation_error(t!("transform.invalid_crop", value = s));
+        let parts = s.split(':')
+            .map(|p| p.trim().parse::<u32>().map_err(|_| invalid()))
+            .collect::<AppResult<Vec<u32>>>()?;
+        match parts[..] {
+            [width, height] => Ok(CropRect { width, height, x: None, y: None }),
+            [width, height, x, y] => Ok(CropRect { width, height, x: Some(x), y: Some(y) }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// 同时指定宽高时的缩放方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScaleMode {
+    #[default]
+    Fit,        // 保持比例缩放到目标尺寸以内，可用 pad_color 补边到目标尺寸
+    Fill,       // 保持比例缩放到覆盖目标尺寸，再居中裁掉多余部分
+    Stretch,    // 不保持比例，直接拉伸到目标尺寸
+}
+
+impl FromStr for ScaleMode {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "fit" => Ok(ScaleMode::Fit),
+            "fill" => Ok(ScaleMode::Fill),
+            "stretch" => Ok(ScaleMode::Stretch),
+            _ => Err(validation_error(t!("transform.scale_mode_unknown", mode = s))),
+        }
+    }
+}
+
+/// 翻转方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Flip {
+    Horizontal,
+    Vertical,
+    Both,
+}
+
+impl FromStr for Flip {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "h" | "horizontal" => Ok(Flip::Horizontal),
+            "v" | "vertical" => Ok(Flip::Vertical),
+            "both" => Ok(Flip::Both),
+            _ => Err(validation_error(t!("transform.flip_unknown", flip = s))),
+        }
+    }
+}
+
+/// 导出时的几何变换，按 裁剪 -> 旋转 / 翻转 -> 缩放 -> 补边 的顺序应用
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransformOptions {
+    pub crop: Option<CropRect>,         // 裁剪区域
+    pub width: Option<u32>,             // 缩放后的宽度，只指定一边时按比例缩放
+    pub height: Option<u32>,            // 缩放后的高度
+    pub scale_mode: ScaleMode,          // 同时指定宽高时的缩放方式
+    pub pad_color: Option<String>,      // fit 缩放后补边到目标尺寸的颜色，例如 black、white、#202020
+    pub rotate: Option<u32>,            // 顺时针旋转 90 / 180 / 270 度
+    pub flip: Option<Flip>,             // 翻转
+}
+
+/// 滤镜链和输出的显示尺寸
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Geometry {
+    filters: Vec<String>,
+    width: u32,
+    height: u32,
+}
+
+impl TransformOptions {
+    /// 只有旋转、不需要改动像素时可以只改写显示矩阵
+    fn is_rotation_only(&self) -> bool {
+        self.rotate.is_some() && self.crop.is_none() && self.width.is_none() && self.height.is_none()
+            && self.pad_color.is_none() && self.flip.is_none()
+    }
+
+    /// 校验选项，按源的显示尺寸计算滤镜链和输出尺寸
+    fn geometry(&self, source_width: u32, source_height: u32) -> AppResult<Geometry> {
+        let invalid_size = || validation_error(t!("transform.invalid_size", max = MAX_DIMENSION));
+        if self == &TransformOptions::default() {
+            return Err(validation_error(t!("transform.empty")));
+        }
+        let mut filters = Vec::new();
+        let (mut width, mut height) = (source_width, source_height);
+
+        if let Some(crop) = &self.crop {
+            let x = crop.x.unwrap_or(width.saturating_sub(crop.width) / 2);
+            let y = crop.y.unwrap_or(height.saturating_sub(crop.height) / 2);
+            let out_of_bounds = || validation_error(t!(
+                "transform.crop_out_of_bounds", width = source_width, height = source_height
+            ));
+            let right = x.checked_add(crop.width).ok_or_else(out_of_bounds)?;
+            let bottom = y.checked_add(crop.height).ok_or_else(out_of_bounds)?;
+            if crop.width == 0 || crop.height == 0 || right > width || bottom > height {
+                return Err(out_of_bounds());
+            }
+            filters.push(format!("crop={}:{}:{}:{}", crop.width, crop.height, x, y));
+            (width, height) = (crop.width, crop.height);
+        }
+
+        match self.rotate {
+            None | Some(0) => {}
+            Some(90) => filters.push("transpose=clock".to_string()),
+            Some(270) => filters.push("transpose=cclock".to_string()),
+            Some(180) => filters.extend(["hflip".to_string(), "vflip".to_string()]),
+            Some(degrees) => return Err(validation_error(t!("transform.invalid_rotation", degrees = degrees))),
+        }
+        if matches!(self.rotate, Some(90) | Some(270)) {
+            (width, height) = (height, width);
+        }
+
+        match self.flip {
+            None => {}
+            Some(Flip::Horizontal) => filters.push("hflip".to_string()),
+            Some(Flip::Vertical) => filters.push("vflip".to_string()),
+            Some(Flip::Both) => filters.extend(["hflip".to_string(), "vflip".to_string()]),
+        }
+
+        for target in [self.width, self.height].into_iter().flatten() {
+            if target == 0 || target % 2 != 0 || target > MAX_DIMENSION {
+                return Err(invalid_size());
+            }
+        }
+        let (w, h) = (width as f64, height as f64);
+        let pad_allowed = self.scale_mode == ScaleMode::Fit && self.width.is_some() && self.height.is_some();
+        if self.pad_color.is_some() && !pad_allowed {
+            return Err(validation_error(t!("transform.pad_requires_fit")));
+        }
+
+        match (self.width, self.height) {
+            (None, None) => {}
+            (Some(target_w), None) => {
+                let target_h = even(h * target_w as f64 / w);
+                filters.push(format!("scale={}:{},setsar=1", target_w, target_h));
+                (width, height) = (target_w, target_h);
+            }
+            (None, Some(target_h)) => {
+                let target_w = even(w * target_h as f64 / h);
+                filters.push(format!("scale={}:{},setsar=1", target_w, target_h));
+                (width, height) = (target_w, target_h);
+            }
+            (Some(target_w), Some(target_h)) => match self.scale_mode {
+                ScaleMode::Stretch => {
+                    filters.push(format!("scale={}:{},setsar=1", target_w, target_h));
+                    (width, height) = (target_w, target_h);
+                }
+                ScaleMode::Fit => {
+                    let ratio = (target_w as f64 / w).min(target_h as f64 / h);
+                    let (scaled_w, scaled_h) = (even(w * ratio).min(target_w), even(h * ratio).min(target_h));
+                    filters.push(format!("scale={}:{},setsar=1", scaled_w, scaled_h));
+                    (width, height) = (scaled_w, scaled_h);
+                    if let Some(color) = &self.pad_color {
+                        let color = validate_color(color)?;
+                        // 偏移取偶数，避免 4:2:0 色度错位
+                        let x = (target_w - scaled_w) / 4 * 2;
+                        let y = (target_h - scaled_h) / 4 * 2;
+                        filters.push(format!("pad={}:{}:{}:{}:{}", target_w, target_h, x, y, color));
+                        (width, height) = (target_w, target_h);
+                    }
+                }
+                ScaleMode::Fill => {
+                    let ratio = (target_w as f64 / w).max(target_h as f64 / h);
+                    let (scaled_w, scaled_h) = (even(w * ratio).max(target_w), even(h * ratio).max(target_h));
+                    filters.push(format!(
+                        "scale={}:{},setsar=1,crop={}:{}:{}:{}",
+                        scaled_w, scaled_h, target_w, target_h, (scaled_w - target_w) / 4 * 2, (scaled_h - target_h) / 4 * 2
+                    ));
+                    (width, height) = (target_w, target_h);
+                }
+            },
+        }
+
+        // 4:2:0 编码要求宽高为偶数
+        if width % 2 != 0 || height % 2 != 0 {
+            return Err(validation_error(t!("transform.odd_size", width = width, height = height)));
+        }
+        if width > MAX_DIMENSION || height > MAX_DIMENSION {
+            return Err(invalid_size());
+        }
+        Ok(Geometry { filters, width, height })
+    }
+}
+
+/// 四舍五入到最近的偶数，至少为 2
+fn even(value: f64) -> u32 {
+    ((value / 2.0).round() as u32 * 2).max(2)
+}
+
+/// 校验补边颜色：颜色名或 #RRGGBB / 0xRRGGBB[AA]
+fn validate_color(color: &str) -> AppResult<&str> {
+    let color = color.trim();
+    let hex = color.strip_prefix('#').or_else(|| color.strip_prefix("0x"));
+    let valid = match hex {
+        Some(digits) => (digits.len() == 6 || digits.len() == 8) && digits.chars().all(|c| c.is_ascii_hexdigit()),
+        None => !color.is_empty() && color.len() <= 32 && color.chars().all(|c| c.is_ascii_alphabetic()),
+    };
+    if !valid {
+        return Err(validation_error(t!("transform.invalid_color", color = color)));
+    }
+    Ok(color)
+}
+
+/// 当前 ffmpeg 是否支持 `-display_rotation`，开发版视为支持
+fn supports_display_rotation(caps: &FFmpegCapabilities) -> bool {
+    match (caps.version_major, caps.version_minor) {
+        (Some(major), minor) => (major, minor.unwrap_or(0)) >= DISPLAY_ROTATION_MIN_VERSION,
+        (None, _) => true,
+    }
+}
+
+/// 对时间范围（或整个文件）应用几何变换，输出到下一个版本文件
+///
+/// 只旋转且输出为 MP4 / MOV 时流复制并改写显示矩阵，不重新编码；
+/// 否则重新编码视频，音频能放入输出容器时流复制
+#[allow(clippy::too_many_arguments)]
+pub fn transform_video(
+    input_path: &str,
+    start: Option<&TimePoint>,
+    end: Option<&TimePoint>,
+    options: &TransformOptions,
+    notes: Option<&str>,
+    output_dir: Option<&str>,
+    container: Option<&str>
+) -> AppResult<TransformResult> {
+    transform_video_with(&ProcessBackend, input_path, start, end, options, notes, output_dir, container)
+}
+
+/// 使用指定后端应用几何变换，参数含义同 [`transform_video`]
+#[allow(clippy::too_many_arguments)]
+pub fn transform_video_with(
+    backend: &dyn MediaBackend,
+    input_path: &str,
+    start: Option<&TimePoint>,
+    end: Option<&TimePoint>,
+    options: &TransformOptions,
+    notes: Option<&str>,
+    output_dir: Option<&str>,
+    container: Option<&str>
+) -> AppResult<TransformResult> {
+    let request = ExportRequest::new(input_path, notes, output_dir, container)?;
+
+    // 按源的显示尺寸计算输出尺寸
+    let info = get_video_info_with(backend, input_path)?;
+    let (source_width, source_height) = info.display_size();
+    let geometry = options.geometry(source_width, source_height)?;
+
+    let audio = audio_streams(get_stream_codecs_with(backend, input_path)?);
+    let range = resolve_optional_range_with(backend, input_path, start, end)?;
+    let span = range.map(|r| (r.start, r.end));
+    let duration = range.map(|r| r.duration().to_f64()).unwrap_or(info.duration);
+
+    // 预留输出文件并检查磁盘空间
+    let target = request.reserve(request.estimate_size(span, info.duration)?)?;
+    let caps = backend.capabilities()?;
+    let lossless = options.is_rotation_only()
+        && DISPLAY_MATRIX_CONTAINERS.contains(&target.ext.as_str())
+        && supports_display_rotation(&caps);
+
+    let exported = if lossless {
+        // 显示矩阵为逆时针角度，替换源的显示矩阵
+        let rotation = (info.rotation - options.rotate.unwrap_or(0) as i32).rem_euclid(360);
+        let mut args: Vec<String> = vec!["-y".into(), "-display_rotation:v:0".into(), rotation.to_string()];
+        if let Some((start, end)) = span {
+            args.extend([
+                "-ss".into(), start.to_ffmpeg_seconds(),
+                "-t".into(), (end - start).to_ffmpeg_seconds(),
+            ]);
+        }
+        args.extend([
+            "-i".into(), input_path.into(),
+            "-map".into(), "0:v:0".into(),
+            "-map".into(), "0:a?".into(),
+            "-c".into(), "copy".into(),
+            "-avoid_negative_ts".into(), "1".into(),
+            "-sn".into(), target.output()?.into(),
+        ]);
+        run_ffmpeg(backend, args)?;
+        let expected_streams = StreamCounts { video: 1, audio: audio.len() as u32, subtitle: 0 };
+        target.finish(backend, duration, expected_streams, false)?
+    } else {
+        let encode = Encode {
+            input_path,
+            span,
+            extra_inputs: Vec::new(),
+            video: VideoFilter::Chain(geometry.filters.join(",")),
+            audio: AudioPlan::Keep,
+        };
+        reencode_export(backend, &caps, target, &encode, &audio, duration)?
+    };
+
+    // 校验显示尺寸
+    let Exported { output_path, verification } = exported;
+    let (width, height) = get_video_info_with(backend, &output_path)?.display_size();
+    let dimensions_ok = (width, height) == (geometry.width, geometry.height);
+
+    let message = if !dimensions_ok {
+        t!("transform.size_mismatch", expected = format!("{}x{}", geometry.width, geometry.height),
+            actual = format!("{}x{}", width, height))
+    } else if !verification.passed {
+        t!("transform.verification_failed", path = output_path)
+    } else {
+        t!("transform.success", width = width, height = height, path = output_path)
+    };
+
+    Ok(TransformResult {
+        output_path,
+        message,
+        verification,
+        lossless,
+        expected_width: geometry.width,
+        expected_height: geometry.height,
+        width,
+        height,
+        dimensions_ok,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use crate::backend::{CommandOutput, MockBackend};
+    use crate::binaries::Tool;
+
+    fn geometry(options: TransformOptions, width: u32, height: u32) -> AppResult<(Vec<String>, u32, u32)> {
+        options.geometry(width, height).map(|g| (g.filters, g.width, g.height))
+    }
+
+    #[test]
+    fn test_parse_options() {
+        assert_eq!("608:1080".parse::<CropRect>().unwrap(), CropRect { width: 608, height: 1080, x: None, y: None });
+        assert_eq!("100:50:10:20".parse::<CropRect>().unwrap().y, Some(20));
+        assert!("100:50:10".parse::<CropRect>().is_err());
+        assert_eq!("Fill".parse::<ScaleMode>().unwrap(), ScaleMode::Fill);
+        assert_eq!("h".parse::<Flip>().unwrap(), Flip::Horizontal);
+        assert!("diagonal".parse::<Flip>().is_err());
+    }
+
+    #[test]
+    fn test_geometry_vertical_formats() {
+        // 横屏 1920x1080 改为竖屏 1080x1920：居中裁剪后缩放
+        let fill = TransformOptions { width: Some(1080), height: Some(1920), scale_mode: ScaleMode::Fill, ..Default::default() };
+        assert_eq!(geometry(fill, 1920, 1080).unwrap(), (
+            vec!["scale=3414:1920,setsar=1,crop=1080:1920:1166:0".to_string()], 1080, 1920
+        ));
+
+        // 保持完整画面并上下补黑边
+        let letterbox = TransformOptions {
+            width: Some(1080), height: Some(1920), pad_color: Some("black".to_string()), ..Default::default()
+        };
+        assert_eq!(geometry(letterbox, 1920, 1080).unwrap(), (
+            vec!["scale=1080:608,setsar=1".to_string(), "pad=1080:1920:0:656:black".to_string()], 1080, 1920
+        ));
+
+        // 先裁剪中间 9:16 的区域，再缩小
+        let crop = TransformOptions {
+            crop: Some("608:1080".parse().unwrap()), height: Some(1280), ..Default::default()
+        };
+        assert_eq!(geometry(crop, 1920, 1080).unwrap(), (
+            vec!["crop=608:1080:656:0".to_string(), "scale=720:1280,setsar=1".to_string()], 720, 1280
+        ));
+    }
+
+    #[test]
+    fn test_geometry_rotate_and_flip() {
+        let rotate = TransformOptions { rotate: Some(90), flip: Some(Flip::Vertical), ..Default::default() };
+        assert_eq!(geometry(rotate, 1920, 1080).unwrap(), (
+            vec!["transpose=clock".to_string(), "vflip".to_string()], 1080, 1920
+        ));
+
+        let invalid = [
+            TransformOptions::default(),
+            TransformOptions { rotate: Some(45), ..Default::default() },
+            TransformOptions { crop: Some("1000:1000:1000:0".parse().unwrap()), ..Default::default() },
+            TransformOptions { crop: Some("101:100".parse().unwrap()), ..Default::default() },
+            TransformOptions { crop: Some("100:100:4294967295:0".parse().unwrap()), ..Default::default() },
+            TransformOptions { width: Some(1081), ..Default::default() },
+            TransformOptions { width: Some(1080), pad_color: Some("black".to_string()), ..Default::default() },
+            TransformOptions {
+                width: Some(1080), height: Some(1920), pad_color: Some("#12345".to_string()), ..Default::default()
+            },
+        ];
+        for options in invalid {
+            assert!(options.geometry(1920, 1080).is_err(), "{:?}", options);
+        }
+    }
+
+    fn mock_source(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("instant-cut-transform-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("clip.mp4");
+        fs::write(&input, vec![0u8; 1024]).unwrap();
+        (dir, input)
+    }
+
+    fn probe(width: u32, height: u32, rotation: i32) -> CommandOutput {
+        CommandOutput::ok(serde_json::json!({
+            "streams": [
+                {
+                    "index": 0, "codec_type": "video", "codec_name": "h264", "width": width, "height": height,
+                    "r_frame_rate": "25/1", "time_base": "1/12800",
+                    "side_data_list": [{ "side_data_type": "Display Matrix", "rotation": rotation }],
+                },
+                { "index": 1, "codec_type": "audio", "codec_name": "aac" },
+            ],
+            "format": { "duration": "10.000000", "format_name": "mov,mp4,m4a,3gp,3g2,mj2", "start_time": "0.000000" },
+        }).to_string())
+    }
+
+    #[test]
+    fn test_transform_lossless_rotation() {
+        let (dir, input) = mock_source("lossless");
+        let mock = MockBackend::new();
+        // 横屏源顺时针旋转 90 度：显示矩阵为逆时针 270 度，显示尺寸为 1080x1920
+        mock.respond(Tool::FFprobe, probe(1920, 1080, 0));
+        mock.set_default(Tool::FFprobe, probe(1920, 1080, 270));
+        mock.set_output_contents(b"rotated");
+        mock.set_capabilities(FFmpegCapabilities { version_major: Some(6), version_minor: Some(1), ..Default::default() });
+
+        let options = TransformOptions { rotate: Some(90), ..Default::default() };
+        let result = transform_video_with(&mock, input.to_str().unwrap(), None, None, &options, None, None, None).unwrap();
+        assert!(result.lossless);
+        assert!(result.dimensions_ok, "{}", result.message);
+        assert_eq!((result.width, result.height), (1080, 1920));
+
+        let args = &mock.calls_to(Tool::FFmpeg)[0];
+        assert_eq!(args[1..3], ["-display_rotation:v:0", "270"]);
+        assert!(args.windows(2).any(|w| w == ["-c", "copy"]));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_transform_reencode_checks_dimensions() {
+        let (dir, input) = mock_source("reencode");
+        let mock = MockBackend::new();
+        mock.respond(Tool::FFprobe, probe(1920, 1080, 0));
+        mock.set_default(Tool::FFprobe, probe(1920, 1080, 0));
+        mock.set_output_contents(b"scaled");
+        mock.set_capabilities(FFmpegCapabilities { encoders: vec!["libx264".into()], ..Default::default() });
+
+        // 输出仍为 1920x1080，与计算出的 1080x1920 不符
+        let options = TransformOptions {
+            width: Some(1080), height: Some(1920), scale_mode: ScaleMode::Fill, ..Default::default()
+        };
+        let result = transform_video_with(&mock, input.to_str().unwrap(), None, None, &options, None, None, None).unwrap();
+        assert!(!result.lossless);
+        assert!(!result.dimensions_ok);
+
+        let args = &mock.calls_to(Tool::FFmpeg)[0];
+        assert!(args.windows(2).any(|w| w == ["-c:v", "libx264"]));
+        assert!(args.windows(2).any(|w| w == ["-c:a", "copy"]));
+        assert!(args.windows(2).any(|w| w[0] == "-vf" && w[1].starts_with("scale=3414:1920")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
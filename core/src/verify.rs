@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use crate::error::AppResult;
+use crate::media::{get_video_duration_with, get_stream_codecs_with};
+use crate::backend::MediaBackend;
+use crate::t;
+
+/// 时长允许的误差（秒）
+pub const DURATION_TOLERANCE: f64 = 0.5;
+
+/// 流复制时由关键帧对齐引起的最大额外时长（秒）
+pub const MAX_KEYFRAME_OFFSET: f64 = 10.0;
+
+/// 解码检查最多保留的错误行数
+const MAX_DECODE_ERRORS: usize = 20;
+
+/// 各类型流的数量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamCounts {
+    pub video: u32,
+    pub audio: u32,
+    pub subtitle: u32,
+}
+
+impl StreamCounts {
+    /// 统计 (codec_type, codec_name) 列表中的流数量
+    pub fn from_streams(streams: &[(String, String)]) -> Self {
+        let mut counts = Self::default();
+        for (codec_type, _) in streams {
+            match codec_type.as_str() {
+                "video" => counts.video += 1,
+                "audio" => counts.audio += 1,
+                "subtitle" => counts.subtitle += 1,
+                _ => {}
+            }
+        }
+        counts
+    }
+
+    /// ffmpeg 默认流选择下，剪辑输出应包含的流数量（每种类型最多一路）
+    pub fn expected_output(&self, keep_subtitles: bool) -> Self {
+        Self {
+            video: self.video.min(1),
+            audio: self.audio.min(1),
+            subtitle: if keep_subtitles { self.subtitle.min(1) } else { 0 },
+        }
+    }
+}
+
+/// 剪辑输出的校验报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub passed: bool,
+    pub requested_duration: f64,    // 请求的时长（秒）
+    pub actual_duration: f64,       // 输出文件的实际时长（秒）
+    pub keyframe_offset: f64,       // 实际时长 - 请求时长，通常由关键帧对齐引起
+    pub duration_ok: bool,
+    pub expected_streams: StreamCounts,
+    pub actual_streams: StreamCounts,
+    pub streams_ok: bool,
+    pub decode_checked: bool,
+    pub decode_errors: Vec<String>,
+}
+
+/// 判断时长偏差是否在允许范围内
+///
+/// 流复制的输出从关键帧开始，可能比请求的长；重新编码的输出按帧精确裁剪，只允许少量误差
+pub fn duration_within_tolerance(requested: f64, actual: f64, reencoded: bool) -> bool {
+    let offset = actual - requested;
+    let max_offset = if reencoded { DURATION_TOLERANCE } else { MAX_KEYFRAME_OFFSET + DURATION_TOLERANCE };
+    (-DURATION_TOLERANCE..=max_offset).contains(&offset)
+}
+
+/// 判断输出的流是否满足预期（数量不少于预期）
+pub fn streams_match(expected: &StreamCounts, actual: &StreamCounts) -> bool {
+    actual.video >= expected.video
+        && actual.audio >= expected.audio
+        && actual.subtitle >= expected.subtitle
+}
+
+/// 使用 `-f null` 快速解码整个文件，返回解码错误信息
+pub fn decode_check(backend: &dyn MediaBackend, path: &str) -> AppResult<Vec<String>> {
+    let output = backend.ffmpeg(&["-v", "error", "-i", path, "-f", "null", "-"])?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let mut errors: Vec<String> = stderr.lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .take(MAX_DECODE_ERRORS)
+        .map(|l| l.to_string())
+        .collect();
+
+    if !output.success() && errors.is_empty() {
+        errors.push(t!("verify.decode_failed"));
+    }
+
+    Ok(errors)
+}
+
+/// 探测剪辑输出，校验时长、流和（可选）解码完整性
+///
+/// `reencoded` 为真时输出经过重新编码，时长按严格的误差校验
+pub fn verify_output(
+    backend: &dyn MediaBackend,
+    output_path: &str,
+    requested_duration: f64,
+    expected_streams: StreamCounts,
+    reencoded: bool,
+    check_decode: bool
+) -> AppResult<VerificationReport> {
+    let actual_duration = get_video_duration_with(backend, output_path)?;
+    let actual_streams = StreamCounts::from_streams(&get_stream_codecs_with(backend, output_path)?);
+
+    let duration_ok = duration_within_tolerance(requested_duration, actual_duration, reencoded);
+    let streams_ok = streams_match(&expected_streams, &actual_streams);
+
+    let decode_errors = if check_decode {
+        decode_check(backend, output_path)?
+    } else {
+        Vec::new()
+    };
+
+    Ok(VerificationReport {
+        passed: duration_ok && streams_ok && decode_errors.is_empty(),
+        requested_duration,
+        actual_duration,
+        keyframe_offset: actual_duration - requested_duration,
+        duration_ok,
+        expected_streams,
+        actual_streams,
+        streams_ok,
+        decode_checked: check_decode,
+        decode_errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration_within_tolerance() {
+        assert!(duration_within_tolerance(10.0, 10.0, false));
+        // 关键帧对齐导致的额外时长
+        assert!(duration_within_tolerance(10.0, 13.5, false));
+        // 时长明显不足
+        assert!(!duration_within_tolerance(10.0, 8.0, false));
+        // 时长远超预期
+        assert!(!duration_within_tolerance(10.0, 30.0, false));
+
+        // 重新编码的输出不允许额外时长
+        assert!(duration_within_tolerance(10.0, 10.4, true));
+        assert!(!duration_within_tolerance(10.0, 13.5, true));
+        assert!(!duration_within_tolerance(10.0, 9.4, true));
+    }
+
+    #[test]
+    fn test_expected_streams() {
+        let source = StreamCounts::from_streams(&[
+            ("video".to_string(), "h264".to_string()),
+            ("audio".to_string(), "aac".to_string()),
+            ("audio".to_string(), "ac3".to_string()),
+            ("subtitle".to_string(), "subrip".to_string()),
+            ("data".to_string(), "bin_data".to_string()),
+        ]);
+        assert_eq!(source, StreamCounts { video: 1, audio: 2, subtitle: 1 });
+
+        let expected = source.expected_output(false);
+        assert_eq!(expected, StreamCounts { video: 1, audio: 1, subtitle: 0 });
+
+        assert!(streams_match(&expected, &StreamCounts { video: 1, audio: 1, subtitle: 0 }));
+        assert!(!streams_match(&expected, &StreamCounts { video: 1, audio: 0, subtitle: 0 }));
+    }
+}
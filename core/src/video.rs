@@ -0,0 +1,209 @@
+use serde::{Deserialize, Serialize};
+use crate::audio::AudioAdjustments;
+use crate::loudness::LoudnessReport;
+use crate::verify::VerificationReport;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoInfo {
+    pub duration: f64,      // 时长（秒）
+    pub width: u32,         // 宽度
+    pub height: u32,        // 高度
+    pub fps: f64,           // 帧率
+    pub codec: String,      // 编码格式
+    pub format: String,     // 容器格式
+    pub frame_rate: String, // 精确帧率 (例如 "30000/1001")
+    pub time_base: String,  // 视频流时间基 (例如 "1/30000")
+    pub start_timecode: Option<String>,     // 源起始时间码 (例如 "01:00:00;00")
+    pub rotation: i32,      // 显示矩阵的逆时针旋转角度 (0 / 90 / 180 / 270)
+}
+
+impl VideoInfo {
+    pub fn new(duration: f64, width: u32, height: u32, fps: f64, codec: String, format: String) -> Self {
+        Self {
+            duration,
+            width,
+            height,
+            fps,
+            codec,
+            format,
+            frame_rate: String::new(),
+            time_base: String::new(),
+            start_timecode: None,
+            rotation: 0,
+        }
+    }
+
+    /// 按显示矩阵旋转后的显示尺寸
+    pub fn display_size(&self) -> (u32, u32) {
+        if self.rotation % 180 == 90 {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        }
+    }
+}
+
+
+/// 剪辑结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CutResult {
+    pub output_path: String,                // 输出文件路径
+    pub message: String,                    // 提示信息
+    pub verification: VerificationReport,   // 输出校验报告
+    pub timecode: Option<String>,           // 写入输出文件的起始时间码
+    pub loudness: Option<LoudnessReport>,   // 响度标准化前的测量结果
+    pub audio: Option<AudioAdjustments>,    // 应用的音频处理，未处理时为空
+    pub subtitles: Vec<String>,             // 随剪辑写入的外挂字幕文件
+    pub skipped_subtitles: Vec<SkippedSubtitle>,    // 未能随剪辑写入的外挂字幕
+}
+
+/// 未随剪辑写入的外挂字幕
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkippedSubtitle {
+    pub path: String,       // 源文件旁的字幕文件
+    pub reason: String,     // 跳过原因
+}
+
+/// 静帧导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StillResult {
+    pub output_paths: Vec<String>,  // 输出文件路径，与请求的时间点一一对应
+    pub message: String,            // 提示信息
+}
+
+/// 动图大小估算
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnimationEstimate {
+    pub width: u32,             // 输出宽度
+    pub height: u32,            // 输出高度
+    pub frames: u64,            // 输出帧数
+    pub estimated_bytes: u64,   // 估算的文件大小（字节）
+}
+
+/// 动图导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnimationResult {
+    pub output_path: String,            // 输出文件路径
+    pub message: String,                // 提示信息
+    pub estimate: AnimationEstimate,    // 导出前的大小估算
+    pub width: u32,                     // 探测到的输出宽度
+    pub height: u32,                    // 探测到的输出高度
+    pub frames: u64,                    // 探测到的输出帧数
+    pub matches_estimate: bool,         // 输出尺寸和帧数与估算一致
+}
+
+/// 音频流信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioStream {
+    pub index: u32,                 // 在文件中的流序号 (ffprobe 的 index)
+    pub codec: String,              // 编码格式
+    pub channels: u32,              // 声道数
+    pub sample_rate: u32,           // 采样率
+    pub bit_rate: Option<u64>,      // 码率 (bit/s)，容器未记录时为空
+    pub language: Option<String>,   // 语言标签 (例如 "eng")
+}
+
+/// 字幕流信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubtitleStream {
+    pub index: u32,                 // 在文件中的流序号 (ffprobe 的 index)
+    pub codec: String,              // 编码格式 (例如 "subrip"、"ass"、"hdmv_pgs_subtitle")
+    pub language: Option<String>,   // 语言标签 (例如 "eng")
+    pub title: Option<String>,      // 轨道标题
+}
+
+/// 音频导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioResult {
+    pub output_path: String,    // 输出文件路径
+    pub message: String,        // 提示信息
+    pub codec: String,          // 输出的音频编码
+    pub copied: bool,           // 是否为流复制（未重新编码）
+    pub loudness: Option<LoudnessReport>,   // 响度标准化前的测量结果
+}
+
+/// 多段导出的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JoinMode {
+    Copy,       // 整体流复制
+    Smart,      // 只重新编码两端和转场附近的片段
+    Reencode,   // 整体重新编码
+}
+
+/// 多段导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JoinResult {
+    pub output_path: String,                // 输出文件路径
+    pub message: String,                    // 提示信息
+    pub verification: VerificationReport,   // 输出校验报告
+    pub mode: JoinMode,                     // 处理方式
+    pub reencoded_duration: f64,            // 重新编码的源视频总时长（秒）
+}
+
+/// 几何变换导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransformResult {
+    pub output_path: String,                // 输出文件路径
+    pub message: String,                    // 提示信息
+    pub verification: VerificationReport,   // 输出校验报告
+    pub lossless: bool,                     // 只改写显示矩阵，未重新编码
+    pub expected_width: u32,                // 按选项计算的显示宽度
+    pub expected_height: u32,               // 按选项计算的显示高度
+    pub width: u32,                         // 探测到的输出显示宽度
+    pub height: u32,                        // 探测到的输出显示高度
+    pub dimensions_ok: bool,                // 输出尺寸与计算结果一致
+}
+
+/// 变速和倒放导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeedResult {
+    pub output_path: String,                // 输出文件路径
+    pub message: String,                    // 提示信息
+    pub verification: VerificationReport,   // 输出校验报告（按变速后的时长）
+    pub speed: f64,                         // 速度倍数
+    pub reversed: bool,                     // 是否倒放
+    pub expected_duration: f64,             // 变速后的预期时长（秒）
+    pub chunks: u32,                        // 倒放的分段数，不倒放时为 1
+}
+
+/// 字幕提取结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubtitleResult {
+    pub output_path: String,    // 输出文件路径
+    pub message: String,        // 提示信息
+    pub cues: usize,            // 输出的字幕条数
+}
+
+/// 字幕烧录结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurnResult {
+    pub output_path: String,                // 输出文件路径
+    pub message: String,                    // 提示信息
+    pub verification: VerificationReport,   // 输出校验报告
+}
+
+/// 叠加水印 / 文字的导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayResult {
+    pub output_path: String,                // 输出文件路径
+    pub message: String,                    // 提示信息
+    pub verification: VerificationReport,   // 输出校验报告
+}
+
+/// 调色和修复滤镜的导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrectionResult {
+    pub output_path: String,                // 输出文件路径
+    pub message: String,                    // 提示信息
+    pub verification: VerificationReport,   // 输出校验报告
+    pub filter_chain: String,               // 实际使用的滤镜链
+}
+
+/// 版本文件信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionEntry {
+    pub path: String,           // 文件路径
+    pub versions: Vec<u32>,     // 版本号序列 (例如 video_1_2.mp4 -> [1, 2])
+    pub notes: Option<String>,  // 备注
+}
@@ -0,0 +1,179 @@
+//! 集成测试的公共工具：用 ffmpeg 的 lavfi 源 (testsrc / sine) 生成小体积测试素材
+//!
+//! 未安装 ffmpeg / ffprobe 时，测试通过 [`require_ffmpeg!`] 直接跳过
+
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use instant_cut_core::binaries::{self, Tool};
+use instant_cut_core::capabilities;
+use instant_cut_core::utils::{execute_ffmpeg, check_command_success};
+
+/// 测试素材的时长（秒）
+pub const CLIP_DURATION: f64 = 6.0;
+
+/// 测试素材的帧率
+pub const CLIP_FPS: u32 = 25;
+
+/// 黑边素材上下黑边的高度（像素），与宏块对齐，避免编码后边缘模糊
+pub const LETTERBOX_BAR: u32 = 32;
+
+/// 未找到 ffmpeg / ffprobe 时跳过当前测试
+macro_rules! require_ffmpeg {
+    () => {
+        if !crate::common::ffmpeg_available() {
+            eprintln!("跳过：未找到 ffmpeg / ffprobe");
+            return;
+        }
+    };
+}
+
+/// ffmpeg 和 ffprobe 是否都可用
+pub fn ffmpeg_available() -> bool {
+    binaries::locate(Tool::FFmpeg).is_some() && binaries::locate(Tool::FFprobe).is_some()
+}
+
+/// 测试素材的描述
+#[derive(Debug, Clone, Copy)]
+pub struct ClipSpec {
+    pub name: &'static str,
+    pub ext: &'static str,
+    pub video_codec: &'static str,
+    pub audio_codec: Option<&'static str>,
+    pub subtitle_codec: Option<&'static str>,
+    /// 关键帧间隔（帧）
+    pub gop: u32,
+    /// 写入的起始时间码
+    pub timecode: Option<&'static str>,
+}
+
+impl ClipSpec {
+    /// 需要的编码器
+    fn encoders(&self) -> Vec<&'static str> {
+        let mut list = vec![self.video_codec];
+        list.extend(self.audio_codec);
+        list.extend(self.subtitle_codec);
+        list
+    }
+
+    /// (视频, 音频, 字幕) 流数量
+    pub fn stream_counts(&self) -> (u32, u32, u32) {
+        (1, self.audio_codec.is_some() as u32, self.subtitle_codec.is_some() as u32)
+    }
+}
+
+/// 覆盖常见容器、编码、GOP 和流布局的素材
+pub const CLIPS: &[ClipSpec] = &[
+    ClipSpec { name: "h264_aac", ext: "mp4", video_codec: "libx264", audio_codec: Some("aac"), subtitle_codec: None, gop: 25, timecode: None },
+    ClipSpec { name: "h264_long_gop", ext: "mp4", video_codec: "libx264", audio_codec: Some("aac"), subtitle_codec: None, gop: 250, timecode: None },
+    ClipSpec { name: "mpeg4_video_only", ext: "mp4", video_codec: "mpeg4", audio_codec: None, subtitle_codec: None, gop: 12, timecode: None },
+    ClipSpec { name: "h264_subs", ext: "mkv", video_codec: "libx264", audio_codec: Some("aac"), subtitle_codec: Some("subrip"), gop: 25, timecode: None },
+    ClipSpec { name: "vp9_opus", ext: "webm", video_codec: "libvpx-vp9", audio_codec: Some("libopus"), subtitle_codec: None, gop: 25, timecode: None },
+    ClipSpec { name: "mpeg2_mp2", ext: "ts", video_codec: "mpeg2video", audio_codec: Some("mp2"), subtitle_codec: None, gop: 15, timecode: None },
+    ClipSpec { name: "mpeg4_mp3", ext: "mov", video_codec: "mpeg4", audio_codec: Some("libmp3lame"), subtitle_codec: None, gop: 50, timecode: None },
+    ClipSpec { name: "h264_timecode", ext: "mov", video_codec: "libx264", audio_codec: Some("aac"), subtitle_codec: None, gop: 25, timecode: Some("10:00:00:00") },
+];
+
+/// 每个测试独立的临时目录，结束时删除
+pub struct Fixture {
+    pub dir: PathBuf,
+}
+
+impl Fixture {
+    pub fn new(test_name: &str) -> Self {
+        let dir = std::env::temp_dir()
+            .join(format!("instant-cut-it-{}-{}", test_name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("创建临时目录失败");
+        Self { dir }
+    }
+
+    /// 生成素材，当前 ffmpeg 缺少所需编码器时返回 `None`
+    pub fn clip(&self, spec: &ClipSpec) -> Option<PathBuf> {
+        let caps = capabilities::capabilities().expect("读取 ffmpeg 能力失败");
+        if let Some(missing) = spec.encoders().into_iter().find(|e| !caps.has_encoder(e)) {
+            eprintln!("跳过素材 {}：缺少编码器 {}", spec.name, missing);
+            return None;
+        }
+
+        let output = self.dir.join(format!("{}.{}", spec.name, spec.ext));
+        let subtitle_file = self.dir.join(format!("{}.srt", spec.name));
+        generate(spec, &output, &subtitle_file);
+        Some(output)
+    }
+
+    /// 上下各有 [`LETTERBOX_BAR`] 像素黑边的 320x240 H.264 素材，缺少 libx264 时返回 `None`
+    pub fn letterboxed_clip(&self) -> Option<PathBuf> {
+        let caps = capabilities::capabilities().expect("读取 ffmpeg 能力失败");
+        if !caps.has_encoder("libx264") {
+            eprintln!("跳过黑边素材：缺少编码器 libx264");
+            return None;
+        }
+
+        let output = self.dir.join("letterbox.mp4");
+        let video_source = format!(
+            "color=c=gray:size=320x{}:rate={}:duration={},pad=320:240:0:{}:black",
+            240 - 2 * LETTERBOX_BAR, CLIP_FPS, CLIP_DURATION, LETTERBOX_BAR
+        );
+        let args = ["-v", "error", "-y", "-f", "lavfi", "-i", &video_source,
+            "-c:v", "libx264", "-g", "25", "-pix_fmt", "yuv420p", output.to_str().expect("临时路径不是 UTF-8")];
+        let result = execute_ffmpeg(&args).expect("启动 ffmpeg 失败");
+        check_command_success(&result, "ffmpeg").expect("生成测试素材失败");
+        Some(output)
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// 调用 ffmpeg 生成素材
+fn generate(spec: &ClipSpec, output: &Path, subtitle_file: &Path) {
+    let duration = CLIP_DURATION.to_string();
+    let video_source = format!("testsrc=size=320x240:rate={}:duration={}", CLIP_FPS, duration);
+    let audio_source = format!("sine=frequency=440:sample_rate=48000:duration={}", duration);
+    let gop = spec.gop.to_string();
+
+    let mut args: Vec<String> = ["-v", "error", "-y", "-f", "lavfi", "-i", &video_source]
+        .iter().map(|s| s.to_string()).collect();
+    let mut maps = vec!["0:v".to_string()];
+
+    if spec.audio_codec.is_some() {
+        args.extend(["-f", "lavfi", "-i", &audio_source].iter().map(|s| s.to_string()));
+        maps.push("1:a".to_string());
+    }
+    if spec.subtitle_codec.is_some() {
+        fs::write(subtitle_file, srt(CLIP_DURATION as u32)).expect("写入字幕失败");
+        args.extend(["-i".to_string(), subtitle_file.to_string_lossy().to_string()]);
+        maps.push(format!("{}:s", maps.len()));
+    }
+
+    for map in maps {
+        args.extend(["-map".to_string(), map]);
+    }
+    args.extend(["-c:v", spec.video_codec, "-g", &gop, "-pix_fmt", "yuv420p"].iter().map(|s| s.to_string()));
+    if let Some(codec) = spec.audio_codec {
+        args.extend(["-c:a".to_string(), codec.to_string()]);
+    }
+    if let Some(codec) = spec.subtitle_codec {
+        args.extend(["-c:s".to_string(), codec.to_string()]);
+    }
+    if let Some(timecode) = spec.timecode {
+        args.extend(["-timecode".to_string(), timecode.to_string()]);
+    }
+    args.push(output.to_string_lossy().to_string());
+
+    let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    let result = execute_ffmpeg(&args).expect("启动 ffmpeg 失败");
+    check_command_success(&result, "ffmpeg").expect("生成测试素材失败");
+}
+
+/// 每秒一条字幕
+fn srt(seconds: u32) -> String {
+    (0..seconds)
+        .map(|i| format!("{}\n00:00:{:02},000 --> 00:00:{:02},900\nline {}\n\n", i + 1, i, i, i + 1))
+        .collect()
+}
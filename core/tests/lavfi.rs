@@ -0,0 +1,448 @@
+//! 使用 lavfi 合成素材的端到端测试：探测、剪辑和输出校验
+//!
+//! 需要本机安装 ffmpeg / ffprobe，未安装时各测试直接跳过
+
+#[macro_use]
+mod common;
+
+use std::path::Path;
+use instant_cut_core::animation::{self, AnimationOptions};
+use instant_cut_core::audio::{self, AudioAdjustments, AudioOptions};
+use instant_cut_core::backend::ProcessBackend;
+use instant_cut_core::correction::{self, CorrectionFilter};
+use instant_cut_core::cropdetect;
+use instant_cut_core::join::{self, JoinOptions, TimeRange};
+use instant_cut_core::loudness::{self, LoudnessPreset};
+use instant_cut_core::media;
+use instant_cut_core::overlay::{self, ImageWatermark, OverlayOptions, Position};
+use instant_cut_core::speed::{self, SpeedOptions};
+use instant_cut_core::still::{self, StillOptions};
+use instant_cut_core::subtitle::{self, SubtitleFormat};
+use instant_cut_core::timestamp::TimePoint;
+use instant_cut_core::transform::{self, CropRect, TransformOptions};
+use instant_cut_core::verify::{self, StreamCounts, DURATION_TOLERANCE};
+use instant_cut_core::video::JoinMode;
+use common::{Fixture, ClipSpec, CLIPS, CLIP_DURATION, LETTERBOX_BAR};
+
+fn find_clip(name: &str) -> &'static ClipSpec {
+    CLIPS.iter().find(|c| c.name == name).expect("未知的测试素材")
+}
+
+fn counts(spec: &ClipSpec) -> StreamCounts {
+    let (video, audio, subtitle) = spec.stream_counts();
+    StreamCounts { video, audio, subtitle }
+}
+
+fn secs(seconds: f64) -> TimePoint {
+    TimePoint::seconds(seconds)
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().expect("临时路径不是 UTF-8")
+}
+
+#[test]
+fn probe_reports_duration_and_streams() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("probe");
+
+    for spec in CLIPS {
+        let Some(clip) = fixture.clip(spec) else { continue };
+
+        let info = media::get_video_info(path_str(&clip)).unwrap();
+        assert!((info.duration - CLIP_DURATION).abs() < DURATION_TOLERANCE, "{}: 时长 {}", spec.name, info.duration);
+        assert_eq!((info.width, info.height), (320, 240), "{}", spec.name);
+        assert!((info.fps - 25.0).abs() < 0.01, "{}: 帧率 {}", spec.name, info.fps);
+        assert_eq!(info.frame_rate, "25/1", "{}", spec.name);
+
+        let streams = StreamCounts::from_streams(&media::get_stream_codecs(path_str(&clip)).unwrap());
+        assert_eq!(streams, counts(spec), "{}", spec.name);
+    }
+}
+
+#[test]
+fn cut_passes_verification_in_every_layout() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("cut");
+
+    for spec in CLIPS {
+        let Some(clip) = fixture.clip(spec) else { continue };
+
+        let result = media::cut_video(path_str(&clip), &secs(1.0), &secs(4.0), None, None, None, &AudioAdjustments::default(), true).unwrap();
+        let report = &result.verification;
+        assert!(report.passed, "{}: {:?}", spec.name, report);
+        assert!(report.decode_checked && report.decode_errors.is_empty(), "{}", spec.name);
+        assert_eq!(report.actual_streams, counts(spec), "{}", spec.name);
+
+        let expected_name = format!("{}_1.{}", spec.name, spec.ext);
+        assert!(result.output_path.ends_with(&expected_name), "{}", result.output_path);
+    }
+}
+
+#[test]
+fn short_gop_cut_is_close_to_requested_duration() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("short-gop");
+    let Some(clip) = fixture.clip(find_clip("h264_aac")) else { return };
+
+    // 每秒一个关键帧，起点正好落在关键帧上
+    let result = media::cut_video(path_str(&clip), &secs(2.0), &secs(5.0), None, None, None, &AudioAdjustments::default(), false).unwrap();
+    assert!(result.verification.keyframe_offset.abs() < DURATION_TOLERANCE, "{:?}", result.verification);
+}
+
+#[test]
+fn frame_and_timecode_addressing_match_seconds() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("frames");
+    let Some(clip) = fixture.clip(find_clip("h264_aac")) else { return };
+
+    // 25 fps 下第 50 帧 = 00:00:02:00 = 2 秒
+    let by_frame = media::resolve_time(path_str(&clip), &"50f".parse().unwrap()).unwrap();
+    assert_eq!(by_frame.seconds, 2.0);
+    assert_eq!(by_frame.timecode.as_deref(), Some("00:00:02:00"));
+
+    let start: TimePoint = "00:00:02:00".parse().unwrap();
+    let end: TimePoint = "125f".parse().unwrap();
+    let result = media::cut_video(path_str(&clip), &start, &end, None, None, None, &AudioAdjustments::default(), false).unwrap();
+    assert_eq!(result.verification.requested_duration, 3.0);
+    assert!(result.verification.keyframe_offset.abs() < DURATION_TOLERANCE, "{:?}", result.verification);
+}
+
+#[test]
+fn source_timecode_is_read_and_offset_in_output() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("timecode");
+    let Some(clip) = fixture.clip(find_clip("h264_timecode")) else { return };
+    let clip = path_str(&clip);
+
+    let info = media::get_video_info(clip).unwrap();
+    assert_eq!(info.start_timecode.as_deref(), Some("10:00:00:00"));
+
+    // 时间码按源时间码解释，早于源起点的时间码被拒绝
+    assert!(media::resolve_time(clip, &"00:00:02:00".parse().unwrap()).is_err());
+
+    let start: TimePoint = "10:00:02:00".parse().unwrap();
+    let end: TimePoint = "10:00:04:00".parse().unwrap();
+    let result = media::cut_video(clip, &start, &end, None, None, None, &AudioAdjustments::default(), false).unwrap();
+    assert_eq!(result.verification.requested_duration, 2.0);
+    assert_eq!(result.timecode.as_deref(), Some("10:00:02:00"));
+
+    let output = media::get_video_info(&result.output_path).unwrap();
+    assert_eq!(output.start_timecode.as_deref(), Some("10:00:02:00"));
+}
+
+#[test]
+fn long_gop_cut_reports_keyframe_offset() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("long-gop");
+    let Some(clip) = fixture.clip(find_clip("h264_long_gop")) else { return };
+
+    // 只有第 0 帧是关键帧，流复制会从 0 秒开始，输出比请求的更长
+    let result = media::cut_video(path_str(&clip), &secs(2.0), &secs(4.0), None, None, None, &AudioAdjustments::default(), false).unwrap();
+    let report = &result.verification;
+    assert!(report.duration_ok, "{:?}", report);
+    assert!(report.keyframe_offset > 1.0, "{:?}", report);
+}
+
+#[test]
+fn cut_into_mp4_drops_unsupported_subtitles() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("subs");
+    let spec = find_clip("h264_subs");
+    let Some(clip) = fixture.clip(spec) else { return };
+
+    let result = media::cut_video(path_str(&clip), &secs(0.0), &secs(3.0), None, None, Some("mp4"), &AudioAdjustments::default(), false).unwrap();
+    assert!(result.output_path.ends_with("h264_subs_1.mp4"), "{}", result.output_path);
+    assert!(result.verification.passed, "{:?}", result.verification);
+    assert_eq!(result.verification.actual_streams, StreamCounts { video: 1, audio: 1, subtitle: 0 });
+}
+
+#[test]
+fn cut_into_incompatible_container_is_rejected() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("incompatible");
+    let Some(clip) = fixture.clip(find_clip("h264_aac")) else { return };
+
+    // h264 / aac 不能流复制到 webm
+    assert!(media::cut_video(path_str(&clip), &secs(0.0), &secs(3.0), None, None, Some("webm"), &AudioAdjustments::default(), false).is_err());
+    assert_eq!(std::fs::read_dir(&fixture.dir).unwrap().count(), 1);
+}
+
+#[test]
+fn repeated_cuts_create_nested_versions() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("versions");
+    let Some(clip) = fixture.clip(find_clip("h264_aac")) else { return };
+    let clip = path_str(&clip);
+
+    let first = media::cut_video(clip, &secs(0.0), &secs(3.0), None, None, None, &AudioAdjustments::default(), false).unwrap();
+    let second = media::cut_video(clip, &secs(3.0), &secs(6.0), Some("tail"), None, None, &AudioAdjustments::default(), false).unwrap();
+    let nested = media::cut_video(&first.output_path, &secs(1.0), &secs(2.0), None, None, None, &AudioAdjustments::default(), false).unwrap();
+
+    assert!(first.output_path.ends_with("h264_aac_1.mp4"));
+    assert!(second.output_path.ends_with("h264_aac_2_tail.mp4"));
+    assert!(nested.output_path.ends_with("h264_aac_1_1.mp4"));
+
+    let versions: Vec<Vec<u32>> = media::list_versions(clip, None, None).unwrap()
+        .into_iter().map(|v| v.versions).collect();
+    assert_eq!(versions, vec![vec![1], vec![1, 1], vec![2]]);
+}
+
+#[test]
+fn stills_are_exported_at_requested_times() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("stills");
+    let Some(clip) = fixture.clip(find_clip("h264_aac")) else { return };
+    let clip = path_str(&clip);
+
+    let options = StillOptions { format: Some("png".to_string()), width: Some(160), height: None };
+    let times = [secs(1.0), "100f".parse().unwrap(), secs(0.0)];
+    let result = still::export_stills(clip, &times, &options, None, None).unwrap();
+
+    assert_eq!(result.output_paths.len(), 3);
+    for (i, path) in result.output_paths.iter().enumerate() {
+        assert!(path.ends_with(&format!("h264_aac_{}.png", i + 1)), "{}", path);
+        let info = media::get_video_info(path).unwrap();
+        assert_eq!((info.width, info.height), (160, 120));
+    }
+}
+
+#[test]
+fn animations_are_exported_at_requested_size() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("animation");
+    let Some(clip) = fixture.clip(find_clip("h264_aac")) else { return };
+    let clip = path_str(&clip);
+
+    let gif = AnimationOptions { fps: Some(10), width: Some(160), ..Default::default() };
+    let result = animation::export_animation(clip, &secs(1.0), &secs(3.0), &gif, None, None).unwrap();
+    assert!(result.output_path.ends_with("h264_aac_1.gif"), "{}", result.output_path);
+    assert_eq!(result.estimate.frames, 20);
+    assert!(result.matches_estimate, "{}", result.message);
+
+    let info = media::get_video_info(&result.output_path).unwrap();
+    assert_eq!((info.width, info.height), (160, 120));
+
+    // 动态 WebP 依赖 libwebp 编码器
+    let caps = instant_cut_core::capabilities::capabilities().unwrap();
+    if caps.has_encoder("libwebp") {
+        let webp = AnimationOptions { format: Some("webp".to_string()), ..Default::default() };
+        let result = animation::export_animation(clip, &secs(0.0), &secs(2.0), &webp, None, None).unwrap();
+        assert!(result.output_path.ends_with("h264_aac_2.webp"), "{}", result.output_path);
+    }
+}
+
+#[test]
+fn audio_is_copied_or_transcoded() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("audio");
+    let Some(clip) = fixture.clip(find_clip("h264_aac")) else { return };
+    let clip = path_str(&clip);
+
+    let streams = media::get_audio_streams(clip).unwrap();
+    assert_eq!(streams.len(), 1);
+    assert_eq!((streams[0].index, streams[0].codec.as_str()), (1, "aac"));
+
+    // AAC 直接复制到 m4a
+    let copied = audio::extract_audio(clip, None, None, &AudioOptions::default(), None, None).unwrap();
+    assert!(copied.output_path.ends_with("h264_aac_1.m4a"), "{}", copied.output_path);
+    assert!(copied.copied);
+    let duration = media::get_video_duration(&copied.output_path).unwrap();
+    assert!((duration - CLIP_DURATION).abs() < DURATION_TOLERANCE, "时长 {}", duration);
+
+    // 转码为 FLAC，只导出一段
+    let options = AudioOptions { format: Some("flac".to_string()), track: Some(1), ..Default::default() };
+    let flac = audio::extract_audio(clip, Some(&secs(1.0)), Some(&secs(3.0)), &options, None, None).unwrap();
+    assert!(!flac.copied);
+    let duration = media::get_video_duration(&flac.output_path).unwrap();
+    assert!((duration - 2.0).abs() < DURATION_TOLERANCE, "时长 {}", duration);
+}
+
+#[test]
+fn loudness_is_measured_and_normalized() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("loudness");
+    let Some(clip) = fixture.clip(find_clip("h264_aac")) else { return };
+    let clip = path_str(&clip);
+
+    let before = loudness::measure_loudness(clip, None, None, None, LoudnessPreset::Podcast).unwrap();
+    assert!(before.input_i.is_finite());
+
+    let podcast = AudioAdjustments { loudness: Some(LoudnessPreset::Podcast), ..Default::default() };
+    let result = media::cut_video(clip, &secs(1.0), &secs(5.0), None, None, None, &podcast, false).unwrap();
+    assert!(result.verification.passed, "{:?}", result.verification);
+    assert_eq!(result.loudness.as_ref().map(|r| r.input_i.is_finite()), Some(true));
+
+    // 正弦波测试音标准化后应接近 -16 LUFS
+    let after = loudness::measure_loudness(&result.output_path, None, None, None, LoudnessPreset::Podcast).unwrap();
+    assert!((after.input_i + 16.0).abs() < 1.0, "标准化后 {} LUFS", after.input_i);
+}
+
+#[test]
+fn audio_adjustments_reencode_audio_only() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("adjust");
+    let Some(clip) = fixture.clip(find_clip("h264_aac")) else { return };
+    let clip = path_str(&clip);
+
+    let audio = AudioAdjustments { gain_db: Some(-6.0), fade_out: Some(1.0), downmix: Some(audio::Downmix::Mono), ..Default::default() };
+    let result = media::cut_video(clip, &secs(1.0), &secs(4.0), None, None, None, &audio, false).unwrap();
+    assert!(result.verification.passed, "{:?}", result.verification);
+    assert_eq!(result.audio.as_ref(), Some(&audio));
+
+    let streams = media::get_audio_streams(&result.output_path).unwrap();
+    assert_eq!(streams[0].channels, 1);
+
+    // 只移除音轨时流复制，输出没有音频
+    let dropped = AudioAdjustments { drop_tracks: vec![1], ..Default::default() };
+    let result = media::cut_video(clip, &secs(1.0), &secs(4.0), None, None, None, &dropped, false).unwrap();
+    assert_eq!(result.verification.actual_streams, StreamCounts { video: 1, audio: 0, subtitle: 0 });
+}
+
+#[test]
+fn ranges_are_joined_with_transitions() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("join");
+    let Some(clip) = fixture.clip(find_clip("h264_aac")) else { return };
+    let clip = path_str(&clip);
+    let ranges = [
+        TimeRange { start: secs(0.0), end: secs(2.0) },
+        TimeRange { start: secs(3.0), end: secs(5.0) },
+    ];
+
+    // 没有效果时整体流复制
+    let result = join::export_ranges(clip, &ranges, &JoinOptions::default(), None, None, None).unwrap();
+    assert_eq!(result.mode, JoinMode::Copy);
+    assert!(result.verification.passed, "{:?}", result.verification);
+
+    // 转场使输出缩短一个转场时长
+    let options = JoinOptions {
+        fade_in: Some(0.5), transition: Some("dissolve".to_string()), transition_duration: Some(0.5), ..Default::default()
+    };
+    let result = join::export_ranges(clip, &ranges, &options, None, None, None).unwrap();
+    assert_ne!(result.mode, JoinMode::Copy);
+    assert!((result.verification.actual_duration - 3.5).abs() < DURATION_TOLERANCE, "{:?}", result.verification);
+
+    // H.264 + AAC 的素材只重新编码转场附近，拼接后能完整解码
+    if instant_cut_core::capabilities::capabilities().unwrap().has_encoder("libx264") {
+        assert_eq!(result.mode, JoinMode::Smart);
+        assert!(result.reencoded_duration < 4.0, "{}", result.reencoded_duration);
+        let report = verify::verify_output(
+            &ProcessBackend, &result.output_path, 3.5, StreamCounts { video: 1, audio: 1, subtitle: 0 }, true, true
+        ).unwrap();
+        assert!(report.passed, "{:?}", report);
+    }
+}
+
+#[test]
+fn transforms_match_the_probed_size() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("transform");
+    let Some(clip) = fixture.clip(find_clip("h264_aac")) else { return };
+    let clip = path_str(&clip);
+
+    let letterbox = TransformOptions {
+        width: Some(180), height: Some(320), pad_color: Some("black".to_string()), ..Default::default()
+    };
+    let result = transform::transform_video(clip, Some(&secs(1.0)), Some(&secs(3.0)), &letterbox, None, None, None).unwrap();
+    assert!(!result.lossless);
+    assert!(result.dimensions_ok && result.verification.passed, "{}", result.message);
+    assert_eq!((result.width, result.height), (180, 320));
+
+    // 只旋转时改写显示矩阵
+    let rotate = TransformOptions { rotate: Some(90), ..Default::default() };
+    let result = transform::transform_video(clip, None, None, &rotate, None, None, None).unwrap();
+    assert!(result.lossless, "{}", result.message);
+    assert!(result.dimensions_ok, "{}", result.message);
+}
+
+#[test]
+fn letterbox_is_detected() {
+    require_ffmpeg!();
+    if !instant_cut_core::capabilities::capabilities().unwrap().features.cropdetect {
+        return;
+    }
+    let fixture = Fixture::new("cropdetect");
+    let Some(clip) = fixture.letterboxed_clip() else { return };
+
+    let detection = cropdetect::detect_crop(path_str(&clip), None, None).unwrap();
+    assert_eq!(detection.crop, Some(CropRect { width: 320, height: 240 - 2 * LETTERBOX_BAR, x: Some(0), y: Some(LETTERBOX_BAR) }));
+    assert_eq!(detection.confidence, 1.0);
+    assert_eq!(detection.samples, CLIP_DURATION as u32);
+    assert_eq!((detection.source_width, detection.source_height), (320, 240));
+}
+
+#[test]
+fn speed_changes_scale_the_duration() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("speed");
+    let Some(clip) = fixture.clip(find_clip("h264_aac")) else { return };
+    let clip = path_str(&clip);
+
+    let fast = SpeedOptions { speed: Some(4.0), ..Default::default() };
+    let result = speed::export_speed(clip, &secs(0.0), &secs(4.0), &fast, None, None, None).unwrap();
+    assert!(result.verification.passed, "{:?}", result.verification);
+    assert!((result.verification.actual_duration - 1.0).abs() < DURATION_TOLERANCE);
+
+    let reversed = SpeedOptions { speed: Some(0.5), reverse: true, ..Default::default() };
+    let result = speed::export_speed(clip, &secs(1.0), &secs(3.0), &reversed, None, None, None).unwrap();
+    assert!(result.verification.passed, "{:?}", result.verification);
+    assert!((result.verification.actual_duration - 4.0).abs() < DURATION_TOLERANCE);
+    assert_eq!(result.verification.actual_streams.audio, 1);
+}
+
+#[test]
+fn subtitles_follow_cuts_and_extract_as_text() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("subtitles");
+    let Some(clip) = fixture.clip(find_clip("h264_subs")) else { return };
+    let clip = path_str(&clip);
+
+    // 素材旁的 h264_subs.srt 随剪辑裁剪，第 1 秒正好是关键帧
+    let result = media::cut_video(clip, &secs(1.0), &secs(4.0), None, None, None, &AudioAdjustments::default(), false).unwrap();
+    assert_eq!(result.subtitles.len(), 1);
+    assert!(result.subtitles[0].ends_with("h264_subs_1.srt"), "{:?}", result.subtitles);
+    let sidecar = std::fs::read_to_string(&result.subtitles[0]).unwrap();
+    assert!(sidecar.starts_with("1\n00:00:00,000 --> 00:00:00,900\nline 1\n"), "{}", sidecar);
+
+    let extracted = subtitle::extract_subtitles(clip, Some(&secs(1.0)), Some(&secs(4.0)), None, SubtitleFormat::Srt, None, None).unwrap();
+    assert_eq!(extracted.cues, 3);
+}
+
+#[test]
+fn watermarks_are_overlaid() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("overlay");
+    let Some(clip) = fixture.clip(find_clip("h264_aac")) else { return };
+    let clip = path_str(&clip);
+
+    // 用导出的静帧作为水印图片
+    let still_options = StillOptions { format: Some("png".to_string()), width: Some(64), height: None };
+    let logo = still::export_stills(clip, &[secs(0.0)], &still_options, Some("logo"), None).unwrap().output_paths.remove(0);
+
+    let options = OverlayOptions {
+        watermark: Some(ImageWatermark {
+            path: logo, position: Position::TopRight, margin: 8, scale: Some(0.25), opacity: 0.5,
+        }),
+        text: None,
+    };
+    let result = overlay::export_overlay(clip, &secs(1.0), &secs(3.0), &options, None, None, None).unwrap();
+    assert!(result.verification.passed, "{:?}", result.verification);
+    assert_eq!(result.verification.actual_streams, StreamCounts { video: 1, audio: 1, subtitle: 0 });
+}
+
+#[test]
+fn correction_filters_are_applied_in_order() {
+    require_ffmpeg!();
+    let fixture = Fixture::new("correction");
+    let Some(clip) = fixture.clip(find_clip("h264_aac")) else { return };
+    let clip = path_str(&clip);
+
+    let filters: Vec<CorrectionFilter> = ["deinterlace", "denoise=3", "eq=contrast=1.1:saturation=1.2"]
+        .iter()
+        .map(|f| f.parse().unwrap())
+        .collect();
+    let result = correction::correct_video(clip, Some(&secs(1.0)), Some(&secs(3.0)), &filters, None, None, None).unwrap();
+    assert!(result.verification.passed, "{:?}", result.verification);
+    assert!(result.filter_chain.starts_with("yadif=mode=send_frame,hqdn3d="), "{}", result.filter_chain);
+}
@@ -1,35 +1,291 @@
 // 防止在 Windows 发布版本中出现额外的控制台窗口
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod video;
-mod error;
-mod utils;
-mod media;
-
-use video::VideoInfo;
+use tauri::Manager;
+use instant_cut_core::{animation, audio, correction, cropdetect, join, loudness, media, output, overlay, settings, speed, still, subtitle, t, transform};
+use instant_cut_core::error::AppResult;
+use instant_cut_core::settings::Settings;
+use instant_cut_core::binaries::FFmpegStatus;
+use instant_cut_core::video::{VideoInfo, CutResult, StillResult, AnimationEstimate, AnimationResult, AudioStream, AudioResult, JoinResult, SpeedResult, TransformResult, SubtitleStream, SubtitleResult, BurnResult, OverlayResult, CorrectionResult};
+use instant_cut_core::still::StillOptions;
+use instant_cut_core::animation::AnimationOptions;
+use instant_cut_core::audio::{AudioAdjustments, AudioOptions};
+use instant_cut_core::join::{JoinOptions, TimeRange};
+use instant_cut_core::loudness::{LoudnessPreset, LoudnessReport};
+use instant_cut_core::timestamp::{ResolvedTime, TimePoint};
+use instant_cut_core::transform::TransformOptions;
+use instant_cut_core::cropdetect::CropDetection;
+use instant_cut_core::overlay::OverlayOptions;
+use instant_cut_core::correction::CorrectionFilter;
+use instant_cut_core::speed::SpeedOptions;
+use instant_cut_core::subtitle::{SubtitleFormat, SubtitleSource};
 
 #[tauri::command]
-fn check_ffmpeg() -> Result<bool, String> {
+fn check_ffmpeg() -> AppResult<FFmpegStatus> {
     media::check_ffmpeg_installed()
 }
 
 #[tauri::command]
-fn get_video_info(path: String) -> Result<VideoInfo, String> {
+fn get_video_info(path: String) -> AppResult<VideoInfo> {
     media::get_video_info(&path)
 }
 
 #[tauri::command]
-fn cut_video(input: String, start: f64, end: f64, notes: Option<String>) -> Result<String, String> {
-    media::cut_video(&input, start, end, notes.as_deref())
+fn resolve_time(path: String, time: TimePoint) -> AppResult<ResolvedTime> {
+    media::resolve_time(&path, &time)
+}
+
+#[tauri::command]
+fn cut_video(
+    input: String,
+    start: TimePoint,
+    end: TimePoint,
+    notes: Option<String>,
+    output_dir: Option<String>,
+    container: Option<String>,
+    audio: Option<AudioAdjustments>,
+    verify_decode: Option<bool>
+) -> AppResult<CutResult> {
+    media::cut_video(
+        &input, &start, &end, notes.as_deref(), output_dir.as_deref(), container.as_deref(),
+        &audio.unwrap_or_default(), verify_decode.unwrap_or(false)
+    )
+}
+
+#[tauri::command]
+fn export_ranges(
+    input: String,
+    ranges: Vec<TimeRange>,
+    options: Option<JoinOptions>,
+    notes: Option<String>,
+    output_dir: Option<String>,
+    container: Option<String>
+) -> AppResult<JoinResult> {
+    join::export_ranges(
+        &input, &ranges, &options.unwrap_or_default(), notes.as_deref(), output_dir.as_deref(), container.as_deref()
+    )
+}
+
+#[tauri::command]
+fn transform_video(
+    input: String,
+    start: Option<TimePoint>,
+    end: Option<TimePoint>,
+    options: TransformOptions,
+    notes: Option<String>,
+    output_dir: Option<String>,
+    container: Option<String>
+) -> AppResult<TransformResult> {
+    transform::transform_video(
+        &input, start.as_ref(), end.as_ref(), &options, notes.as_deref(), output_dir.as_deref(), container.as_deref()
+    )
+}
+
+#[tauri::command]
+fn export_speed(
+    input: String,
+    start: TimePoint,
+    end: TimePoint,
+    options: SpeedOptions,
+    notes: Option<String>,
+    output_dir: Option<String>,
+    container: Option<String>
+) -> AppResult<SpeedResult> {
+    speed::export_speed(
+        &input, &start, &end, &options, notes.as_deref(), output_dir.as_deref(), container.as_deref()
+    )
+}
+
+#[tauri::command]
+fn export_overlay(
+    input: String,
+    start: TimePoint,
+    end: TimePoint,
+    options: Option<OverlayOptions>,
+    preset: Option<String>,
+    notes: Option<String>,
+    output_dir: Option<String>,
+    container: Option<String>
+) -> AppResult<OverlayResult> {
+    // 指定预设名时使用设置中保存的预设
+    let options = match preset {
+        Some(name) => overlay::find_preset(&name)?,
+        None => options.unwrap_or_default(),
+    };
+    overlay::export_overlay(
+        &input, &start, &end, &options, notes.as_deref(), output_dir.as_deref(), container.as_deref()
+    )
+}
+
+#[tauri::command]
+fn correct_video(
+    input: String,
+    start: Option<TimePoint>,
+    end: Option<TimePoint>,
+    filters: Vec<CorrectionFilter>,
+    notes: Option<String>,
+    output_dir: Option<String>,
+    container: Option<String>
+) -> AppResult<CorrectionResult> {
+    correction::correct_video(
+        &input, start.as_ref(), end.as_ref(), &filters, notes.as_deref(), output_dir.as_deref(), container.as_deref()
+    )
+}
+
+#[tauri::command]
+fn detect_crop(input: String, start: Option<TimePoint>, end: Option<TimePoint>) -> AppResult<CropDetection> {
+    cropdetect::detect_crop(&input, start.as_ref(), end.as_ref())
+}
+
+#[tauri::command]
+fn export_stills(
+    input: String,
+    times: Vec<TimePoint>,
+    options: Option<StillOptions>,
+    notes: Option<String>,
+    output_dir: Option<String>
+) -> AppResult<StillResult> {
+    still::export_stills(
+        &input, &times, &options.unwrap_or_default(), notes.as_deref(), output_dir.as_deref()
+    )
+}
+
+#[tauri::command]
+fn estimate_animation(
+    input: String,
+    start: TimePoint,
+    end: TimePoint,
+    options: Option<AnimationOptions>
+) -> AppResult<AnimationEstimate> {
+    animation::estimate_animation(&input, &start, &end, &options.unwrap_or_default())
+}
+
+#[tauri::command]
+fn export_animation(
+    input: String,
+    start: TimePoint,
+    end: TimePoint,
+    options: Option<AnimationOptions>,
+    notes: Option<String>,
+    output_dir: Option<String>
+) -> AppResult<AnimationResult> {
+    animation::export_animation(
+        &input, &start, &end, &options.unwrap_or_default(), notes.as_deref(), output_dir.as_deref()
+    )
+}
+
+#[tauri::command]
+fn get_audio_streams(path: String) -> AppResult<Vec<AudioStream>> {
+    media::get_audio_streams(&path)
+}
+
+#[tauri::command]
+fn extract_audio(
+    input: String,
+    start: Option<TimePoint>,
+    end: Option<TimePoint>,
+    options: Option<AudioOptions>,
+    notes: Option<String>,
+    output_dir: Option<String>
+) -> AppResult<AudioResult> {
+    audio::extract_audio(
+        &input, start.as_ref(), end.as_ref(), &options.unwrap_or_default(), notes.as_deref(), output_dir.as_deref()
+    )
+}
+
+#[tauri::command]
+fn get_subtitle_streams(path: String) -> AppResult<Vec<SubtitleStream>> {
+    media::get_subtitle_streams(&path)
+}
+
+#[tauri::command]
+fn extract_subtitles(
+    input: String,
+    start: Option<TimePoint>,
+    end: Option<TimePoint>,
+    track: Option<u32>,
+    format: SubtitleFormat,
+    notes: Option<String>,
+    output_dir: Option<String>
+) -> AppResult<SubtitleResult> {
+    subtitle::extract_subtitles(
+        &input, start.as_ref(), end.as_ref(), track, format, notes.as_deref(), output_dir.as_deref()
+    )
+}
+
+#[tauri::command]
+fn burn_subtitles(
+    input: String,
+    start: TimePoint,
+    end: TimePoint,
+    source: SubtitleSource,
+    notes: Option<String>,
+    output_dir: Option<String>,
+    container: Option<String>
+) -> AppResult<BurnResult> {
+    subtitle::burn_subtitles(
+        &input, &start, &end, &source, notes.as_deref(), output_dir.as_deref(), container.as_deref()
+    )
+}
+
+#[tauri::command]
+fn measure_loudness(
+    input: String,
+    start: Option<TimePoint>,
+    end: Option<TimePoint>,
+    track: Option<u32>,
+    preset: LoudnessPreset
+) -> AppResult<LoudnessReport> {
+    loudness::measure_loudness(&input, start.as_ref(), end.as_ref(), track, preset)
+}
+
+#[tauri::command]
+fn get_settings() -> Settings {
+    settings::current()
+}
+
+#[tauri::command]
+fn update_settings(settings: Settings) -> AppResult<()> {
+    settings::update(settings)
 }
 
 fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
+        .setup(|app| {
+            if let Ok(data_dir) = app.path().app_data_dir() {
+                // 加载设置（包括界面语言）
+                settings::init_settings(&data_dir);
+
+                // 清理上次运行中断后遗留的临时输出文件
+                if let Err(e) = output::init_temp_journal(&data_dir) {
+                    eprintln!("{}", t!("app.temp_cleanup_failed", error = e));
+                }
+            }
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             check_ffmpeg,
             get_video_info,
-            cut_video
+            resolve_time,
+            cut_video,
+            export_ranges,
+            transform_video,
+            detect_crop,
+            export_speed,
+            export_overlay,
+            correct_video,
+            export_stills,
+            estimate_animation,
+            export_animation,
+            get_audio_streams,
+            extract_audio,
+            get_subtitle_streams,
+            extract_subtitles,
+            burn_subtitles,
+            measure_loudness,
+            get_settings,
+            update_settings
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
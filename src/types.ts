@@ -5,6 +5,361 @@ export interface VideoInfo {
   fps: number
   codec: string
   format: string
+  frame_rate: string  // 精确帧率，例如 "30000/1001"
+  time_base: string   // 视频流时间基，例如 "1/30000"
+  start_timecode: string | null  // 源起始时间码，例如 "01:00:00;00"
+  rotation: number  // 显示矩阵的逆时针旋转角度 (0 / 90 / 180 / 270)
+}
+
+/**
+ * 时间点：秒数，或字符串形式的秒数 ("12.5")、[HH:]MM:SS[.sss]、
+ * 帧号 ("1234f")、时间码 ("00:01:02:03"，丢帧用 "00:01:02;03")
+ */
+export type TimePoint = number | string
+
+export interface ResolvedTime {
+  seconds: number
+  frame: number | null
+  timecode: string | null
+}
+
+export interface StreamCounts {
+  video: number
+  audio: number
+  subtitle: number
+}
+
+export interface VerificationReport {
+  passed: boolean
+  requested_duration: number
+  actual_duration: number
+  keyframe_offset: number
+  duration_ok: boolean
+  expected_streams: StreamCounts
+  actual_streams: StreamCounts
+  streams_ok: boolean
+  decode_checked: boolean
+  decode_errors: string[]
+}
+
+export interface CutResult {
+  output_path: string
+  message: string
+  verification: VerificationReport
+  timecode: string | null  // 写入输出文件的起始时间码
+  loudness: LoudnessReport | null  // 响度标准化前的测量结果
+  audio: AudioAdjustments | null   // 应用的音频处理
+  subtitles: string[]  // 随剪辑写入的外挂字幕（已裁剪并平移）
+  skipped_subtitles: SkippedSubtitle[]  // 不是 UTF-8 或目标已存在而跳过的外挂字幕
+}
+
+export interface SkippedSubtitle {
+  path: string
+  reason: string
+}
+
+// 剪辑时的音频处理，除 drop_tracks 外都会重新编码音频（视频仍为流复制）
+export interface AudioAdjustments {
+  mute?: boolean
+  gain_db?: number | null
+  fade_in?: number | null   // 秒
+  fade_out?: number | null  // 秒
+  downmix?: 'stereo' | 'mono' | null
+  drop_tracks?: number[]    // 要移除的音频流序号
+  loudness?: LoudnessPreset | null
+}
+
+export type LoudnessPreset = 'streaming' | 'podcast' | 'broadcast'  // -14 / -16 / -23 LUFS
+
+export interface LoudnessReport {
+  preset: LoudnessPreset
+  input_i: number       // 综合响度 (LUFS)
+  input_tp: number      // 真峰值 (dBTP)
+  input_lra: number     // 响度范围 (LU)
+  input_thresh: number
+  target_offset: number
+}
+
+export interface TimeRange {
+  start: TimePoint
+  end: TimePoint
+}
+
+// 多段导出的淡入淡出和转场，未设置时整体流复制
+export interface JoinOptions {
+  fade_in?: number | null              // 秒，画面和声音
+  fade_out?: number | null             // 秒，画面和声音
+  transition?: string | null           // xfade 转场类型，例如 fade、dissolve、wipeleft
+  transition_duration?: number | null  // 秒，默认 1
+}
+
+export interface JoinResult {
+  output_path: string
+  message: string
+  verification: VerificationReport
+  mode: 'copy' | 'smart' | 'reencode'  // smart 只重新编码两端和转场附近的片段
+  reencoded_duration: number           // 重新编码的源视频总时长（秒）
+}
+
+// 几何变换，按 裁剪 -> 旋转 / 翻转 -> 缩放 -> 补边 的顺序应用；只旋转时 MP4/MOV 只改写显示矩阵
+export interface CropRect {
+  width: number
+  height: number
+  x?: number | null  // 为空时居中
+  y?: number | null
+}
+
+export interface TransformOptions {
+  crop?: CropRect | null
+  width?: number | null   // 偶数，只指定一边时按比例缩放
+  height?: number | null
+  scale_mode?: 'fit' | 'fill' | 'stretch'
+  pad_color?: string | null  // fit 后补边到目标尺寸，例如 'black'、'#202020'
+  rotate?: 90 | 180 | 270 | null  // 顺时针
+  flip?: 'horizontal' | 'vertical' | 'both' | null
+}
+
+export interface TransformResult {
+  output_path: string
+  message: string
+  verification: VerificationReport
+  lossless: boolean  // 只改写显示矩阵，未重新编码
+  expected_width: number
+  expected_height: number
+  width: number      // 探测到的输出显示尺寸
+  height: number
+  dimensions_ok: boolean
+}
+
+// 黑边检测结果，crop 可直接作为 TransformOptions.crop 去黑边
+export interface CropDetection {
+  crop: CropRect | null  // 没有黑边时为空
+  confidence: number     // 与该区域一致的采样比例 (0-1)
+  samples: number
+  source_width: number
+  source_height: number
+}
+
+// 变速和倒放，总是重新编码
+export interface SpeedOptions {
+  speed?: number | null  // 0.1-100，默认 1；大于 1 为快放，小于 1 为慢放
+  reverse?: boolean      // 倒放，按内存预算分段处理
+  drop_audio?: boolean   // 去掉音频，否则用 atempo 保持音调变速
+}
+
+export interface SpeedResult {
+  output_path: string
+  message: string
+  verification: VerificationReport  // 按变速后的时长校验
+  speed: number
+  reversed: boolean
+  expected_duration: number
+  chunks: number  // 倒放的分段数
+}
+
+export interface SubtitleStream {
+  index: number  // 在文件中的流序号
+  codec: string
+  language: string | null
+  title: string | null
+}
+
+export interface SubtitleResult {
+  output_path: string
+  message: string
+  cues: number  // 写入的字幕条数
+}
+
+// 烧录内嵌字幕流（流序号）或外部 SRT / ASS 文件
+export type SubtitleSource = { track: number } | { file: string }
+
+export interface BurnResult {
+  output_path: string
+  message: string
+  verification: VerificationReport
+}
+
+export type OverlayPosition = 'top-left' | 'top-right' | 'bottom-left' | 'bottom-right' | 'center'
+
+export interface ImageWatermark {
+  path: string
+  position?: OverlayPosition  // 默认 bottom-right
+  margin?: number             // 与画面边缘的距离（像素），默认 24
+  scale?: number | null       // 水印宽度占画面宽度的比例 (0-1]，为空时保持原尺寸
+  opacity?: number            // 不透明度 [0-1]，默认 1
+}
+
+// 文字模板支持 {filename}、{name}、{notes}，以及只能位于末尾的 {timecode}
+export interface TextOverlay {
+  text: string
+  position?: OverlayPosition
+  margin?: number
+  font_size?: number          // 默认 32
+  font_color?: string         // 默认 white
+  font_file?: string | null
+  background?: string | null  // 背景框颜色，例如 black@0.5
+}
+
+export interface OverlayOptions {
+  watermark?: ImageWatermark | null
+  text?: TextOverlay | null
+}
+
+export interface OverlayPreset extends OverlayOptions {
+  name: string
+}
+
+export interface OverlayResult {
+  output_path: string
+  message: string
+  verification: VerificationReport
+}
+
+// 调色和修复滤镜，按数组顺序应用（反交错只能在最前面）
+export type CorrectionFilter =
+  | { type: 'deinterlace', method?: 'yadif' | 'bwdif' }
+  | { type: 'denoise', strength?: number }  // hqdn3d 亮度空域强度 (0-30]，默认 4
+  | { type: 'lut', path: string }           // .cube 文件
+  | { type: 'eq', brightness?: number, contrast?: number, saturation?: number, gamma?: number }
+
+export interface CorrectionResult {
+  output_path: string
+  message: string
+  verification: VerificationReport
+  filter_chain: string  // 实际使用的滤镜链
+}
+
+export interface StillOptions {
+  format?: 'png' | 'jpg' | 'webp' | null
+  width?: number | null
+  height?: number | null
+}
+
+export interface StillResult {
+  output_paths: string[]  // 与请求的时间点一一对应
+  message: string
+}
+
+export interface AnimationOptions {
+  format?: 'gif' | 'webp' | null
+  fps?: number | null         // 1-50，默认 12
+  width?: number | null       // 默认 480，不超过源视频宽度
+  loop_count?: number | null  // 播放次数，0（默认）为无限循环
+  dither?: 'sierra2_4a' | 'sierra2' | 'floyd_steinberg' | 'bayer' | 'heckbert' | 'none' | null
+}
+
+export interface AnimationEstimate {
+  width: number
+  height: number
+  frames: number
+  estimated_bytes: number
+}
+
+export interface AnimationResult {
+  output_path: string
+  message: string
+  estimate: AnimationEstimate
+  width: number               // 探测到的输出宽度
+  height: number
+  frames: number              // 探测到的输出帧数
+  matches_estimate: boolean   // 输出尺寸和帧数与估算一致
+}
+
+export interface AudioStream {
+  index: number  // 在文件中的流序号
+  codec: string
+  channels: number
+  sample_rate: number
+  bit_rate: number | null
+  language: string | null
+}
+
+export interface AudioOptions {
+  format?: 'm4a' | 'ogg' | 'mp3' | 'flac' | 'wav' | 'opus' | null  // 未指定时优先流复制
+  track?: number | null    // 音频流序号，默认第一个音频流
+  bitrate?: number | null  // kbps，指定后总是重新编码
+  loudness?: LoudnessPreset | null  // 两遍响度标准化，指定后总是重新编码
+}
+
+export interface AudioResult {
+  output_path: string
+  message: string
+  codec: string
+  copied: boolean  // 是否为流复制
+  loudness: LoudnessReport | null
+}
+
+export type AppErrorCode =
+  | 'FFMPEG_ERROR'
+  | 'FFMPEG_CODEC_NOT_SUPPORTED'
+  | 'FFMPEG_NON_MONOTONIC_TIMESTAMPS'
+  | 'FFMPEG_PERMISSION_DENIED'
+  | 'FFMPEG_NO_SPACE_LEFT'
+  | 'FFMPEG_INVALID_DATA'
+  | 'FFMPEG_UNKNOWN_ENCODER'
+  | 'FFPROBE_ERROR'
+  | 'FILESYSTEM_ERROR'
+  | 'INSUFFICIENT_SPACE'
+  | 'JSON_ERROR'
+  | 'VALIDATION_ERROR'
+  | 'IO_ERROR'
+  | 'PATH_ERROR'
+
+export interface AppError {
+  code: AppErrorCode
+  params: {
+    needed_gb?: number
+    available_gb?: number
+    path?: string
+    stderr_tail?: string | null
+    suggestion?: string
+    kind?: string
+  }
+  message: string
+}
+
+export interface BinaryLocation {
+  path: string
+  source: 'setting' | 'sidecar' | 'path' | 'common_location'
+}
+
+export interface FeatureSupport {
+  h264_encoder: string | null
+  hevc_encoder: string | null
+  av1_encoder: string | null
+  loudnorm: boolean
+  vidstab: boolean
+  cropdetect: boolean
+  lut3d: boolean
+  subtitles: boolean
+  drawtext: boolean
+}
+
+export interface FFmpegCapabilities {
+  version: string
+  version_major: number | null
+  version_minor: number | null
+  configuration: string[]
+  encoders: string[]
+  decoders: string[]
+  muxers: string[]
+  filters: string[]
+  features: FeatureSupport
+}
+
+export interface FFmpegStatus {
+  installed: boolean
+  ffmpeg: BinaryLocation | null
+  ffprobe: BinaryLocation | null
+  capabilities: FFmpegCapabilities | null
+  message: string | null  // 未找到，或设置中的路径无法运行时的提示
+}
+
+export interface Settings {
+  locale: string | null
+  ffmpeg_path: string | null
+  ffprobe_path: string | null
+  overlay_presets: OverlayPreset[]  // 导出时可选用的水印 / 文字叠加预设
 }
 
 export interface AppState {
@@ -0,0 +1,27 @@
+/**
+ * 错误处理工具函数
+ * 后端命令返回结构化错误对象 { code, params, message }
+ */
+
+import type { AppError } from '../types'
+
+/**
+ * 判断是否为后端返回的结构化错误
+ * @param error 捕获到的错误
+ */
+export function isAppError(error: unknown): error is AppError {
+  return typeof error === 'object' && error !== null &&
+    'code' in error && 'message' in error
+}
+
+/**
+ * 获取可展示的错误信息
+ * @param error 捕获到的错误
+ * @returns 错误信息字符串
+ */
+export function formatError(error: unknown): string {
+  if (isAppError(error)) {
+    return error.message
+  }
+  return String(error)
+}
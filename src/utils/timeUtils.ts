@@ -3,6 +3,8 @@
  * 统一处理时间格式化和解析逻辑，避免在多个组件中重复代码
  */
 
+import type { VideoInfo } from '../types'
+
 /**
  * 将秒数格式化为时间字符串
  * @param seconds 秒数
@@ -179,4 +181,114 @@ export function formatDurationDescription(startTime: number, endTime: number): s
 export function safeParseTimeInput(input: string, defaultValue: number = 0): number {
   const parsed = parseTimeInput(input)
   return parsed !== null ? parsed : defaultValue
-}
\ No newline at end of file
+}
+/**
+ * 源时间码的换算参数，与 core 的 StreamClock 保持一致
+ */
+interface TimecodeClock {
+  rate: number        // 精确帧率
+  fps: number         // 时间码使用的整数帧率 (例如 29.97 -> 30)
+  dropped: number     // 丢帧时每分钟跳过的帧号数量
+  dropFrame: boolean  // 源时间码是否为丢帧时间码
+  origin: number      // 源起始时间码对应的帧号
+}
+
+const TIMECODE_PATTERN = /^(\d+):(\d+):(\d+)([:;])(\d+)$/
+
+/**
+ * 时间码转换为从 0 开始的帧号，格式无效时返回 null
+ */
+function timecodeToFrame(input: string, fps: number, dropped: number): number | null {
+  const match = TIMECODE_PATTERN.exec(input.trim())
+  if (!match) return null
+
+  const [h, m, s, f] = [match[1], match[2], match[3], match[5]].map(p => parseInt(p, 10))
+  const dropFrame = match[4] === ';'
+  if (m >= 60 || s >= 60 || f >= fps) return null
+  if (dropFrame && (dropped === 0 || (s === 0 && m % 10 !== 0 && f < dropped))) return null
+
+  const totalMinutes = h * 60 + m
+  let frame = (totalMinutes * 60 + s) * fps + f
+  if (dropFrame) {
+    // 除每第十分钟外，每分钟开头的帧号被跳过
+    frame -= dropped * (totalMinutes - Math.floor(totalMinutes / 10))
+  }
+  return frame
+}
+
+/**
+ * 帧号转换为时间码
+ */
+function frameToTimecode(frame: number, fps: number, dropped: number, dropFrame: boolean): string {
+  if (dropFrame) {
+    const perTenMinutes = fps * 600 - dropped * 9
+    const perMinute = fps * 60 - dropped
+    const rest = frame % perTenMinutes
+    frame += dropped * 9 * Math.floor(frame / perTenMinutes)
+    if (rest > dropped) {
+      frame += dropped * Math.floor((rest - dropped) / perMinute)
+    }
+  }
+
+  const pad = (n: number) => n.toString().padStart(2, '0')
+  const h = Math.floor(frame / (fps * 3600))
+  const m = Math.floor(frame / (fps * 60)) % 60
+  const s = Math.floor(frame / fps) % 60
+  const f = frame % fps
+  return `${pad(h)}:${pad(m)}:${pad(s)}${dropFrame ? ';' : ':'}${pad(f)}`
+}
+
+/**
+ * 由视频信息构造源时间码的换算参数，没有源时间码或帧率未知时返回 null
+ */
+function timecodeClock(info: Pick<VideoInfo, 'frame_rate' | 'start_timecode'>): TimecodeClock | null {
+  if (!info.start_timecode) return null
+
+  const [num, den = 1] = info.frame_rate.split('/').map(Number)
+  if (!(num > 0) || !(den > 0)) return null
+
+  const rate = num / den
+  const fps = Math.max(1, Math.round(rate))
+  // 只有 29.97 / 59.94 可使用丢帧时间码
+  const dropped = den === 1001 && (num === 30000 || num === 60000) ? fps / 15 : 0
+  const origin = timecodeToFrame(info.start_timecode, fps, dropped)
+  if (origin === null) return null
+
+  return { rate, fps, dropped, dropFrame: info.start_timecode.includes(';'), origin }
+}
+
+/**
+ * 将秒数（从视频开头计算）格式化为源时间码
+ * @param seconds 秒数
+ * @param info 视频信息
+ * @returns 源时间码，视频没有源时间码时返回 null
+ */
+export function formatSourceTimecode(
+  seconds: number,
+  info: Pick<VideoInfo, 'frame_rate' | 'start_timecode'>
+): string | null {
+  const clock = timecodeClock(info)
+  if (!clock) return null
+
+  // 与 core 的 seconds_to_frame 一致取所在帧，加上微小偏移避免浮点误差落到前一帧
+  const frame = Math.max(0, Math.floor(seconds * clock.rate + 1e-6))
+  return frameToTimecode(clock.origin + frame, clock.fps, clock.dropped, clock.dropFrame)
+}
+
+/**
+ * 将源时间码解析为从视频开头计算的秒数
+ * @param input 时间码字符串 (HH:MM:SS:FF 或丢帧的 HH:MM:SS;FF)
+ * @param info 视频信息
+ * @returns 秒数，视频没有源时间码、格式无效或早于源起始时间码时返回 null
+ */
+export function parseSourceTimecode(
+  input: string,
+  info: Pick<VideoInfo, 'frame_rate' | 'start_timecode'>
+): number | null {
+  const clock = timecodeClock(info)
+  if (!clock) return null
+
+  const frame = timecodeToFrame(input, clock.fps, clock.dropped)
+  if (frame === null || frame < clock.origin) return null
+  return (frame - clock.origin) / clock.rate
+}